@@ -0,0 +1,200 @@
+//! Benchmarks for reading maps.
+//!
+//! Large Ring Racers maps run to several megabytes of `TEXTMAP`, so these
+//! read a generated grid of sectors of about that size, both on its own and
+//! from a WAD.
+
+use std::fmt::Write;
+use std::io::Cursor;
+
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+
+use rrmap::format::wad::Wad;
+use rrmap::map::Map;
+
+/// How many sectors wide and tall the generated map is.
+const GRID_SIZE: usize = 128;
+
+/// How wide each sector of the generated map is, in map units.
+const CELL: usize = 64;
+
+/// Writes a `TEXTMAP` of a `size` by `size` grid of square sectors.
+///
+/// Every sector has a thing in the middle, and every wall has textures and
+/// offsets, like a real map. Inner walls are two-sided.
+fn grid_map(size: usize) -> String {
+    let mut out = String::new();
+    let vertex = |x: usize, y: usize| y * (size + 1) + x;
+    let sector = |x: usize, y: usize| y * size + x;
+
+    writeln!(out, "// generated for benchmarks").unwrap();
+    writeln!(out, "namespace = \"ringracers\";").unwrap();
+    writeln!(out, "version = 1;").unwrap();
+
+    for y in 0..size {
+        for x in 0..size {
+            writeln!(
+                out,
+                "thing\n{{\nx = {}.0;\ny = {}.0;\nangle = {};\ntype = {};\narg0 = {};\n}}",
+                x * CELL + CELL / 2,
+                y * CELL + CELL / 2,
+                (x * 45) % 360,
+                2000 + (x + y) % 4,
+                x,
+            )
+            .unwrap();
+        }
+    }
+
+    for y in 0..=size {
+        for x in 0..=size {
+            writeln!(
+                out,
+                "vertex\n{{\nx = {}.0;\ny = {}.0;\n}}",
+                x * CELL,
+                y * CELL
+            )
+            .unwrap();
+        }
+    }
+
+    // each line is (v1, v2, front sector, back sector)
+    let mut lines = Vec::new();
+    for y in 0..=size {
+        for x in 0..size {
+            let below = (y > 0).then(|| sector(x, y - 1));
+            let above = (y < size).then(|| sector(x, y));
+            lines.push((
+                vertex(x, y),
+                vertex(x + 1, y),
+                above.or(below),
+                above.and(below),
+            ));
+        }
+    }
+    for x in 0..=size {
+        for y in 0..size {
+            let left = (x > 0).then(|| sector(x - 1, y));
+            let right = (x < size).then(|| sector(x, y));
+            lines.push((
+                vertex(x, y + 1),
+                vertex(x, y),
+                right.or(left),
+                right.and(left),
+            ));
+        }
+    }
+
+    let mut sides = Vec::new();
+    for (idx, &(v1, v2, front, back)) in lines.iter().enumerate() {
+        let side_front = sides.len();
+        sides.extend(front);
+        let side_back = back.map(|_| sides.len());
+        sides.extend(back);
+
+        write!(
+            out,
+            "linedef\n{{\nv1 = {};\nv2 = {};\nsidefront = {};\n",
+            v1, v2, side_front
+        )
+        .unwrap();
+        if let Some(side_back) = side_back {
+            write!(out, "sideback = {};\ntwosided = true;\n", side_back).unwrap();
+        } else {
+            writeln!(out, "blocking = true;").unwrap();
+        }
+        if idx % 16 == 0 {
+            write!(out, "special = 2001;\narg0 = {};\n", idx).unwrap();
+        }
+        writeln!(out, "}}").unwrap();
+    }
+
+    for (idx, sector) in sides.iter().enumerate() {
+        writeln!(
+            out,
+            "sidedef\n{{\noffsetx = {};\nsector = {};\ntexturemiddle = \"GFZWALL{}\";\ncomment = \"wall \\\"{}\\\"\";\n}}",
+            idx % 64,
+            sector,
+            idx % 8,
+            idx,
+        )
+        .unwrap();
+    }
+
+    for idx in 0..size * size {
+        writeln!(
+            out,
+            "sector\n{{\nheightfloor = {};\nheightceiling = 256;\ntexturefloor = \"GFZFLR01\";\ntextureceiling = \"F_SKY1\";\nlightlevel = 255;\nspecial = {};\n}}",
+            (idx % 8) * 16,
+            idx % 3,
+        )
+        .unwrap();
+    }
+
+    out
+}
+
+/// Puts `textmap` in a WAD as the map `MAP01`.
+fn wad_with(textmap: &str) -> Vec<u8> {
+    let lumps: [(&str, &[u8]); 3] = [
+        ("MAP01", b""),
+        ("TEXTMAP", textmap.as_bytes()),
+        ("ENDMAP", b""),
+    ];
+    let data_len = lumps.iter().map(|(_, data)| data.len()).sum::<usize>();
+
+    let mut out = Vec::new();
+    out.extend(b"PWAD");
+    out.extend((lumps.len() as i32).to_le_bytes());
+    out.extend(((12 + data_len) as i32).to_le_bytes());
+
+    for (_, data) in lumps.iter() {
+        out.extend(*data);
+    }
+
+    let mut pos = 12;
+    for (name, data) in lumps.iter() {
+        out.extend((pos as i32).to_le_bytes());
+        out.extend((data.len() as i32).to_le_bytes());
+
+        let mut padded = [0u8; 8];
+        padded[..name.len()].copy_from_slice(name.as_bytes());
+        out.extend(padded);
+
+        pos += data.len();
+    }
+
+    out
+}
+
+fn parse(c: &mut Criterion) {
+    let textmap = grid_map(GRID_SIZE);
+    let wad = wad_with(&textmap);
+
+    let mut group = c.benchmark_group("parse");
+    group.sample_size(10);
+    group.throughput(Throughput::Bytes(textmap.len() as u64));
+
+    group.bench_function("textmap", |b| b.iter(|| Map::from_str(&textmap).unwrap()));
+    group.bench_function("textmap_preserving", |b| {
+        b.iter(|| Map::from_str_preserving(&textmap).unwrap())
+    });
+    group.bench_function("wad", |b| {
+        b.iter(|| {
+            let wad = Wad::from_reader(Cursor::new(&wad)).unwrap();
+            let textmap = wad.map_lump("MAP01", "TEXTMAP").unwrap();
+            Map::from_str(std::str::from_utf8(textmap.data()).unwrap()).unwrap()
+        })
+    });
+
+    group.finish();
+}
+
+fn write(c: &mut Criterion) {
+    let map = Map::from_str(&grid_map(GRID_SIZE)).unwrap();
+
+    c.bench_function("write", |b| b.iter(|| map.to_string()));
+}
+
+criterion_group!(benches, parse, write);
+criterion_main!(benches);
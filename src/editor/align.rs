@@ -0,0 +1,86 @@
+//! Texture offset tools.
+
+use std::collections::BTreeSet;
+
+use bevy::prelude::*;
+
+use super::nudge::NudgeTarget;
+use super::{Cursor, Editor, Selection};
+use crate::map::{self, Map};
+
+/// How far offsets are nudged with shift held.
+const NUDGE_FAST: i32 = 8;
+
+/// The sidedefs of the selection.
+///
+/// These are the selected sidedefs and both sides of the selected linedefs.
+pub fn selected_sidedefs(map: &Map, selection: &map::Selection) -> BTreeSet<usize> {
+    let mut sidedefs = selection.sidedefs.clone();
+
+    for linedef in selection
+        .linedefs
+        .iter()
+        .filter_map(|&idx| map.linedefs.get(idx))
+    {
+        sidedefs.insert(linedef.side_front as usize);
+        sidedefs.extend(linedef.side_back.map(|side| side as usize));
+    }
+
+    sidedefs.retain(|&idx| idx < map.sidedefs.len());
+    sidedefs
+}
+
+/// Aligns textures starting from the selected walls, see
+/// [`Map::align_textures`].
+pub fn align_textures(map: &mut Map, selection: &mut map::Selection) {
+    for sidedef in selected_sidedefs(map, selection) {
+        map.align_textures(sidedef);
+    }
+}
+
+/// Nudges the texture offsets of the selected walls with alt and the arrow
+/// keys, see [`NudgeTarget`].
+pub fn nudge_offsets(
+    keys: Res<ButtonInput<KeyCode>>,
+    cursor: Res<Cursor>,
+    mut editors: Query<(&mut Editor, &Selection)>,
+) {
+    if !cursor.hovered || NudgeTarget::from_keys(&keys) != Some(NudgeTarget::Offsets) {
+        return;
+    }
+
+    let step = if keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]) {
+        NUDGE_FAST
+    } else {
+        1
+    };
+    let (dx, dy) = [
+        (KeyCode::ArrowLeft, (-step, 0)),
+        (KeyCode::ArrowRight, (step, 0)),
+        (KeyCode::ArrowUp, (0, -step)),
+        (KeyCode::ArrowDown, (0, step)),
+    ]
+    .into_iter()
+    .filter(|(key, _)| keys.just_pressed(*key))
+    .fold((0, 0), |(x, y), (_, (dx, dy))| (x + dx, y + dy));
+
+    if (dx, dy) == (0, 0) {
+        return;
+    }
+
+    let Ok((mut editor, selection)) = editors.get_single_mut() else {
+        return;
+    };
+
+    let sidedefs = selected_sidedefs(editor.map(), selection);
+    if sidedefs.is_empty() {
+        return;
+    }
+
+    let map = editor.map_mut();
+
+    for idx in sidedefs {
+        map.sidedefs[idx].offset_x += dx;
+        map.sidedefs[idx].offset_y += dy;
+    }
+}
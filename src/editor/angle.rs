@@ -0,0 +1,154 @@
+//! Turning things around.
+//!
+//! Every thing is drawn with an arrow showing its angle. The tip of the arrow
+//! of a selected thing can be dragged to rotate it, and selected things can
+//! all be turned to face a clicked point.
+
+use bevy::prelude::*;
+use bevy_prototype_lyon::shapes;
+
+use super::select::{PICK_DISTANCE, THING_RADIUS};
+use super::{Cursor, Editor, EditorCamera, Selection};
+use crate::map::things::angle_toward;
+use crate::map::Thing;
+
+/// How much the angle snaps to with shift held, in degrees.
+pub const SNAP_ANGLE: i32 = 45;
+
+/// How long the arrowhead of things is, in map units.
+const ARROWHEAD_LENGTH: f32 = THING_RADIUS / 2.0;
+
+/// The state of the angle tools.
+#[derive(Resource, Debug, Default)]
+pub struct ThingAngles {
+    /// The thing whose arrow is being dragged.
+    pub rotating: Option<usize>,
+    /// Whether the next click turns the selected things to face it.
+    pub facing: bool,
+}
+
+/// The direction a thing at `angle` faces, as a unit vector.
+pub fn direction(angle: i32) -> Vec2 {
+    Vec2::from_angle((angle as f32).to_radians())
+}
+
+/// Where the tip of the arrow of `thing` is.
+pub fn arrow_tip(thing: &Thing) -> Vec2 {
+    Vec2::new(thing.x, thing.y) + direction(thing.angle) * THING_RADIUS
+}
+
+/// The lines of the arrow of `thing`, from its center to the edge of its
+/// circle.
+pub fn arrow(thing: &Thing) -> [shapes::Line; 3] {
+    let tip = arrow_tip(thing);
+    let barb = |turn: i32| tip + direction(thing.angle + turn) * ARROWHEAD_LENGTH;
+
+    [
+        shapes::Line(Vec2::new(thing.x, thing.y), tip),
+        shapes::Line(tip, barb(150)),
+        shapes::Line(tip, barb(-150)),
+    ]
+}
+
+/// Rotates selected things by dragging the tips of their arrows.
+///
+/// Holding shift snaps the angle to [`SNAP_ANGLE`]. The click is taken from
+/// the select mode, so dragging an arrow doesn't box select.
+pub fn rotate_things(
+    mut mouse: ResMut<ButtonInput<MouseButton>>,
+    keys: Res<ButtonInput<KeyCode>>,
+    cursor: Res<Cursor>,
+    mut angles: ResMut<ThingAngles>,
+    cameras: Query<&OrthographicProjection, With<EditorCamera>>,
+    mut editors: Query<(&mut Editor, &Selection)>,
+) {
+    let (Ok((mut editor, selection)), Ok(projection)) =
+        (editors.get_single_mut(), cameras.get_single())
+    else {
+        return;
+    };
+
+    if angles.facing {
+        return;
+    }
+
+    if let Some(position) = cursor
+        .position
+        .filter(|_| mouse.just_pressed(MouseButton::Left))
+    {
+        let pick_distance = PICK_DISTANCE * projection.scale;
+        let grabbed = selection.things.iter().copied().find(|&idx| {
+            editor
+                .map()
+                .things
+                .get(idx)
+                .is_some_and(|thing| arrow_tip(thing).distance(position) <= pick_distance)
+        });
+
+        if grabbed.is_some() {
+            mouse.clear_just_pressed(MouseButton::Left);
+            angles.rotating = grabbed;
+        }
+    }
+
+    let Some(idx) = angles.rotating else {
+        return;
+    };
+
+    if !mouse.pressed(MouseButton::Left) {
+        mouse.clear_just_released(MouseButton::Left);
+        angles.rotating = None;
+        return;
+    }
+
+    let (Some(position), Some(thing)) = (cursor.position, editor.map().things.get(idx)) else {
+        return;
+    };
+
+    let mut angle = angle_toward(position.x - thing.x, position.y - thing.y);
+    if keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]) {
+        angle = ((angle as f32 / SNAP_ANGLE as f32).round() as i32 * SNAP_ANGLE).rem_euclid(360);
+    }
+
+    if thing.angle != angle {
+        editor.map_mut().things[idx].angle = angle;
+    }
+}
+
+/// Turns the selected things to face the next click, once
+/// [`ThingAngles::facing`] is set.
+///
+/// Escape cancels.
+pub fn face_point(
+    mut mouse: ResMut<ButtonInput<MouseButton>>,
+    keys: Res<ButtonInput<KeyCode>>,
+    cursor: Res<Cursor>,
+    mut angles: ResMut<ThingAngles>,
+    mut editors: Query<(&mut Editor, &Selection)>,
+) {
+    if !angles.facing {
+        return;
+    }
+
+    if keys.just_pressed(KeyCode::Escape) {
+        angles.facing = false;
+        return;
+    }
+
+    let Some(position) = cursor
+        .position
+        .filter(|_| mouse.just_pressed(MouseButton::Left))
+    else {
+        return;
+    };
+    mouse.clear_just_pressed(MouseButton::Left);
+    angles.facing = false;
+
+    let Ok((mut editor, selection)) = editors.get_single_mut() else {
+        return;
+    };
+
+    editor
+        .map_mut()
+        .face_point(&selection.things, position.x, position.y);
+}
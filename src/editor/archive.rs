@@ -0,0 +1,501 @@
+//! The archive the map was opened from.
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use bevy::ecs::system::RunSystemOnce;
+use bevy::prelude::*;
+
+use super::session::{Error, MapFile, Session, OPEN_TASK};
+use super::tasks::BackgroundTasks;
+use super::{Editor, EditorBundle};
+use crate::format::pk3::is_pk3;
+use crate::format::wad::{Wad, WadType};
+use crate::map::namespace::Namespace;
+use crate::map::{Diagnostic, Map};
+
+/// Opens another map of the [`Archive`], replacing the open map.
+///
+/// Changes to the open map are kept in the archive, but not saved.
+#[derive(Event, Clone, Debug)]
+pub struct OpenArchiveMap(pub String);
+
+/// The WAD the open map was read from.
+///
+/// This is kept around so the rest of the WAD can be looked at, and saved
+/// back with the map.
+#[derive(Resource, Debug)]
+pub struct Archive {
+    path: PathBuf,
+    pub wad: Wad,
+    /// The name of the open map.
+    pub map: Option<String>,
+    /// The lump being looked at in the archive tabs, by index.
+    pub selected: Option<usize>,
+    /// What happened with the last archive operation, to show the user.
+    pub status: Option<String>,
+    /// What was skipped when reading the open map.
+    pub diagnostics: Vec<Diagnostic>,
+    /// Whether the archive can't be saved over.
+    read_only: bool,
+    /// Whether the archive is being saved in the background.
+    saving: bool,
+}
+
+/// A map read from an [`Archive`], with what was skipped reading it.
+pub type ReadMap = Result<(Map, Vec<Diagnostic>), Error>;
+
+impl Archive {
+    /// Creates a new `Archive` for `wad`, read from `path`.
+    ///
+    /// IWADs and files that can't be written to are opened read-only, so game
+    /// data isn't changed by accident. They can still be saved to a new PWAD
+    /// with [`Archive::save_as`]. PK3s are always read-only, since they are
+    /// only read, see [`read_pk3`](crate::format::pk3::read_pk3).
+    pub fn new(path: impl Into<PathBuf>, wad: Wad) -> Archive {
+        let path = path.into();
+        let read_only = wad.header().ident == WadType::Iwad
+            || is_pk3(&path)
+            || OpenOptions::new().write(true).open(&path).is_err();
+
+        Archive {
+            path,
+            read_only,
+            wad,
+            map: None,
+            selected: None,
+            status: None,
+            diagnostics: Vec::new(),
+            saving: false,
+        }
+    }
+
+    /// Where the archive was read from.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Whether the archive can only be saved with [`Archive::save_as`].
+    pub fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+
+    /// Whether the archive is being saved.
+    ///
+    /// The archive shouldn't be changed until it is done, since the changes
+    /// would be lost when the saved archive is put back.
+    pub fn is_saving(&self) -> bool {
+        self.saving
+    }
+
+    /// Reads the map `name`.
+    ///
+    /// Damaged maps are read as far as they can be, see
+    /// [`Map::from_str_lenient`].
+    pub fn read_map(&self, name: &str) -> ReadMap {
+        let textmap = self.wad.map_lump(name, "TEXTMAP").ok_or(Error::NoTextMap)?;
+
+        Ok(read_textmap(textmap.data()))
+    }
+
+    /// Puts `map` in the archive as the open map.
+    ///
+    /// The `TEXTMAP` is only touched if the map changed.
+    pub fn store(&mut self, map: &Map) -> Result<(), Error> {
+        let name = self.map.as_deref().ok_or(Error::NoTextMap)?;
+        let mut output = String::new();
+        map.write(&mut output)?;
+
+        let idx = self
+            .wad
+            .map_lump_index(name, "TEXTMAP")
+            .ok_or(Error::NoTextMap)?;
+        let mut textmap = self.wad.lump_at_mut(idx).ok_or(Error::NoTextMap)?;
+
+        if textmap.data() != output.as_bytes() {
+            textmap.set_data(output);
+        }
+
+        Ok(())
+    }
+
+    /// Puts `map` in the archive and saves the archive's changes to its file.
+    pub fn save(&mut self, map: &Map) -> Result<(), Error> {
+        if self.read_only {
+            return Err(Error::ReadOnly);
+        }
+
+        self.store(map)?;
+
+        let mut file = OpenOptions::new().write(true).open(&self.path)?;
+        self.wad.write_changes(&mut file)?;
+
+        Ok(())
+    }
+
+    /// Puts `map` in the archive and writes all of it to a new PWAD at `path`.
+    ///
+    /// The archive is read back from `path`, and is saved there from then on.
+    /// Archives read from PK3s keep the full names of their files, so they
+    /// can't be saved if any of these are longer than a WAD allows.
+    pub fn save_as(&mut self, path: impl Into<PathBuf>, map: &Map) -> Result<(), Error> {
+        let path = path.into();
+
+        // save as can't be used to get around read-only
+        if self.read_only
+            && path.exists()
+            && fs::canonicalize(&path).ok() == fs::canonicalize(&self.path).ok()
+        {
+            return Err(Error::ReadOnly);
+        }
+
+        // checked first, so nothing is written over
+        if let Some(lump) = self.wad.lumps().find(|lump| lump.name().len() > 8) {
+            return Err(Error::LumpNameTooLong(lump.name().to_string()));
+        }
+
+        self.store(map)?;
+
+        // the new file is a PWAD, but the archive only becomes one once it is
+        // read back from it
+        let ident = self.wad.header().ident;
+        self.wad.set_wad_type(WadType::Pwad);
+        let written = write_wad(&self.wad, &path);
+        self.wad.set_wad_type(ident);
+        written?;
+
+        // lumps are laid out differently in the new file
+        self.wad = Wad::from_reader(BufReader::new(File::open(&path)?))?;
+        self.path = path;
+        self.read_only = false;
+
+        Ok(())
+    }
+}
+
+/// Writes all of `wad` to a new file at `path`.
+fn write_wad(wad: &Wad, path: &Path) -> Result<(), Error> {
+    let mut file = BufWriter::new(File::create(path)?);
+    wad.write(&mut file)?;
+    file.flush()?;
+
+    Ok(())
+}
+
+/// Saves the open map and its [`Archive`] in the background.
+pub fn save_archive(world: &mut World) {
+    let mut editors = world.query::<&Editor>();
+    let Ok(editor) = editors.get_single(world) else {
+        return;
+    };
+    let map = editor.map().clone();
+    let Some(path) = world.get_resource::<Archive>().map(|a| a.path().to_owned()) else {
+        return;
+    };
+
+    spawn_save(
+        world,
+        path,
+        move |archive| {
+            archive.save(&map)?;
+            let namespace = Namespace::from_name(&map.namespace);
+            Ok(saved_status(archive.path(), &map, namespace))
+        },
+        |_| (),
+    );
+}
+
+/// Saves the open map and its [`Archive`] to a new file at `path`, in
+/// `namespace`, in the background.
+///
+/// Once it is saved, the map is edited from the new file, in the new
+/// namespace.
+pub fn save_archive_as(world: &mut World, path: PathBuf, namespace: Namespace) {
+    let mut editors = world.query::<&Editor>();
+    let Ok(editor) = editors.get_single(world) else {
+        return;
+    };
+    let mut map = editor.map().clone();
+    map.namespace = namespace.name().to_string();
+
+    let target = path.clone();
+    spawn_save(
+        world,
+        path.clone(),
+        move |archive| {
+            archive.save_as(&target, &map)?;
+            Ok(saved_status(&target, &map, Some(namespace)))
+        },
+        move |world| {
+            let mut editors = world.query::<(&mut Editor, &mut MapFile)>();
+            if let Ok((mut editor, mut file)) = editors.get_single_mut(world) {
+                if Namespace::from_name(&editor.map().namespace) != Some(namespace) {
+                    editor.map_mut().namespace = namespace.name().to_string();
+                }
+                file.0 = path.clone();
+            }
+            world.resource_mut::<Session>().add_recent(path);
+        },
+    );
+}
+
+/// Saves a copy of the [`Archive`] to `path` with `save` on the task pool.
+///
+/// `save` returns what to tell the user. The saved copy replaces the file of
+/// the archive when it is done, and `then` is run if it succeeded. Nothing
+/// happens if the archive is already being saved.
+fn spawn_save<S, T>(world: &mut World, path: PathBuf, save: S, then: T)
+where
+    S: FnOnce(&mut Archive) -> Result<String, Error> + Send + 'static,
+    T: FnOnce(&mut World) + Send + 'static,
+{
+    let Some(mut archive) = world.get_resource_mut::<Archive>() else {
+        return;
+    };
+    if archive.saving {
+        return;
+    }
+    archive.saving = true;
+
+    let mut copy = Archive {
+        path: archive.path.clone(),
+        wad: archive.wad.clone(),
+        map: archive.map.clone(),
+        selected: None,
+        status: None,
+        diagnostics: Vec::new(),
+        read_only: archive.read_only,
+        saving: false,
+    };
+    let from = copy.path.clone();
+
+    world.resource_mut::<BackgroundTasks>().spawn(
+        format!("Saving {}", path.display()),
+        move |progress| {
+            progress.set_message("Writing wad file");
+            let saved = save(&mut copy).map(|status| (copy, status));
+            progress.set_fraction(1.0);
+
+            Box::new(move |world: &mut World| {
+                let Some(mut archive) = world.get_resource_mut::<Archive>() else {
+                    return;
+                };
+                // another archive was opened while saving
+                if !archive.saving || archive.path != from {
+                    return;
+                }
+                archive.saving = false;
+
+                match saved {
+                    Ok((copy, status)) => {
+                        archive.path = copy.path;
+                        archive.wad = copy.wad;
+                        archive.read_only = copy.read_only;
+                        archive.status = Some(status);
+                        then(world);
+                    }
+                    Err(err) => {
+                        error!("failed to save {}: {}", path.display(), err);
+                        archive.status = Some(format!("Failed to save: {}", err));
+                    }
+                }
+            })
+        },
+    );
+}
+
+/// Describes a successful save of `map` to `path`, written in `namespace`.
+///
+/// Maps are saved with any fields the namespace doesn't have, but these are
+/// likely ignored by the game, so they are warned about.
+fn saved_status(path: &Path, map: &Map, namespace: Option<Namespace>) -> String {
+    let invalid = namespace
+        .map(|namespace| (namespace, map.invalid_fields(namespace).len()))
+        .filter(|&(_, count)| count > 0);
+
+    match invalid {
+        Some((namespace, count)) => {
+            warn!(
+                "saved {} with {} fields not in the {} namespace",
+                path.display(),
+                count,
+                namespace
+            );
+            format!(
+                "Saved {}, but {} fields aren't in the {} namespace, see Problems",
+                path.display(),
+                count,
+                namespace
+            )
+        }
+        None => format!("Saved {}", path.display()),
+    }
+}
+
+/// Reads a map from the contents of its `TEXTMAP` lump.
+fn read_textmap(data: &[u8]) -> (Map, Vec<Diagnostic>) {
+    let text = String::from_utf8_lossy(data);
+
+    match Map::from_str_preserving(&text) {
+        Ok(map) => (map, Vec::new()),
+        // only maps that read cleanly keep their original text
+        Err(_) => Map::from_str_lenient(&text),
+    }
+}
+
+/// Starts opening maps of the [`Archive`] on [`OpenArchiveMap`].
+pub fn open_archive_map(
+    mut events: EventReader<OpenArchiveMap>,
+    archive: Option<ResMut<Archive>>,
+    mut tasks: ResMut<BackgroundTasks>,
+) {
+    let Some(OpenArchiveMap(name)) = events.read().last() else {
+        return;
+    };
+    let Some(mut archive) = archive else {
+        return;
+    };
+    if archive.saving {
+        archive.status = Some("Wait for the save to finish to open another map".into());
+        return;
+    }
+
+    let name = name.clone();
+    let textmap = archive
+        .wad
+        .map_lump(&name, "TEXTMAP")
+        .map(|lump| lump.data().to_vec());
+
+    tasks.spawn_latest(OPEN_TASK, format!("Opening {}", name), move |progress| {
+        progress.set_message(format!("Reading {}", name));
+        let map = textmap
+            .ok_or(Error::NoTextMap)
+            .map(|data| read_textmap(&data));
+
+        Box::new(move |world: &mut World| {
+            world.run_system_once_with((name, map), archive_map_loaded)
+        })
+    });
+}
+
+/// Replaces the open map with a map read by [`open_archive_map`].
+fn archive_map_loaded(
+    In((name, map)): In<(String, ReadMap)>,
+    mut commands: Commands,
+    archive: Option<ResMut<Archive>>,
+    editors: Query<(Entity, &Editor, &MapFile)>,
+) {
+    let Some(mut archive) = archive else {
+        return;
+    };
+    if archive.saving {
+        archive.status = Some(format!("Failed to open {}: the wad is being saved", name));
+        return;
+    }
+
+    let (map, diagnostics) = match map {
+        Ok(map) => map,
+        Err(err) => {
+            archive.status = Some(format!("Failed to open {}: {}", name, err));
+            return;
+        }
+    };
+
+    // keep the changes to the old map around
+    if let Ok((_, editor, _)) = editors.get_single() {
+        if let Err(err) = archive.store(editor.map()) {
+            archive.status = Some(format!("Failed to keep changes: {}", err));
+            return;
+        }
+    }
+
+    for (entity, _, _) in editors.iter() {
+        commands.entity(entity).despawn();
+    }
+    commands.spawn((EditorBundle::new(map), MapFile(archive.path().to_owned())));
+
+    archive.map = Some(name);
+    archive.status = None;
+    archive.diagnostics = diagnostics;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::io::Cursor;
+
+    #[test]
+    fn save_iwad_as_pwad() {
+        let textmap = "namespace = \"ringracers\";\nversion = 1;\n";
+        let mut bytes = b"IWAD".to_vec();
+        bytes.extend(2i32.to_le_bytes());
+        bytes.extend((12 + textmap.len() as i32).to_le_bytes());
+        bytes.extend(textmap.as_bytes());
+        for (file_pos, size, name) in [
+            (0i32, 0i32, b"MAP01\0\0\0"),
+            (12, textmap.len() as i32, b"TEXTMAP\0"),
+        ] {
+            bytes.extend(file_pos.to_le_bytes());
+            bytes.extend(size.to_le_bytes());
+            bytes.extend(name);
+        }
+
+        let dir = std::env::temp_dir();
+        let (iwad, pwad) = (
+            dir.join(format!("rrmap-iwad-{}.wad", std::process::id())),
+            dir.join(format!("rrmap-pwad-{}.wad", std::process::id())),
+        );
+        fs::write(&iwad, &bytes).unwrap();
+
+        let wad = Wad::from_reader(Cursor::new(bytes)).unwrap();
+        let mut archive = Archive::new(&iwad, wad);
+        archive.map = Some("MAP01".into());
+        assert!(archive.is_read_only());
+
+        let (mut map, _) = archive.read_map("MAP01").unwrap();
+        map.version = 2;
+        assert!(matches!(archive.save(&map), Err(Error::ReadOnly)));
+        assert!(matches!(archive.save_as(&iwad, &map), Err(Error::ReadOnly)));
+        // a failed save keeps the archive as it was
+        assert!(archive.save_as(&dir, &map).is_err());
+        assert_eq!(archive.wad.header().ident, WadType::Iwad);
+        assert!(archive.is_read_only());
+
+        archive.save_as(&pwad, &map).unwrap();
+        let saved = Wad::from_reader(BufReader::new(File::open(&pwad).unwrap())).unwrap();
+        fs::remove_file(&iwad).unwrap();
+        fs::remove_file(&pwad).unwrap();
+
+        assert!(!archive.is_read_only());
+        assert_eq!(archive.path(), pwad);
+        assert_eq!(saved.header().ident, WadType::Pwad);
+        assert_eq!(archive.read_map("MAP01").unwrap().0.version, 2);
+    }
+
+    #[test]
+    fn save_pk3_with_long_names() {
+        let mut wad = Wad::new(WadType::Pwad);
+        wad.push("MAP01", Vec::new());
+        wad.push("TEXTMAP", "namespace = \"ringracers\";\nversion = 1;\n");
+        wad.push("SOC_MAINMENU", "soc");
+
+        let dir = std::env::temp_dir();
+        let (pk3, pwad) = (
+            dir.join(format!("rrmap-pk3-{}.pk3", std::process::id())),
+            dir.join(format!("rrmap-pk3-{}.wad", std::process::id())),
+        );
+
+        let mut archive = Archive::new(&pk3, wad);
+        archive.map = Some("MAP01".into());
+        assert!(archive.is_read_only());
+
+        let (map, _) = archive.read_map("MAP01").unwrap();
+        assert!(matches!(
+            archive.save_as(&pwad, &map),
+            Err(Error::LumpNameTooLong(name)) if name == "SOC_MAINMENU"
+        ));
+        assert!(!pwad.exists());
+    }
+}
@@ -0,0 +1,85 @@
+//! Commands that change the map.
+//!
+//! Map commands are shown in the edit menu, and run on the open map and its
+//! selection.
+
+use std::borrow::Cow;
+
+use bevy::prelude::*;
+
+use super::{Editor, Selection};
+use crate::map::{self, Map};
+
+/// A function that changes the map.
+pub type MapCommandFn = dyn Fn(&mut Map, &mut map::Selection) + Send + Sync;
+
+/// A named command.
+pub struct MapCommand {
+    /// The name of the command, as shown in the UI.
+    pub name: Cow<'static, str>,
+    run: Box<MapCommandFn>,
+}
+
+impl MapCommand {
+    /// Runs the command.
+    pub fn run(&self, map: &mut Map, selection: &mut map::Selection) {
+        (self.run)(map, selection)
+    }
+}
+
+/// All map commands.
+#[derive(Resource, Default)]
+pub struct MapCommands {
+    commands: Vec<MapCommand>,
+}
+
+impl MapCommands {
+    /// Adds a command.
+    pub fn add<F>(&mut self, name: impl Into<Cow<'static, str>>, f: F)
+    where
+        F: Fn(&mut Map, &mut map::Selection) + Send + Sync + 'static,
+    {
+        self.commands.push(MapCommand {
+            name: name.into(),
+            run: Box::new(f),
+        });
+    }
+
+    /// All commands, in the order they were added.
+    pub fn iter(&self) -> impl Iterator<Item = &MapCommand> + '_ {
+        self.commands.iter()
+    }
+}
+
+/// Runs the command named `name` on the open map.
+pub fn run_map_command(world: &mut World, name: &str) {
+    world.resource_scope::<MapCommands, _>(|world, commands| {
+        let Some(command) = commands.iter().find(|command| command.name == name) else {
+            warn!("no map command named {:?}", name);
+            return;
+        };
+
+        let mut editors = world.query::<(&mut Editor, &mut Selection)>();
+        let Ok((mut editor, mut selection)) = editors.get_single_mut(world) else {
+            return;
+        };
+
+        command.run(editor.map_mut(), &mut selection);
+    });
+}
+
+/// Selects everything in the map.
+pub fn select_all(map: &mut Map, selection: &mut map::Selection) {
+    *selection = map::Selection {
+        things: (0..map.things.len()).collect(),
+        vertices: (0..map.vertices.len()).collect(),
+        linedefs: (0..map.linedefs.len()).collect(),
+        sidedefs: (0..map.sidedefs.len()).collect(),
+        sectors: (0..map.sectors.len()).collect(),
+    };
+}
+
+/// Deselects everything.
+pub fn select_none(_map: &mut Map, selection: &mut map::Selection) {
+    selection.clear();
+}
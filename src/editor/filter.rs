@@ -0,0 +1,86 @@
+//! Filtering things by [category](crate::map::things).
+
+use std::collections::BTreeSet;
+
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+
+use super::group::Groups;
+use crate::map::things::Category;
+use crate::map::{self, Map};
+
+/// Which categories of things are shown.
+///
+/// This is not saved with the map.
+#[derive(Resource, Clone, Debug, PartialEq)]
+pub struct ThingFilter {
+    /// Categories that are not drawn, and cannot be selected.
+    pub hidden: BTreeSet<Category>,
+    /// Whether things are colored by their category.
+    pub colored: bool,
+}
+
+impl ThingFilter {
+    /// The things that are hidden.
+    pub fn hidden_things(&self, map: &Map) -> BTreeSet<usize> {
+        map.things_in(&self.hidden)
+    }
+
+    /// The color of unselected things in `category`.
+    pub fn color(&self, category: Category) -> Color {
+        if !self.colored {
+            return Color::CYAN;
+        }
+
+        match category {
+            Category::PlayerStart => Color::LIME_GREEN,
+            Category::Waypoint => Color::VIOLET,
+            Category::Item => Color::YELLOW,
+            Category::Hazard => Color::RED,
+            Category::Control => Color::ORANGE,
+            Category::Decoration => Color::DARK_GRAY,
+            Category::Other => Color::CYAN,
+        }
+    }
+}
+
+impl Default for ThingFilter {
+    fn default() -> ThingFilter {
+        ThingFilter {
+            hidden: BTreeSet::new(),
+            colored: true,
+        }
+    }
+}
+
+/// The category of a thing entity.
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ThingCategory(pub Category);
+
+/// Everything that hides objects in the map.
+#[derive(SystemParam)]
+pub struct Hidden<'w> {
+    pub groups: Res<'w, Groups>,
+    pub filter: Res<'w, ThingFilter>,
+}
+
+impl Hidden<'_> {
+    /// Whether anything was hidden or shown since the system last ran.
+    pub fn is_changed(&self) -> bool {
+        self.groups.is_changed() || self.filter.is_changed()
+    }
+
+    /// The objects that are not drawn.
+    pub fn hidden(&self, map: &Map) -> map::Selection {
+        let mut hidden = map.in_groups(&self.groups.hidden);
+        hidden.things.extend(self.filter.hidden_things(map));
+        hidden
+    }
+
+    /// The objects that cannot be selected.
+    pub fn unselectable(&self, map: &Map) -> map::Selection {
+        let mut unselectable = self.groups.unselectable(map);
+        unselectable.things.extend(self.filter.hidden_things(map));
+        unselectable
+    }
+}
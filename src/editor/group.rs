@@ -0,0 +1,25 @@
+//! Hiding and locking [groups](crate::map::group).
+
+use std::collections::BTreeSet;
+
+use bevy::prelude::*;
+
+use crate::map::{self, Map};
+
+/// Which groups are hidden or locked.
+///
+/// This is not saved with the map.
+#[derive(Resource, Clone, Debug, Default, PartialEq)]
+pub struct Groups {
+    /// Groups that are not drawn, and cannot be selected.
+    pub hidden: BTreeSet<String>,
+    /// Groups that are drawn, but cannot be selected.
+    pub locked: BTreeSet<String>,
+}
+
+impl Groups {
+    /// The objects that cannot be selected.
+    pub fn unselectable(&self, map: &Map) -> map::Selection {
+        map.in_groups(&self.hidden.union(&self.locked).cloned().collect())
+    }
+}
@@ -0,0 +1,409 @@
+//! Undo and redo.
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use bevy::core::FrameCount;
+use bevy::prelude::*;
+
+use super::{Cursor, Editor};
+use crate::map::{Extras, LineDef, Map, Sector, SideDef, Thing, Vertex};
+
+/// How many changes can be undone.
+pub const MAX_HISTORY: usize = 100;
+
+/// The past and undone versions of the open map.
+///
+/// Whenever the map changes, it is compared to a copy of the map as it was,
+/// and only the objects that changed are kept, so anything that changes the
+/// map through [`Editor::map_mut`] can be undone. Changes made over
+/// consecutive frames, like dragging a value around, are undone together.
+#[derive(Resource, Default)]
+pub struct History {
+    undo: Vec<Change>,
+    redo: Vec<Change>,
+    /// The map as it is now.
+    current: Option<Arc<Map>>,
+    /// The frame the map last changed on, if the next change can be merged
+    /// with it.
+    changed_on: Option<u32>,
+}
+
+impl History {
+    /// Whether there is anything to undo.
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    /// Whether there is anything to redo.
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    /// The map as it was last recorded.
+    ///
+    /// This is shared, so it can be read elsewhere without copying the map
+    /// again.
+    pub fn snapshot(&self) -> Option<Arc<Map>> {
+        self.current.clone()
+    }
+
+    /// Puts the map of `editor` back to before the last change.
+    pub fn undo(&mut self, editor: &mut Editor) {
+        // a change made this frame might not be recorded yet
+        self.record(editor.map(), None);
+
+        if let Some(change) = self.undo.pop() {
+            let redo = self.restore(change, editor);
+            self.redo.push(redo);
+        }
+    }
+
+    /// Redoes the last undone change to the map of `editor`.
+    pub fn redo(&mut self, editor: &mut Editor) {
+        if let Some(change) = self.redo.pop() {
+            let undo = self.restore(change, editor);
+            self.undo.push(undo);
+        }
+    }
+
+    /// Applies `change` to the map of `editor`, returning the change that
+    /// reverses it.
+    fn restore(&mut self, change: Change, editor: &mut Editor) -> Change {
+        if let Some(current) = &mut self.current {
+            change.clone().apply(Arc::make_mut(current));
+        }
+        self.changed_on = None;
+
+        change.apply(editor.map_mut())
+    }
+
+    /// Records a change to the map, made on `frame`.
+    ///
+    /// Changes without a frame are never merged.
+    fn record(&mut self, map: &Map, frame: Option<u32>) {
+        let Some(current) = &mut self.current else {
+            return;
+        };
+
+        // the copy is only shared while the map is being checked
+        let Some(change) = Change::update(Arc::make_mut(current), map) else {
+            return;
+        };
+
+        let merge = matches!(
+            (self.changed_on, frame),
+            (Some(last), Some(frame)) if last.wrapping_add(1) == frame
+        );
+        self.changed_on = frame;
+
+        match self.undo.last_mut() {
+            Some(last) if merge => last.merge(change),
+            _ => {
+                self.undo.push(change);
+                self.redo.clear();
+
+                if self.undo.len() > MAX_HISTORY {
+                    self.undo.remove(0);
+                }
+            }
+        }
+    }
+}
+
+/// A change to the map, holding the objects that changed as they were
+/// before.
+#[derive(Clone, Debug)]
+struct Change {
+    header: Option<Header>,
+    things: Objects<Thing>,
+    vertices: Objects<Vertex>,
+    linedefs: Objects<LineDef>,
+    sidedefs: Objects<SideDef>,
+    sectors: Objects<Sector>,
+}
+
+impl Change {
+    /// Brings `old` up to date with `new`, returning the change that puts it
+    /// back, or `None` if nothing changed.
+    fn update(old: &mut Map, new: &Map) -> Option<Change> {
+        let change = Change {
+            header: Header::update(old, new),
+            things: Objects::update(&mut old.things, &new.things),
+            vertices: Objects::update(&mut old.vertices, &new.vertices),
+            linedefs: Objects::update(&mut old.linedefs, &new.linedefs),
+            sidedefs: Objects::update(&mut old.sidedefs, &new.sidedefs),
+            sectors: Objects::update(&mut old.sectors, &new.sectors),
+        };
+
+        let unchanged = change.header.is_none()
+            && change.things.is_unchanged(&new.things)
+            && change.vertices.is_unchanged(&new.vertices)
+            && change.linedefs.is_unchanged(&new.linedefs)
+            && change.sidedefs.is_unchanged(&new.sidedefs)
+            && change.sectors.is_unchanged(&new.sectors);
+
+        (!unchanged).then_some(change)
+    }
+
+    /// Applies the change to `map`, returning the change that reverses it.
+    fn apply(self, map: &mut Map) -> Change {
+        Change {
+            header: self.header.map(|header| header.apply(map)),
+            things: self.things.apply(&mut map.things),
+            vertices: self.vertices.apply(&mut map.vertices),
+            linedefs: self.linedefs.apply(&mut map.linedefs),
+            sidedefs: self.sidedefs.apply(&mut map.sidedefs),
+            sectors: self.sectors.apply(&mut map.sectors),
+        }
+    }
+
+    /// Merges `next`, the change made right after this one, into it.
+    fn merge(&mut self, next: Change) {
+        if self.header.is_none() {
+            self.header = next.header;
+        }
+        self.things.merge(next.things);
+        self.vertices.merge(next.vertices);
+        self.linedefs.merge(next.linedefs);
+        self.sidedefs.merge(next.sidedefs);
+        self.sectors.merge(next.sectors);
+    }
+}
+
+/// The fields of the map that aren't objects.
+#[derive(Clone, Debug)]
+struct Header {
+    namespace: String,
+    version: i32,
+    extras: Extras,
+}
+
+impl Header {
+    fn update(old: &mut Map, new: &Map) -> Option<Header> {
+        if old.namespace == new.namespace && old.version == new.version && old.extras == new.extras
+        {
+            return None;
+        }
+
+        Some(Header {
+            namespace: std::mem::replace(&mut old.namespace, new.namespace.clone()),
+            version: std::mem::replace(&mut old.version, new.version),
+            extras: std::mem::replace(&mut old.extras, new.extras.clone()),
+        })
+    }
+
+    fn apply(self, map: &mut Map) -> Header {
+        Header {
+            namespace: std::mem::replace(&mut map.namespace, self.namespace),
+            version: std::mem::replace(&mut map.version, self.version),
+            extras: std::mem::replace(&mut map.extras, self.extras),
+        }
+    }
+}
+
+/// A change to one kind of object.
+#[derive(Clone, Debug)]
+struct Objects<T> {
+    /// How many objects there were.
+    len: usize,
+    /// The objects that changed or were removed, by index.
+    changed: BTreeMap<usize, T>,
+}
+
+impl<T> Objects<T>
+where
+    T: Clone + PartialEq,
+{
+    fn update(old: &mut Vec<T>, new: &[T]) -> Objects<T> {
+        let len = old.len();
+        let mut changed = BTreeMap::new();
+
+        for (idx, (old, new)) in old.iter_mut().zip(new).enumerate() {
+            if old != new {
+                changed.insert(idx, std::mem::replace(old, new.clone()));
+            }
+        }
+
+        if len > new.len() {
+            changed.extend((new.len()..).zip(old.drain(new.len()..)));
+        } else {
+            old.extend_from_slice(&new[len..]);
+        }
+
+        Objects { len, changed }
+    }
+
+    fn is_unchanged(&self, objects: &[T]) -> bool {
+        self.changed.is_empty() && self.len == objects.len()
+    }
+
+    fn apply(self, objects: &mut Vec<T>) -> Objects<T> {
+        let len = objects.len();
+        let start = self.len.min(len);
+        let mut changed = (start..)
+            .zip(objects.drain(start..))
+            .collect::<BTreeMap<_, _>>();
+
+        // removed objects are always at the end, so they can be pushed back
+        for (idx, object) in self.changed {
+            match objects.get_mut(idx) {
+                Some(slot) => {
+                    changed.insert(idx, std::mem::replace(slot, object));
+                }
+                None => objects.push(object),
+            }
+        }
+
+        Objects { len, changed }
+    }
+
+    fn merge(&mut self, next: Objects<T>) {
+        for (idx, object) in next.changed {
+            // objects added by this change didn't exist before it
+            if idx >= self.len {
+                break;
+            }
+            self.changed.entry(idx).or_insert(object);
+        }
+    }
+}
+
+/// Keeps the [`History`] when the map changes.
+pub fn record_history(
+    frame: Res<FrameCount>,
+    mut history: ResMut<History>,
+    editors: Query<Ref<Editor>>,
+) {
+    let Ok(editor) = editors.get_single() else {
+        if history.current.is_some() {
+            *history = History::default();
+        }
+        return;
+    };
+
+    if editor.is_added() {
+        // a new map was opened
+        *history = History {
+            current: Some(Arc::new(Editor::map(&editor).clone())),
+            ..default()
+        };
+    } else if editor.is_changed() {
+        // undoing and redoing keep the copy up to date, so they record nothing
+        history.record(Editor::map(&editor), Some(frame.0));
+    }
+}
+
+/// Undoes with ctrl+z, and redoes with ctrl+y or ctrl+shift+z.
+pub fn undo_keys(
+    keys: Res<ButtonInput<KeyCode>>,
+    cursor: Res<Cursor>,
+    mut history: ResMut<History>,
+    mut editors: Query<&mut Editor>,
+) {
+    if !cursor.hovered || !keys.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight]) {
+        return;
+    }
+
+    let Ok(mut editor) = editors.get_single_mut() else {
+        return;
+    };
+
+    let shift = keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
+
+    // only touch the editor if there is something to swap in, so nothing
+    // else sees a change
+    if keys.just_pressed(KeyCode::KeyY) || (shift && keys.just_pressed(KeyCode::KeyZ)) {
+        if history.can_redo() {
+            history.redo(&mut editor);
+        }
+    } else if keys.just_pressed(KeyCode::KeyZ) && (history.can_undo() || editor.is_changed()) {
+        history.undo(&mut editor);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn history(editor: &Editor) -> History {
+        History {
+            current: Some(Arc::new(editor.map().clone())),
+            ..default()
+        }
+    }
+
+    #[test]
+    fn undo_redo() {
+        let map = Map::from_str(
+            r#"
+            namespace = "ringracers";
+            version = 1;
+            vertex { x = 0.0; y = 0.0; }
+            "#,
+        )
+        .unwrap();
+
+        let mut editor = Editor::new(map);
+        let mut history = history(&editor);
+
+        editor.map_mut().translate(8.0, 0.0);
+        history.record(editor.map(), Some(10));
+        // the next frame is part of the same change
+        editor.map_mut().translate(8.0, 0.0);
+        history.record(editor.map(), Some(11));
+        editor.map_mut().translate(0.0, 8.0);
+        history.record(editor.map(), Some(20));
+
+        history.undo(&mut editor);
+        assert_eq!(editor.map().vertices[0].x, 16.0);
+        assert_eq!(editor.map().vertices[0].y, 0.0);
+
+        history.undo(&mut editor);
+        assert_eq!(editor.map().vertices[0].x, 0.0);
+        assert!(!history.can_undo());
+
+        history.redo(&mut editor);
+        history.redo(&mut editor);
+        assert_eq!(editor.map().vertices[0].y, 8.0);
+        assert!(!history.can_redo());
+
+        // recording the restored map is not another change
+        history.record(editor.map(), Some(30));
+        assert_eq!(history.undo.len(), 2);
+    }
+
+    #[test]
+    fn undo_added_and_removed() {
+        let map = Map::from_str(
+            r#"
+            namespace = "ringracers";
+            version = 1;
+            vertex { x = 0.0; y = 0.0; }
+            vertex { x = 8.0; y = 0.0; }
+            "#,
+        )
+        .unwrap();
+
+        let mut editor = Editor::new(map);
+        let mut history = history(&editor);
+
+        let vertex = editor.map().vertices[0].clone();
+        editor.map_mut().vertices.push(vertex.clone());
+        editor.map_mut().vertices.push(vertex);
+        history.record(editor.map(), Some(10));
+        // removes one added and one old vertex
+        editor.map_mut().vertices.drain(1..3);
+        history.record(editor.map(), Some(11));
+        assert_eq!(editor.map().vertices.len(), 2);
+
+        history.undo(&mut editor);
+        assert_eq!(editor.map().vertices.len(), 2);
+        assert_eq!(editor.map().vertices[1].x, 8.0);
+
+        history.redo(&mut editor);
+        assert_eq!(editor.map().vertices.len(), 2);
+        assert_eq!(editor.map().vertices[1].x, 0.0);
+        assert_eq!(history.snapshot().unwrap().vertices, editor.map().vertices);
+    }
+}
@@ -0,0 +1,154 @@
+//! Level of detail, to keep big maps smooth when zoomed out.
+
+use std::collections::BTreeSet;
+
+use bevy::prelude::*;
+use bevy::render::mesh::PrimitiveTopology;
+use bevy::render::render_asset::RenderAssetUsages;
+
+use super::EditorCamera;
+use crate::map::query::Segment;
+use crate::map::Map;
+
+/// The camera scale past which vertices and things are hidden.
+pub const REDUCED_SCALE: f32 = 4.0;
+
+/// The camera scale past which the map is drawn as one simplified mesh.
+pub const OVERVIEW_SCALE: f32 = 16.0;
+
+/// How much the scale has to come back down before detail is restored.
+///
+/// This keeps the detail from flickering while zooming around a threshold.
+const HYSTERESIS: f32 = 0.85;
+
+/// Linedefs shorter than this many pixels are hidden with reduced detail.
+const MIN_LINE_PIXELS: f32 = 2.0;
+
+/// How many cells the longest side of the map is snapped to in the overview
+/// mesh, see [`Map::simplified_linedefs`].
+const OVERVIEW_CELLS: f32 = 1024.0;
+
+/// How much of the map is drawn.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Detail {
+    /// Everything is drawn.
+    #[default]
+    Full,
+    /// Vertices, things and tiny linedefs are hidden.
+    Reduced,
+    /// Only a simplified mesh of the linedefs is drawn.
+    Overview,
+}
+
+impl Detail {
+    /// The detail for a camera scale, coming from the `current` detail.
+    pub fn for_scale(scale: f32, current: Detail) -> Detail {
+        // thresholds are lower on the way back in
+        let threshold = |detail: Detail, scale: f32| {
+            if current >= detail {
+                scale * HYSTERESIS
+            } else {
+                scale
+            }
+        };
+
+        if scale > threshold(Detail::Overview, OVERVIEW_SCALE) {
+            Detail::Overview
+        } else if scale > threshold(Detail::Reduced, REDUCED_SCALE) {
+            Detail::Reduced
+        } else {
+            Detail::Full
+        }
+    }
+}
+
+/// The current level of detail.
+///
+/// The map entities are respawned with only what is needed when this changes.
+#[derive(Resource, Clone, Copy, Debug, Default, PartialEq)]
+pub struct Lod {
+    pub detail: Detail,
+    /// How long linedefs have to be to be drawn with [`Detail::Reduced`], in
+    /// map units.
+    pub min_length: f32,
+}
+
+impl Lod {
+    /// The level of detail for a camera scale, coming from the `current`
+    /// detail.
+    pub fn for_scale(scale: f32, current: Detail) -> Lod {
+        let detail = Detail::for_scale(scale, current);
+        let min_length = match detail {
+            // only lines that are at least a couple pixels long at this zoom
+            Detail::Reduced => MIN_LINE_PIXELS * scale,
+            Detail::Full | Detail::Overview => 0.0,
+        };
+
+        Lod { detail, min_length }
+    }
+}
+
+/// Tag for the simplified mesh drawn with [`Detail::Overview`].
+#[derive(Component, Clone, Copy, Debug, Default)]
+pub struct OverviewMesh;
+
+/// Changes the detail as the camera zooms.
+pub fn update_lod(
+    mut lod: ResMut<Lod>,
+    cameras: Query<Ref<OrthographicProjection>, With<EditorCamera>>,
+) {
+    let Ok(projection) = cameras.get_single() else {
+        return;
+    };
+
+    if projection.is_changed() {
+        let new = Lod::for_scale(projection.scale, lod.detail);
+        lod.set_if_neq(new);
+    }
+}
+
+/// Builds the simplified mesh of the linedefs of `map`, leaving out `skip`.
+pub fn overview_mesh(map: &Map, skip: &BTreeSet<usize>) -> Mesh {
+    line_mesh(map.simplified_linedefs(OVERVIEW_CELLS, skip))
+}
+
+/// Builds a mesh drawing each segment as a line.
+pub fn line_mesh(segments: impl IntoIterator<Item = Segment>) -> Mesh {
+    let positions = segments
+        .into_iter()
+        .flat_map(|segment| [segment.a, segment.b])
+        .map(|point| [point.x, point.y, 0.0])
+        .collect::<Vec<_>>();
+
+    let mut mesh = Mesh::new(PrimitiveTopology::LineList, RenderAssetUsages::RENDER_WORLD);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+    mesh
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detail_for_scale() {
+        assert_eq!(Detail::for_scale(1.0, Detail::Full), Detail::Full);
+        assert_eq!(Detail::for_scale(5.0, Detail::Full), Detail::Reduced);
+        assert_eq!(Detail::for_scale(20.0, Detail::Full), Detail::Overview);
+
+        // zooming back in just past a threshold keeps the detail
+        assert_eq!(Detail::for_scale(3.9, Detail::Reduced), Detail::Reduced);
+        assert_eq!(Detail::for_scale(15.0, Detail::Overview), Detail::Overview);
+        assert_eq!(Detail::for_scale(3.0, Detail::Overview), Detail::Full);
+    }
+
+    #[test]
+    fn min_length_follows_scale() {
+        let lod = Lod::for_scale(5.0, Detail::Full);
+        assert_eq!(lod.min_length, MIN_LINE_PIXELS * 5.0);
+
+        // zooming within the same detail still moves the length
+        let lod = Lod::for_scale(10.0, lod.detail);
+        assert_eq!(lod.detail, Detail::Reduced);
+        assert_eq!(lod.min_length, MIN_LINE_PIXELS * 10.0);
+    }
+}
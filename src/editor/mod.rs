@@ -1,10 +1,130 @@
 //! Main editor components and systems.
 
+pub mod align;
+pub mod angle;
+pub mod archive;
+pub mod command;
+pub mod filter;
+pub mod group;
+pub mod history;
+pub mod lod;
+pub mod mode;
+pub mod nodes;
+pub mod nudge;
+pub mod paint;
+pub mod prefab;
+pub mod preview;
+pub mod select;
+pub mod session;
+pub mod tasks;
+pub mod underlay;
+pub mod validate;
+pub mod view3d;
+
 use bevy::prelude::*;
-use bevy::sprite::Mesh2dHandle;
-use bevy_prototype_lyon::{draw::Stroke, entity::Path};
+use bevy::sprite::{MaterialMesh2dBundle, Mesh2dHandle};
+use bevy_prototype_lyon::{
+    draw::{Fill, Stroke},
+    entity::Path,
+    geometry::GeometryBuilder,
+    shapes,
+};
 
+use crate::map::query::LineIndex;
 use crate::map::{self, Map};
+use crate::EditorAppExt;
+
+pub use command::{MapCommand, MapCommands};
+pub use mode::{in_edit_mode, EditMode, EditModes};
+pub use select::{Cursor, Selection};
+
+/// The color of selected objects.
+pub const SELECTED_COLOR: Color = Color::ORANGE;
+
+/// Editor plugin for [`bevy`].
+///
+/// Keeps the entities of the map in sync with the [`Editor`].
+pub struct EditorPlugin;
+
+impl Plugin for EditorPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Cursor>()
+            .init_resource::<angle::ThingAngles>()
+            .init_resource::<EditModes>()
+            .init_resource::<filter::ThingFilter>()
+            .init_resource::<group::Groups>()
+            .init_resource::<history::History>()
+            .init_resource::<nudge::Grid>()
+            .init_resource::<lod::Lod>()
+            .init_resource::<MapCommands>()
+            .init_resource::<nodes::NodesOverlay>()
+            .init_resource::<paint::Painter>()
+            .init_resource::<prefab::PrefabLibrary>()
+            .init_resource::<preview::OperationPreview>()
+            .init_resource::<session::Session>()
+            .init_resource::<tasks::BackgroundTasks>()
+            .init_resource::<underlay::Underlay>()
+            .init_resource::<validate::Validation>()
+            .init_resource::<view3d::View3d>()
+            .add_event::<session::OpenMap>()
+            .add_event::<archive::OpenArchiveMap>()
+            .add_edit_mode(select::MODE, "Select")
+            .add_edit_mode(paint::MODE, "Paint")
+            .add_map_command("Select all", command::select_all)
+            .add_map_command("Select none", command::select_none)
+            .add_map_command("Align textures", align::align_textures)
+            .add_systems(
+                Startup,
+                (
+                    prefab::load_prefabs,
+                    session::load_session,
+                    view3d::setup_view3d,
+                ),
+            )
+            .add_systems(
+                Update,
+                (
+                    session::open_map,
+                    archive::open_archive_map,
+                    nodes::load_nodes,
+                    underlay::restore_underlay,
+                    underlay::remember_underlay,
+                    underlay::load_underlay,
+                    (select::update_cursor, index_map).chain(),
+                    (
+                        angle::face_point,
+                        angle::rotate_things.run_if(in_edit_mode(select::MODE)),
+                    )
+                        .chain(),
+                    select::select.run_if(in_edit_mode(select::MODE)),
+                    paint::paint.run_if(in_edit_mode(paint::MODE)),
+                    align::nudge_offsets,
+                    nudge::nudge_selection,
+                    history::undo_keys,
+                    history::record_history,
+                    lod::update_lod,
+                    sync_map,
+                    nodes::draw_nodes,
+                    underlay::draw_underlay,
+                    preview::draw_preview,
+                    select::highlight_selection,
+                )
+                    .chain(),
+            )
+            .add_systems(Update, validate::validate.after(history::record_history))
+            .add_systems(
+                Update,
+                (
+                    view3d::update_view3d,
+                    view3d::build_walls,
+                    view3d::aim_camera,
+                )
+                    .after(select::highlight_selection),
+            )
+            .add_systems(PreUpdate, tasks::poll_tasks)
+            .add_systems(Last, session::save_session);
+    }
+}
 
 /// The root editor component.
 #[derive(Component)]
@@ -13,21 +133,94 @@ pub struct Editor {
 }
 
 impl Editor {
+    /// Creates a new `Editor` editing `map`.
+    pub fn new(map: Map) -> Editor {
+        Editor { map }
+    }
+
     /// The map that the `Editor` contains.
     pub fn map(&self) -> &Map {
         &self.map
     }
 
+    /// The map that the `Editor` contains, mutably.
+    pub fn map_mut(&mut self) -> &mut Map {
+        &mut self.map
+    }
+
     /// Gets the vertex at index `i`.
     pub fn vertex(&self, idx: usize) -> Option<&map::Vertex> {
         self.map.vertices.get(idx)
     }
 }
 
+/// A bundle for spawning an editor.
+#[derive(Bundle)]
+pub struct EditorBundle {
+    pub editor: Editor,
+    pub selection: Selection,
+    pub index: MapIndex,
+}
+
+impl EditorBundle {
+    pub fn new(map: Map) -> EditorBundle {
+        EditorBundle {
+            editor: Editor::new(map),
+            selection: default(),
+            index: default(),
+        }
+    }
+}
+
+/// The [`LineIndex`] of the map of an [`Editor`].
+#[derive(Component, Clone, Debug, Default, Deref)]
+pub struct MapIndex(LineIndex);
+
+/// Rebuilds the [`MapIndex`] when the map changes.
+fn index_map(mut editors: Query<(Ref<Editor>, &mut MapIndex)>) {
+    for (editor, mut index) in editors.iter_mut() {
+        if editor.is_changed() {
+            index.0 = LineIndex::new(&editor.map);
+        }
+    }
+}
+
 /// Tag for the editor camera.
 #[derive(Component, Clone, Copy, Debug, Default)]
 pub struct EditorCamera;
 
+/// A bundle for spawning a vertex entity.
+#[derive(Bundle)]
+pub struct VertexBundle {
+    pub transform: Transform,
+    pub global_transform: GlobalTransform,
+    pub visibility: Visibility,
+    pub view_visibility: ViewVisibility,
+    pub inherited_visibility: InheritedVisibility,
+    pub path: Path,
+    pub mesh_2d_handle: Mesh2dHandle,
+    pub material_handle: Handle<ColorMaterial>,
+    pub fill: Fill,
+    pub vertex: Vertex,
+}
+
+impl VertexBundle {
+    pub fn new(idx: usize) -> VertexBundle {
+        VertexBundle {
+            transform: Transform::from_xyz(0.0, 0.0, 2.0),
+            global_transform: default(),
+            visibility: default(),
+            view_visibility: default(),
+            inherited_visibility: default(),
+            path: default(),
+            mesh_2d_handle: default(),
+            material_handle: default(),
+            fill: Fill::color(Color::GRAY),
+            vertex: Vertex(idx),
+        }
+    }
+}
+
 /// Represents a vertex.
 #[derive(Component, Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
 pub struct Vertex(pub usize);
@@ -67,3 +260,155 @@ impl LineDefBundle {
 /// Represents a linedef.
 #[derive(Component, Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
 pub struct LineDef(pub usize);
+
+/// A bundle for spawning a thing entity.
+#[derive(Bundle)]
+pub struct ThingBundle {
+    pub transform: Transform,
+    pub global_transform: GlobalTransform,
+    pub visibility: Visibility,
+    pub view_visibility: ViewVisibility,
+    pub inherited_visibility: InheritedVisibility,
+    pub path: Path,
+    pub mesh_2d_handle: Mesh2dHandle,
+    pub material_handle: Handle<ColorMaterial>,
+    pub stroke: Stroke,
+    pub thing: Thing,
+    pub category: filter::ThingCategory,
+}
+
+impl ThingBundle {
+    pub fn new(idx: usize, category: map::things::Category) -> ThingBundle {
+        ThingBundle {
+            transform: Transform::from_xyz(0.0, 0.0, 1.0),
+            global_transform: default(),
+            visibility: default(),
+            view_visibility: default(),
+            inherited_visibility: default(),
+            path: default(),
+            mesh_2d_handle: default(),
+            material_handle: default(),
+            stroke: Stroke::new(Color::CYAN, 1.0),
+            thing: Thing(idx),
+            category: filter::ThingCategory(category),
+        }
+    }
+}
+
+/// Represents a thing.
+#[derive(Component, Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Thing(pub usize);
+
+/// Filter for all entities spawned for the map.
+type MapEntity = Or<(
+    With<LineDef>,
+    With<Vertex>,
+    With<Thing>,
+    With<lod::OverviewMesh>,
+)>;
+
+/// Respawns the map entities when the map, hidden groups, thing filter or
+/// level of detail change.
+fn sync_map(
+    mut commands: Commands,
+    hiding: filter::Hidden,
+    lod: Res<lod::Lod>,
+    editors: Query<Ref<Editor>>,
+    entities: Query<Entity, MapEntity>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+) {
+    let Ok(editor) = editors.get_single() else {
+        return;
+    };
+
+    if !editor.is_changed() && !hiding.is_changed() && !lod.is_changed() {
+        return;
+    }
+
+    let hidden = hiding.hidden(&editor.map);
+
+    for entity in entities.iter() {
+        commands.entity(entity).despawn();
+    }
+
+    if lod.detail == lod::Detail::Overview {
+        commands.spawn((
+            MaterialMesh2dBundle {
+                mesh: Mesh2dHandle(meshes.add(lod::overview_mesh(&editor.map, &hidden.linedefs))),
+                material: materials.add(ColorMaterial::from(Color::WHITE)),
+                ..default()
+            },
+            lod::OverviewMesh,
+        ));
+        return;
+    }
+
+    for (idx, linedef) in editor.map.linedefs.iter().enumerate() {
+        if hidden.linedefs.contains(&idx) {
+            continue;
+        }
+
+        let (Some(v1), Some(v2)) = (
+            editor.vertex(linedef.v1 as usize),
+            editor.vertex(linedef.v2 as usize),
+        ) else {
+            continue;
+        };
+
+        if lod.detail == lod::Detail::Reduced && (v1.x - v2.x).hypot(v1.y - v2.y) < lod.min_length {
+            continue;
+        }
+
+        let line = shapes::Line(Vec2::new(v1.x, v1.y), Vec2::new(v2.x, v2.y));
+
+        commands.spawn(LineDefBundle {
+            path: GeometryBuilder::build_as(&line),
+            ..LineDefBundle::new(idx)
+        });
+    }
+
+    if lod.detail != lod::Detail::Full {
+        return;
+    }
+
+    for (idx, vertex) in editor.map.vertices.iter().enumerate() {
+        if hidden.vertices.contains(&idx) {
+            continue;
+        }
+
+        let circle = shapes::Circle {
+            radius: select::VERTEX_RADIUS,
+            center: Vec2::new(vertex.x, vertex.y),
+        };
+
+        commands.spawn(VertexBundle {
+            path: GeometryBuilder::build_as(&circle),
+            ..VertexBundle::new(idx)
+        });
+    }
+
+    for (idx, thing) in editor.map.things.iter().enumerate() {
+        if hidden.things.contains(&idx) {
+            continue;
+        }
+
+        let circle = shapes::Circle {
+            radius: select::THING_RADIUS,
+            center: Vec2::new(thing.x, thing.y),
+        };
+
+        let category = map::things::Category::of(thing.kind);
+
+        let mut path = GeometryBuilder::new().add(&circle);
+        for line in angle::arrow(thing) {
+            path = path.add(&line);
+        }
+
+        commands.spawn(ThingBundle {
+            path: path.build(),
+            stroke: Stroke::new(hiding.filter.color(category), 1.0),
+            ..ThingBundle::new(idx, category)
+        });
+    }
+}
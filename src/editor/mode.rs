@@ -0,0 +1,67 @@
+//! Edit modes.
+//!
+//! An edit mode decides what the mouse does in the viewport. Only one mode is
+//! active at a time; systems that belong to a mode should run with
+//! [`in_edit_mode`].
+
+use std::borrow::Cow;
+
+use bevy::prelude::*;
+
+/// An edit mode.
+#[derive(Clone, Debug)]
+pub struct EditMode {
+    /// A unique identifier for the mode.
+    pub id: &'static str,
+    /// The name of the mode, as shown in the UI.
+    pub name: Cow<'static, str>,
+}
+
+/// All edit modes, and which one is active.
+#[derive(Resource, Clone, Debug, Default)]
+pub struct EditModes {
+    modes: Vec<EditMode>,
+    active: Option<&'static str>,
+}
+
+impl EditModes {
+    /// Adds an edit mode.
+    ///
+    /// The first mode added becomes the active one.
+    pub fn add(&mut self, id: &'static str, name: impl Into<Cow<'static, str>>) {
+        if self.modes.iter().any(|mode| mode.id == id) {
+            warn!("edit mode {:?} added twice", id);
+            return;
+        }
+
+        self.modes.push(EditMode {
+            id,
+            name: name.into(),
+        });
+        self.active.get_or_insert(id);
+    }
+
+    /// All edit modes, in the order they were added.
+    pub fn modes(&self) -> &[EditMode] {
+        &self.modes
+    }
+
+    /// The id of the active mode.
+    pub fn active(&self) -> Option<&'static str> {
+        self.active
+    }
+
+    /// Switches to the mode `id`.
+    ///
+    /// Does nothing if there is no mode `id`.
+    pub fn set_active(&mut self, id: &str) {
+        if let Some(mode) = self.modes.iter().find(|mode| mode.id == id) {
+            self.active = Some(mode.id);
+        }
+    }
+}
+
+/// A run condition that checks if the mode `id` is active.
+pub fn in_edit_mode(id: &'static str) -> impl FnMut(Res<EditModes>) -> bool + Clone {
+    move |modes: Res<EditModes>| modes.active() == Some(id)
+}
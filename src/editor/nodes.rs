@@ -0,0 +1,264 @@
+//! Drawing the BSP nodes and blockmap of the map.
+//!
+//! These are read from the `ZNODES` and `BLOCKMAP` lumps the nodebuilder last
+//! wrote to the archive, so they go out of date as the map is edited.
+
+use bevy::ecs::component::Tick;
+use bevy::prelude::*;
+use bevy::sprite::{MaterialMesh2dBundle, Mesh2dHandle};
+
+use super::archive::Archive;
+use super::{lod, Editor};
+use crate::format::blockmap::{self, Blockmap};
+use crate::format::nodes::{self, Child, Node, Nodes};
+use crate::map::query::{Point, Segment};
+use crate::map::Map;
+
+/// The color of subsector boundaries.
+const SUBSECTOR_COLOR: Color = Color::LIME_GREEN;
+
+/// The color of partition lines.
+const SPLIT_COLOR: Color = Color::ORANGE_RED;
+
+/// The color of blockmap cells.
+const BLOCKMAP_COLOR: Color = Color::rgba(0.3, 0.3, 1.0, 0.5);
+
+/// What parts of the nodes are drawn over the map.
+#[derive(Resource, Default)]
+pub struct NodesOverlay {
+    pub show_subsectors: bool,
+    pub show_splits: bool,
+    pub show_blockmap: bool,
+    /// How many levels of partition lines are drawn.
+    pub depth: usize,
+    /// The nodes of the open map, if it has any.
+    pub nodes: Option<Result<Nodes, nodes::Error>>,
+    /// The blockmap of the open map, if it has one.
+    pub blockmap: Option<Result<Blockmap, blockmap::Error>>,
+    /// The lumps the nodes and blockmap were read from.
+    lumps: (Option<Vec<u8>>, Option<Vec<u8>>),
+    /// When the map last changed when the nodes were read.
+    read_at: Option<Tick>,
+}
+
+impl NodesOverlay {
+    /// Whether anything is drawn.
+    pub fn is_shown(&self) -> bool {
+        self.show_subsectors || self.show_splits || self.show_blockmap
+    }
+
+    /// Whether the map changed since the nodes were read.
+    pub fn is_outdated(&self, editor: &Ref<Editor>) -> bool {
+        self.read_at != Some(editor.last_changed())
+    }
+}
+
+/// Tag for the meshes of the [`NodesOverlay`].
+#[derive(Component, Clone, Copy, Debug, Default)]
+pub struct NodesMesh;
+
+/// Reads the nodes and blockmap of the open map from the [`Archive`].
+pub fn load_nodes(
+    mut overlay: ResMut<NodesOverlay>,
+    archive: Option<Res<Archive>>,
+    editors: Query<Ref<Editor>>,
+) {
+    let Some(archive) = archive.filter(|archive| archive.is_changed()) else {
+        return;
+    };
+    let Some(map) = &archive.map else {
+        return;
+    };
+
+    let lump = |name| {
+        archive
+            .wad
+            .map_lump(map, name)
+            .map(|lump| lump.data().to_vec())
+    };
+    let lumps = (lump("ZNODES"), lump("BLOCKMAP"));
+
+    // saving the map touches the archive, but not the nodes
+    if lumps == overlay.lumps {
+        return;
+    }
+
+    overlay.nodes = lumps.0.as_deref().map(Nodes::from_bytes);
+    overlay.blockmap = lumps.1.as_deref().map(Blockmap::from_bytes);
+    overlay.lumps = lumps;
+    overlay.read_at = editors
+        .get_single()
+        .ok()
+        .map(|editor| editor.last_changed());
+
+    if let Some(Ok(nodes)) = &overlay.nodes {
+        overlay.depth = overlay.depth.clamp(1, nodes.depth().max(1));
+    }
+}
+
+/// Redraws the [`NodesOverlay`] when it or the map changes.
+pub fn draw_nodes(
+    mut commands: Commands,
+    overlay: Res<NodesOverlay>,
+    editors: Query<Ref<Editor>>,
+    entities: Query<Entity, With<NodesMesh>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+) {
+    let editor = editors.get_single().ok();
+
+    if !overlay.is_changed() && !editor.as_ref().is_some_and(|editor| editor.is_changed()) {
+        return;
+    }
+
+    for entity in entities.iter() {
+        commands.entity(entity).despawn();
+    }
+
+    let Some(editor) = editor else {
+        return;
+    };
+    let map = Editor::map(&editor);
+
+    let mut layers = Vec::new();
+
+    if let Some(Ok(nodes)) = &overlay.nodes {
+        if overlay.show_subsectors {
+            layers.push((subsector_lines(map, nodes), SUBSECTOR_COLOR));
+        }
+        if overlay.show_splits {
+            layers.push((split_lines(nodes, overlay.depth), SPLIT_COLOR));
+        }
+    }
+    if let Some(Ok(blockmap)) = &overlay.blockmap {
+        if overlay.show_blockmap {
+            layers.push((blockmap_lines(blockmap), BLOCKMAP_COLOR));
+        }
+    }
+
+    for (lines, color) in layers {
+        commands.spawn((
+            MaterialMesh2dBundle {
+                mesh: Mesh2dHandle(meshes.add(lod::line_mesh(lines))),
+                material: materials.add(ColorMaterial::from(color)),
+                // over everything else
+                transform: Transform::from_xyz(0.0, 0.0, 3.0),
+                ..default()
+            },
+            NodesMesh,
+        ));
+    }
+}
+
+/// The segs around every subsector.
+fn subsector_lines(map: &Map, nodes: &Nodes) -> Vec<Segment> {
+    let vertex = |idx: usize| match idx.checked_sub(nodes.map_vertices) {
+        None => map.vertices.get(idx).map(|v| Point::new(v.x, v.y)),
+        Some(idx) => nodes.vertices.get(idx).map(|&[x, y]| Point::new(x, y)),
+    };
+
+    nodes
+        .segs
+        .iter()
+        .filter_map(|seg| Some(Segment::new(vertex(seg.v1)?, vertex(seg.v2)?)))
+        .collect()
+}
+
+/// The partition lines of the first `depth` levels of the tree.
+fn split_lines(nodes: &Nodes, depth: usize) -> Vec<Segment> {
+    let mut lines = Vec::new();
+    let mut level = Vec::from_iter(nodes.root());
+
+    for _ in 0..depth {
+        let next = level
+            .iter()
+            .filter_map(|&idx| nodes.nodes.get(idx))
+            .inspect(|node| lines.extend(clip_partition(node)))
+            .flat_map(|node| node.children)
+            .filter_map(|child| match child {
+                Child::Node(idx) => Some(idx),
+                Child::Subsector(_) => None,
+            })
+            .collect();
+
+        level = next;
+    }
+
+    lines
+}
+
+/// The partition line of `node`, cut to the bounds of its children.
+fn clip_partition(node: &Node) -> Option<Segment> {
+    if node.dx == 0.0 && node.dy == 0.0 {
+        return None;
+    }
+
+    let [[top1, bottom1, left1, right1], [top2, bottom2, left2, right2]] = node.bbox;
+    let (top, bottom) = (top1.max(top2), bottom1.min(bottom2));
+    let (left, right) = (left1.min(left2), right1.max(right2));
+
+    let (mut enter, mut exit) = (f32::NEG_INFINITY, f32::INFINITY);
+
+    for (p, q) in [
+        (-node.dx, node.x - left),
+        (node.dx, right - node.x),
+        (-node.dy, node.y - bottom),
+        (node.dy, top - node.y),
+    ] {
+        if p == 0.0 {
+            // parallel, and outside
+            if q < 0.0 {
+                return None;
+            }
+        } else if p < 0.0 {
+            enter = enter.max(q / p);
+        } else {
+            exit = exit.min(q / p);
+        }
+    }
+
+    (enter <= exit).then(|| {
+        let at = |t: f32| Point::new(node.x + node.dx * t, node.y + node.dy * t);
+        Segment::new(at(enter), at(exit))
+    })
+}
+
+/// The edges of every blockmap cell.
+fn blockmap_lines(blockmap: &Blockmap) -> Vec<Segment> {
+    let (x, y) = (blockmap.origin_x as f32, blockmap.origin_y as f32);
+    let width = blockmap.columns as f32 * blockmap::CELL_SIZE;
+    let height = blockmap.rows as f32 * blockmap::CELL_SIZE;
+
+    let columns = (0..=blockmap.columns).map(|column| {
+        let x = x + column as f32 * blockmap::CELL_SIZE;
+        Segment::new(Point::new(x, y), Point::new(x, y + height))
+    });
+    let rows = (0..=blockmap.rows).map(|row| {
+        let y = y + row as f32 * blockmap::CELL_SIZE;
+        Segment::new(Point::new(x, y), Point::new(x + width, y))
+    });
+
+    columns.chain(rows).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clips_partitions() {
+        let node = Node {
+            x: 32.0,
+            y: 0.0,
+            dx: 0.0,
+            dy: 16.0,
+            bbox: [[64.0, 0.0, 0.0, 32.0], [64.0, -8.0, 32.0, 64.0]],
+            children: [Child::Subsector(0), Child::Subsector(1)],
+        };
+
+        assert_eq!(
+            clip_partition(&node),
+            Some(Segment::new(Point::new(32.0, -8.0), Point::new(32.0, 64.0)))
+        );
+    }
+}
@@ -0,0 +1,86 @@
+//! Moving the selection with the arrow keys.
+
+use bevy::prelude::*;
+
+use super::{Cursor, Editor, Selection};
+
+/// The grid the selection is nudged along.
+#[derive(Resource, Debug)]
+pub struct Grid {
+    /// The size of a grid cell, in map units.
+    pub size: f32,
+}
+
+impl Default for Grid {
+    fn default() -> Grid {
+        Grid { size: 32.0 }
+    }
+}
+
+/// What the arrow keys nudge, depending on the modifiers held.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NudgeTarget {
+    /// The selected vertices, linedefs and things.
+    Selection,
+    /// The texture offsets of the selected walls, with alt held.
+    Offsets,
+}
+
+impl NudgeTarget {
+    /// The target of the arrow keys, or `None` if control is held, so that
+    /// the arrow keys never nudge two things at once.
+    pub fn from_keys(keys: &ButtonInput<KeyCode>) -> Option<NudgeTarget> {
+        if keys.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight]) {
+            None
+        } else if keys.any_pressed([KeyCode::AltLeft, KeyCode::AltRight]) {
+            Some(NudgeTarget::Offsets)
+        } else {
+            Some(NudgeTarget::Selection)
+        }
+    }
+}
+
+/// Nudges the selected vertices, linedefs and things by the [`Grid`] size with
+/// the arrow keys, or by a single unit with shift held.
+///
+/// See [`NudgeTarget`] for the modifiers.
+pub fn nudge_selection(
+    keys: Res<ButtonInput<KeyCode>>,
+    cursor: Res<Cursor>,
+    grid: Res<Grid>,
+    mut editors: Query<(&mut Editor, &Selection)>,
+) {
+    if !cursor.hovered || NudgeTarget::from_keys(&keys) != Some(NudgeTarget::Selection) {
+        return;
+    }
+
+    let step = if keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]) {
+        1.0
+    } else {
+        grid.size
+    };
+    let (dx, dy) = [
+        (KeyCode::ArrowLeft, (-step, 0.0)),
+        (KeyCode::ArrowRight, (step, 0.0)),
+        (KeyCode::ArrowUp, (0.0, step)),
+        (KeyCode::ArrowDown, (0.0, -step)),
+    ]
+    .into_iter()
+    .filter(|(key, _)| keys.just_pressed(*key))
+    .fold((0.0, 0.0), |(x, y), (_, (dx, dy))| (x + dx, y + dy));
+
+    if (dx, dy) == (0.0, 0.0) {
+        return;
+    }
+
+    let Ok((mut editor, selection)) = editors.get_single_mut() else {
+        return;
+    };
+
+    if selection.vertices.is_empty() && selection.linedefs.is_empty() && selection.things.is_empty()
+    {
+        return;
+    }
+
+    editor.map_mut().move_selection(selection, dx, dy);
+}
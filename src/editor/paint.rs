@@ -0,0 +1,237 @@
+//! Copying properties from one sector or sidedef to others.
+
+use std::collections::BTreeSet;
+
+use bevy::prelude::*;
+
+use super::{Cursor, Editor, EditorCamera, MapIndex};
+use crate::map::query::{LineIndex, Point};
+use crate::map::{Extras, Map};
+
+/// The id of the paint [edit mode](super::mode).
+pub const MODE: &str = "paint";
+
+/// A property that can be painted.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Field {
+    HeightFloor,
+    HeightCeiling,
+    TextureFloor,
+    TextureCeiling,
+    Light,
+    Special,
+    TextureTop,
+    TextureMiddle,
+    TextureBottom,
+    OffsetX,
+    OffsetY,
+}
+
+impl Field {
+    /// The fields of sectors.
+    pub const SECTOR: [Field; 6] = [
+        Field::HeightFloor,
+        Field::HeightCeiling,
+        Field::TextureFloor,
+        Field::TextureCeiling,
+        Field::Light,
+        Field::Special,
+    ];
+
+    /// The fields of sidedefs.
+    pub const SIDEDEF: [Field; 5] = [
+        Field::TextureTop,
+        Field::TextureMiddle,
+        Field::TextureBottom,
+        Field::OffsetX,
+        Field::OffsetY,
+    ];
+
+    /// The name of the field, as shown in the UI.
+    pub fn name(self) -> &'static str {
+        match self {
+            Field::HeightFloor => "Floor height",
+            Field::HeightCeiling => "Ceiling height",
+            Field::TextureFloor => "Floor texture",
+            Field::TextureCeiling => "Ceiling texture",
+            Field::Light => "Light level",
+            Field::Special => "Special",
+            Field::TextureTop => "Upper texture",
+            Field::TextureMiddle => "Middle texture",
+            Field::TextureBottom => "Lower texture",
+            Field::OffsetX => "X offset",
+            Field::OffsetY => "Y offset",
+        }
+    }
+}
+
+/// Where properties are copied from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Source {
+    Sector(usize),
+    SideDef(usize),
+}
+
+/// The state of the paint mode.
+#[derive(Resource, Clone, Debug)]
+pub struct Painter {
+    pub source: Option<Source>,
+    /// The fields that are copied.
+    pub fields: BTreeSet<Field>,
+}
+
+impl Default for Painter {
+    fn default() -> Painter {
+        Painter {
+            source: None,
+            fields: Field::SECTOR.into_iter().chain(Field::SIDEDEF).collect(),
+        }
+    }
+}
+
+/// Picks up properties by shift-clicking, and paints them by clicking.
+pub fn paint(
+    mouse: Res<ButtonInput<MouseButton>>,
+    keys: Res<ButtonInput<KeyCode>>,
+    cursor: Res<Cursor>,
+    cameras: Query<&OrthographicProjection, With<EditorCamera>>,
+    mut painter: ResMut<Painter>,
+    mut editors: Query<(&mut Editor, &MapIndex)>,
+) {
+    if !mouse.just_pressed(MouseButton::Left) {
+        return;
+    }
+
+    let (Some(position), Ok(projection), Ok((mut editor, index))) = (
+        cursor.position,
+        cameras.get_single(),
+        editors.get_single_mut(),
+    ) else {
+        return;
+    };
+
+    let pick_distance = super::select::PICK_DISTANCE * projection.scale;
+    let Some(target) = target_at(editor.map(), index, position, pick_distance) else {
+        return;
+    };
+
+    let pick_up = keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
+
+    match (painter.source, pick_up) {
+        (None, _) | (_, true) => painter.source = Some(target),
+        (Some(source), false) => {
+            if source != target {
+                copy_fields(editor.map_mut(), source, target, &painter.fields);
+            }
+        }
+    }
+}
+
+/// Finds the sidedef or sector under `position`.
+///
+/// Sidedefs are picked if a linedef is close enough, otherwise the sector is.
+fn target_at(map: &Map, index: &LineIndex, position: Vec2, distance: f32) -> Option<Source> {
+    let point = Point::new(position.x, position.y);
+
+    if let Some((idx, d)) = index.nearest_linedef(map, point) {
+        if d <= distance {
+            return map
+                .side_facing(&map.linedefs[idx], point)
+                .map(Source::SideDef);
+        }
+    }
+
+    index.sector_at(map, point.x, point.y).map(Source::Sector)
+}
+
+/// Copies `fields` from `source` to `target`.
+///
+/// Sector fields are only copied between sectors, and sidedef fields between
+/// sidedefs.
+pub fn copy_fields(map: &mut Map, source: Source, target: Source, fields: &BTreeSet<Field>) {
+    match (source, target) {
+        (Source::Sector(from), Source::Sector(to)) => {
+            let (Some(from), Some(_)) = (map.sectors.get(from).cloned(), map.sectors.get(to))
+            else {
+                return;
+            };
+            let to = &mut map.sectors[to];
+
+            for field in fields {
+                match field {
+                    Field::HeightFloor => to.height_floor = from.height_floor,
+                    Field::HeightCeiling => to.height_ceiling = from.height_ceiling,
+                    Field::TextureFloor => to.texture_floor.clone_from(&from.texture_floor),
+                    Field::TextureCeiling => to.texture_ceiling.clone_from(&from.texture_ceiling),
+                    Field::Light => copy_extra(&from.extras, &mut to.extras, "lightlevel"),
+                    Field::Special => copy_extra(&from.extras, &mut to.extras, "special"),
+                    _ => (),
+                }
+            }
+        }
+        (Source::SideDef(from), Source::SideDef(to)) => {
+            let (Some(from), Some(_)) = (map.sidedefs.get(from).cloned(), map.sidedefs.get(to))
+            else {
+                return;
+            };
+            let to = &mut map.sidedefs[to];
+
+            for field in fields {
+                match field {
+                    Field::TextureTop => copy_extra(&from.extras, &mut to.extras, "texturetop"),
+                    Field::TextureMiddle => {
+                        copy_extra(&from.extras, &mut to.extras, "texturemiddle")
+                    }
+                    Field::TextureBottom => {
+                        copy_extra(&from.extras, &mut to.extras, "texturebottom")
+                    }
+                    Field::OffsetX => to.offset_x = from.offset_x,
+                    Field::OffsetY => to.offset_y = from.offset_y,
+                    _ => (),
+                }
+            }
+        }
+        _ => (),
+    }
+}
+
+fn copy_extra(from: &Extras, to: &mut Extras, key: &str) {
+    match from.get(key) {
+        Some(value) => {
+            to.insert(key, value.clone());
+        }
+        None => {
+            to.remove(key);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::format::udmf::Value;
+
+    #[test]
+    fn copy_sector_fields() {
+        let mut map = Map::from_str(
+            r#"
+            namespace = "ringracers";
+            version = 1;
+
+            sector { texturefloor = "LAVA"; textureceiling = "CEIL"; heightfloor = 32; lightlevel = 128; }
+            sector { texturefloor = "FLOOR"; textureceiling = "SKY"; special = 4; }
+            "#,
+        )
+        .unwrap();
+
+        let fields = [Field::TextureFloor, Field::Light, Field::Special].into();
+        copy_fields(&mut map, Source::Sector(0), Source::Sector(1), &fields);
+
+        let sector = &map.sectors[1];
+        assert_eq!(sector.texture_floor, "LAVA");
+        assert_eq!(sector.texture_ceiling, "SKY");
+        assert_eq!(sector.height_floor, 0);
+        assert_eq!(sector.extras.get("lightlevel"), Some(&Value::Integer(128)));
+        assert_eq!(sector.extras.get("special"), None);
+    }
+}
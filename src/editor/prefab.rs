@@ -0,0 +1,211 @@
+//! Prefabs, reusable pieces of maps.
+
+use std::fmt::{self, Display, Formatter};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use bevy::prelude::*;
+
+use super::session::config_dir;
+use crate::format::udmf;
+use crate::map::{Map, Selection};
+
+/// The extension prefab files are saved with.
+pub const PREFAB_EXTENSION: &str = "udmf";
+
+/// A named piece of a map.
+///
+/// The map of a prefab is centered around `(0, 0)`.
+#[derive(Clone, Debug)]
+pub struct Prefab {
+    pub name: String,
+    pub map: Map,
+}
+
+/// A library of prefabs.
+///
+/// Each prefab is stored as a `udmf` fragment in the library's directory.
+#[derive(Resource, Debug)]
+pub struct PrefabLibrary {
+    dir: PathBuf,
+    prefabs: Vec<Prefab>,
+}
+
+impl PrefabLibrary {
+    /// Creates a new, empty `PrefabLibrary` that lives in `dir`.
+    ///
+    /// Call [`PrefabLibrary::reload`] to read the prefabs.
+    pub fn new(dir: impl Into<PathBuf>) -> PrefabLibrary {
+        PrefabLibrary {
+            dir: dir.into(),
+            prefabs: Vec::new(),
+        }
+    }
+
+    /// The directory of the library.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// The prefabs in the library, sorted by name.
+    pub fn prefabs(&self) -> &[Prefab] {
+        &self.prefabs
+    }
+
+    /// Gets a prefab by name.
+    pub fn prefab(&self, name: &str) -> Option<&Prefab> {
+        self.prefabs.iter().find(|p| p.name == name)
+    }
+
+    /// Reads all of the prefabs in the library's directory.
+    ///
+    /// Prefabs that fail to load are skipped.
+    pub fn reload(&mut self) -> Result<(), Error> {
+        self.prefabs.clear();
+
+        if !self.dir.exists() {
+            return Ok(());
+        }
+
+        for entry in fs::read_dir(&self.dir)? {
+            let path = entry?.path();
+
+            if path.extension().and_then(|ext| ext.to_str()) != Some(PREFAB_EXTENSION) {
+                continue;
+            }
+
+            let Some(name) = path.file_stem().and_then(|name| name.to_str()) else {
+                continue;
+            };
+
+            let map = match fs::read_to_string(&path)
+                .map_err(Error::from)
+                .and_then(|input| Map::from_str(&input).map_err(Error::from))
+            {
+                Ok(map) => map,
+                Err(err) => {
+                    warn!("failed to load prefab {}: {}", path.display(), err);
+                    continue;
+                }
+            };
+
+            self.prefabs.push(Prefab {
+                name: name.to_owned(),
+                map,
+            });
+        }
+
+        self.prefabs.sort_by(|a, b| a.name.cmp(&b.name));
+
+        Ok(())
+    }
+
+    /// Saves the selected objects of `map` as a new prefab.
+    ///
+    /// If a prefab named `name` already exists, it is overwritten.
+    pub fn save(&mut self, name: &str, map: &Map, selection: &Selection) -> Result<(), Error> {
+        if name.is_empty() || name.contains(['/', '\\', '.']) {
+            return Err(Error::InvalidName(name.to_owned()));
+        }
+
+        let mut fragment = map.extract(selection);
+
+        // center the prefab, keeping it on the grid
+        let points = fragment
+            .vertices
+            .iter()
+            .map(|v| (v.x, v.y))
+            .chain(fragment.things.iter().map(|t| (t.x, t.y)));
+        let bounds = points.fold(None, |bounds, (x, y)| match bounds {
+            None => Some((x, y, x, y)),
+            Some((min_x, min_y, max_x, max_y)) => Some((
+                f32::min(min_x, x),
+                f32::min(min_y, y),
+                f32::max(max_x, x),
+                f32::max(max_y, y),
+            )),
+        });
+
+        if let Some((min_x, min_y, max_x, max_y)) = bounds {
+            let center_x = ((min_x + max_x) / 2.0).round();
+            let center_y = ((min_y + max_y) / 2.0).round();
+            fragment.translate(-center_x, -center_y);
+        }
+
+        let mut output = String::new();
+        fragment.write(&mut output)?;
+
+        fs::create_dir_all(&self.dir)?;
+        fs::write(self.dir.join(name).with_extension(PREFAB_EXTENSION), output)?;
+
+        let prefab = Prefab {
+            name: name.to_owned(),
+            map: fragment,
+        };
+
+        match self.prefabs.binary_search_by(|p| p.name.as_str().cmp(name)) {
+            Ok(idx) => self.prefabs[idx] = prefab,
+            Err(idx) => self.prefabs.insert(idx, prefab),
+        }
+
+        Ok(())
+    }
+}
+
+impl Default for PrefabLibrary {
+    fn default() -> PrefabLibrary {
+        PrefabLibrary::new(config_dir().join("prefabs"))
+    }
+}
+
+/// Reads the prefabs of the [`PrefabLibrary`] at startup.
+pub fn load_prefabs(mut library: ResMut<PrefabLibrary>) {
+    if let Err(err) = library.reload() {
+        warn!(
+            "failed to load prefabs from {}: {}",
+            library.dir().display(),
+            err
+        );
+    }
+}
+
+/// An error for prefab operations.
+#[derive(Debug)]
+pub enum Error {
+    Io(io::Error),
+    Read(udmf::de::Error),
+    Write(udmf::ser::Error),
+    InvalidName(String),
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Io(err) => Display::fmt(err, f),
+            Error::Read(err) => Display::fmt(err, f),
+            Error::Write(err) => Display::fmt(err, f),
+            Error::InvalidName(name) => write!(f, "invalid prefab name: \"{}\"", name),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Error {
+        Error::Io(e)
+    }
+}
+
+impl From<udmf::de::Error> for Error {
+    fn from(e: udmf::de::Error) -> Error {
+        Error::Read(e)
+    }
+}
+
+impl From<udmf::ser::Error> for Error {
+    fn from(e: udmf::ser::Error) -> Error {
+        Error::Write(e)
+    }
+}
@@ -0,0 +1,126 @@
+//! Previews of [destructive operations](crate::map::remove).
+//!
+//! While an operation is hovered in the UI, the linedefs it would change are
+//! drawn over the open map where they would end up, and the linedefs it would
+//! remove are highlighted.
+
+use bevy::prelude::*;
+use bevy::sprite::{MaterialMesh2dBundle, Mesh2dHandle};
+
+use super::{lod, Editor, Selection};
+use crate::map::query::{Point, Segment};
+use crate::map::remove::{Destructive, Impact};
+
+/// The color of linedefs that would be changed.
+const PREVIEW_COLOR: Color = Color::rgb(0.4, 1.0, 0.6);
+
+/// The color of linedefs that would be removed.
+const REMOVED_COLOR: Color = Color::RED;
+
+/// Where the preview is drawn, over the linedefs and things.
+const PREVIEW_Z: f32 = 1.5;
+
+/// The operation being previewed.
+#[derive(Resource, Debug, Default)]
+pub struct OperationPreview {
+    /// The hovered operation.
+    ///
+    /// This is set by the UI every frame.
+    pub operation: Option<Destructive>,
+    /// What the operation would do to the open map.
+    pub impact: Option<Impact>,
+}
+
+/// Tag for the meshes of the [`OperationPreview`].
+#[derive(Component, Clone, Copy, Debug, Default)]
+pub struct PreviewMesh;
+
+/// Redraws the [`OperationPreview`] when it, the map or the selection
+/// changes.
+pub fn draw_preview(
+    mut commands: Commands,
+    mut preview: ResMut<OperationPreview>,
+    editors: Query<(Ref<Editor>, Ref<Selection>)>,
+    entities: Query<Entity, With<PreviewMesh>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+) {
+    let editor = editors.get_single().ok();
+
+    if !preview.is_changed()
+        && !editor
+            .as_ref()
+            .is_some_and(|(editor, selection)| editor.is_changed() || selection.is_changed())
+    {
+        return;
+    }
+
+    for entity in entities.iter() {
+        commands.entity(entity).despawn();
+    }
+
+    // keep the impact from triggering another redraw
+    let preview = preview.bypass_change_detection();
+    preview.impact = None;
+
+    let (Some(operation), Some((editor, selection))) = (preview.operation, editor) else {
+        return;
+    };
+    let map = Editor::map(&editor);
+
+    let impact = operation.preview(map, &selection);
+
+    let point = |v: i32| {
+        let vertex = map.vertices.get(impact.vertex(v) as usize)?;
+        Some(Point::new(vertex.x, vertex.y))
+    };
+    // changed linedefs are drawn where they end up
+    let changed = impact
+        .changed
+        .linedefs
+        .iter()
+        .filter_map(|&idx| {
+            let linedef = &map.linedefs[idx];
+            Some(Segment::new(point(linedef.v1)?, point(linedef.v2)?))
+        })
+        .collect::<Vec<_>>();
+    let removed = impact
+        .removed
+        .linedefs
+        .iter()
+        .filter_map(|&idx| map.linedef_segment(&map.linedefs[idx]))
+        .collect::<Vec<_>>();
+
+    // the rest of the map stays where it is, so only these are drawn
+    for (lines, color, z) in [
+        (changed, PREVIEW_COLOR, PREVIEW_Z),
+        (removed, REMOVED_COLOR, PREVIEW_Z + 0.1),
+    ] {
+        if lines.is_empty() {
+            continue;
+        }
+
+        commands.spawn((
+            MaterialMesh2dBundle {
+                mesh: Mesh2dHandle(meshes.add(lod::line_mesh(lines))),
+                material: materials.add(ColorMaterial::from(color)),
+                transform: Transform::from_xyz(0.0, 0.0, z),
+                ..default()
+            },
+            PreviewMesh,
+        ));
+    }
+
+    preview.impact = Some(impact);
+}
+
+/// Applies `operation` to the open map and its selection.
+pub fn run_destructive(world: &mut World, operation: Destructive) {
+    let mut editors = world.query::<(&mut Editor, &mut Selection)>();
+    let Ok((mut editor, mut selection)) = editors.get_single_mut(world) else {
+        return;
+    };
+
+    let impact = operation.apply(editor.map_mut(), &mut selection);
+    info!("{}: {}", operation.name(), impact);
+}
@@ -0,0 +1,281 @@
+//! Selecting objects with the mouse.
+
+use std::collections::BTreeSet;
+
+use bevy::prelude::*;
+use bevy::window::PrimaryWindow;
+use bevy_prototype_lyon::draw::{Fill, Stroke};
+
+use super::filter::{Hidden, ThingCategory, ThingFilter};
+use super::{Editor, EditorCamera, LineDef, MapIndex, Thing, Vertex, SELECTED_COLOR};
+use crate::map::query::{LineIndex, Point};
+use crate::map::{self, Map};
+
+/// The id of the select [edit mode](super::mode).
+pub const MODE: &str = "select";
+
+/// The radius vertices are drawn with, in map units.
+pub const VERTEX_RADIUS: f32 = 2.0;
+
+/// The radius things are drawn with, in map units.
+pub const THING_RADIUS: f32 = 16.0;
+
+/// How close the mouse has to be to pick something, in pixels.
+pub const PICK_DISTANCE: f32 = 6.0;
+
+/// The objects selected in an [`Editor`].
+#[derive(Component, Clone, Debug, Default, Deref, DerefMut)]
+pub struct Selection(pub map::Selection);
+
+/// Where the mouse is on the map.
+#[derive(Resource, Clone, Debug, Default)]
+pub struct Cursor {
+    /// If the mouse is over the viewport, and not covered by any UI.
+    ///
+    /// This is set by the UI every frame.
+    pub hovered: bool,
+    /// The position of the mouse, in map units.
+    ///
+    /// `None` if the viewport is not hovered.
+    pub position: Option<Vec2>,
+}
+
+/// Updates the [`Cursor`] position.
+pub fn update_cursor(
+    mut cursor: ResMut<Cursor>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    cameras: Query<(&Camera, &GlobalTransform), With<EditorCamera>>,
+) {
+    cursor.position = None;
+
+    if !cursor.hovered {
+        return;
+    }
+
+    let (Ok(window), Ok((camera, transform))) = (windows.get_single(), cameras.get_single()) else {
+        return;
+    };
+    let Some(position) = window.cursor_position() else {
+        return;
+    };
+
+    let offset = camera
+        .logical_viewport_rect()
+        .map(|rect| rect.min)
+        .unwrap_or_default();
+    cursor.position = camera.viewport_to_world_2d(transform, position - offset);
+}
+
+/// Selects objects by clicking on them, or by dragging a box around them.
+///
+/// Holding shift adds to the selection instead of replacing it.
+pub fn select(
+    mouse: Res<ButtonInput<MouseButton>>,
+    keys: Res<ButtonInput<KeyCode>>,
+    cursor: Res<Cursor>,
+    hidden: Hidden,
+    cameras: Query<&OrthographicProjection, With<EditorCamera>>,
+    mut editors: Query<(&Editor, &MapIndex, &mut Selection)>,
+    mut drag_start: Local<Option<Vec2>>,
+) {
+    if mouse.just_pressed(MouseButton::Left) {
+        *drag_start = cursor.position;
+    }
+
+    if !mouse.just_released(MouseButton::Left) {
+        return;
+    }
+
+    let (Some(start), Some(end)) = (drag_start.take(), cursor.position) else {
+        return;
+    };
+    let (Ok((editor, index, mut selection)), Ok(projection)) =
+        (editors.get_single_mut(), cameras.get_single())
+    else {
+        return;
+    };
+
+    let additive = keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
+    let excluded = hidden.unselectable(editor.map());
+    let pick_distance = PICK_DISTANCE * projection.scale;
+
+    if start.distance(end) < pick_distance {
+        // single click
+        let picked = pick(editor.map(), index, end, pick_distance, &excluded);
+
+        if !additive {
+            selection.clear();
+        }
+
+        if let Some(picked) = picked {
+            picked.toggle(&mut selection);
+        }
+    } else {
+        // box select
+        let rect = Rect::from_corners(start, end);
+
+        if !additive {
+            selection.clear();
+        }
+
+        select_in_rect(editor.map(), rect, &excluded, &mut selection);
+    }
+}
+
+/// Colors selected objects.
+pub fn highlight_selection(
+    selections: Query<Ref<Selection>>,
+    mut linedefs: Query<(Ref<LineDef>, &mut Stroke), Without<Thing>>,
+    mut vertices: Query<(Ref<Vertex>, &mut Fill)>,
+    mut things: Query<(Ref<Thing>, &ThingCategory, &mut Stroke), Without<LineDef>>,
+    filter: Res<ThingFilter>,
+) {
+    let Ok(selection) = selections.get_single() else {
+        return;
+    };
+    let changed = selection.is_changed();
+
+    for (linedef, mut stroke) in linedefs.iter_mut() {
+        if changed || linedef.is_added() {
+            stroke.color = if selection.linedefs.contains(&linedef.0) {
+                SELECTED_COLOR
+            } else {
+                Color::WHITE
+            };
+        }
+    }
+
+    for (vertex, mut fill) in vertices.iter_mut() {
+        if changed || vertex.is_added() {
+            fill.color = if selection.vertices.contains(&vertex.0) {
+                SELECTED_COLOR
+            } else {
+                Color::GRAY
+            };
+        }
+    }
+
+    for (thing, category, mut stroke) in things.iter_mut() {
+        if changed || thing.is_added() {
+            stroke.color = if selection.things.contains(&thing.0) {
+                SELECTED_COLOR
+            } else {
+                filter.color(category.0)
+            };
+        }
+    }
+}
+
+/// An object that can be picked with the mouse.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Picked {
+    Vertex(usize),
+    Thing(usize),
+    LineDef(usize),
+}
+
+impl Picked {
+    fn toggle(self, selection: &mut map::Selection) {
+        let (set, idx) = match self {
+            Picked::Vertex(idx) => (&mut selection.vertices, idx),
+            Picked::Thing(idx) => (&mut selection.things, idx),
+            Picked::LineDef(idx) => (&mut selection.linedefs, idx),
+        };
+
+        if !set.remove(&idx) {
+            set.insert(idx);
+        }
+    }
+}
+
+/// Picks the object closest to `point`.
+///
+/// Vertices are picked before things, and things before linedefs. Objects in `excluded` are never picked.
+fn pick(
+    map: &Map,
+    index: &LineIndex,
+    point: Vec2,
+    distance: f32,
+    excluded: &map::Selection,
+) -> Option<Picked> {
+    let closest = |set: &BTreeSet<usize>, iter: &mut dyn Iterator<Item = (usize, f32)>| {
+        iter.filter(|(idx, d)| *d <= distance && !set.contains(idx))
+            .min_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|(idx, _)| idx)
+    };
+
+    let vertex = closest(
+        &excluded.vertices,
+        &mut map
+            .vertices
+            .iter()
+            .enumerate()
+            .map(|(idx, v)| (idx, point.distance(Vec2::new(v.x, v.y)))),
+    );
+    if let Some(idx) = vertex {
+        return Some(Picked::Vertex(idx));
+    }
+
+    let thing = closest(
+        &excluded.things,
+        &mut map.things.iter().enumerate().map(|(idx, t)| {
+            (
+                idx,
+                (point.distance(Vec2::new(t.x, t.y)) - THING_RADIUS).max(0.0),
+            )
+        }),
+    );
+    if let Some(idx) = thing {
+        return Some(Picked::Thing(idx));
+    }
+
+    let point = Point::new(point.x, point.y);
+    let linedef = closest(
+        &excluded.linedefs,
+        &mut index.near(point, distance).filter_map(|idx| {
+            let segment = map.linedef_segment(map.linedefs.get(idx)?)?;
+            Some((idx, segment.distance_to(point)))
+        }),
+    );
+    linedef.map(Picked::LineDef)
+}
+
+/// Adds everything inside of `rect` to the selection, except for objects in
+/// `excluded`.
+fn select_in_rect(
+    map: &Map,
+    rect: Rect,
+    excluded: &map::Selection,
+    selection: &mut map::Selection,
+) {
+    let inside = |x: f32, y: f32| rect.contains(Vec2::new(x, y));
+
+    selection.vertices.extend(
+        map.vertices
+            .iter()
+            .enumerate()
+            .filter(|(idx, v)| inside(v.x, v.y) && !excluded.vertices.contains(idx))
+            .map(|(idx, _)| idx),
+    );
+    selection.things.extend(
+        map.things
+            .iter()
+            .enumerate()
+            .filter(|(idx, t)| inside(t.x, t.y) && !excluded.things.contains(idx))
+            .map(|(idx, _)| idx),
+    );
+    selection.linedefs.extend(
+        map.linedefs
+            .iter()
+            .enumerate()
+            .filter(|(idx, l)| {
+                !excluded.linedefs.contains(idx)
+                    && [l.v1, l.v2].iter().all(|&v| {
+                        map.vertices
+                            .get(v as usize)
+                            .is_some_and(|v| inside(v.x, v.y))
+                    })
+            })
+            .map(|(idx, _)| idx),
+    );
+}
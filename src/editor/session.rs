@@ -0,0 +1,453 @@
+//! Opening maps, recent files and restoring the last session.
+
+use std::fmt::{self, Display, Formatter};
+use std::fs::{self, File};
+use std::io::{self, BufReader};
+use std::path::{Path, PathBuf};
+
+use bevy::app::AppExit;
+use bevy::ecs::system::RunSystemOnce;
+use bevy::prelude::*;
+use serde::de::IgnoredAny;
+use serde::{Deserialize, Serialize};
+
+use super::archive::Archive;
+use super::tasks::{BackgroundTasks, Progress, ProgressReader};
+use super::underlay::UnderlaySettings;
+use super::{Editor, EditorBundle, EditorCamera};
+use crate::format::pk3::{is_pk3, read_pk3};
+use crate::format::udmf;
+use crate::format::wad::{self, Wad};
+use crate::map::Map;
+
+/// How many recent files are remembered.
+pub const MAX_RECENT: usize = 10;
+
+/// The directory the editor keeps its files in.
+///
+/// This is the `rrmap` directory in the config directory of the platform,
+/// or the working directory if there isn't one.
+pub fn config_dir() -> PathBuf {
+    let env = |name: &str| std::env::var_os(name).filter(|value| !value.is_empty());
+
+    let base = if cfg!(windows) {
+        env("APPDATA").map(PathBuf::from)
+    } else if cfg!(target_os = "macos") {
+        env("HOME").map(|home| Path::new(&home).join("Library/Application Support"))
+    } else {
+        env("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .or_else(|| env("HOME").map(|home| Path::new(&home).join(".config")))
+    };
+
+    base.map_or_else(PathBuf::new, |base| base.join("rrmap"))
+}
+
+/// The [`BackgroundTasks`] key of opening maps.
+///
+/// Only the last map asked for is opened.
+pub const OPEN_TASK: &str = "open map";
+
+/// Opens the map in a WAD, replacing the open map.
+#[derive(Event, Clone, Debug)]
+pub struct OpenMap(pub PathBuf);
+
+/// The file the map of an [`Editor`] was opened from.
+#[derive(Component, Clone, Debug)]
+pub struct MapFile(pub PathBuf);
+
+/// The last opened map, and where the camera was left in it.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct LastMap {
+    pub path: PathBuf,
+    pub x: f32,
+    pub y: f32,
+    pub scale: f32,
+}
+
+/// The recently opened files, the last opened map and the preferences of each
+/// recent map.
+///
+/// This is stored as `udmf` in the session file, and saved whenever a map is
+/// opened and when the editor exits.
+#[derive(Resource, Debug)]
+pub struct Session {
+    file: PathBuf,
+    /// The recently opened files, most recent first.
+    pub recent: Vec<PathBuf>,
+    pub last: Option<LastMap>,
+    /// The underlays of the recent files.
+    pub underlays: Vec<UnderlaySettings>,
+    /// Why the last map failed to open.
+    ///
+    /// This is not saved.
+    pub error: Option<String>,
+}
+
+impl Session {
+    /// Creates a new, empty `Session` stored in `file`.
+    ///
+    /// Call [`Session::load`] to read the session.
+    pub fn new(file: impl Into<PathBuf>) -> Session {
+        Session {
+            file: file.into(),
+            recent: Vec::new(),
+            last: None,
+            underlays: Vec::new(),
+            error: None,
+        }
+    }
+
+    /// The file of the session.
+    pub fn file(&self) -> &Path {
+        &self.file
+    }
+
+    /// Reads the session file.
+    pub fn load(&mut self) -> Result<(), Error> {
+        self.recent.clear();
+        self.last = None;
+        self.underlays.clear();
+
+        if !self.file.exists() {
+            return Ok(());
+        }
+
+        let input = fs::read_to_string(&self.file)?;
+        let mut parser = udmf::de::Parser::new(&input);
+
+        while let Some(key) = parser.next_key()? {
+            match key {
+                "recent" => self.recent.push(parser.next_value()?),
+                "last" => self.last = Some(parser.next_value()?),
+                "underlay" => self.underlays.push(parser.next_value()?),
+                _ => {
+                    parser.next_value::<IgnoredAny>()?;
+                }
+            }
+        }
+
+        self.recent.truncate(MAX_RECENT);
+
+        Ok(())
+    }
+
+    /// Writes the session file.
+    pub fn save(&self) -> Result<(), Error> {
+        let mut writer = udmf::ser::Writer::new(String::new());
+
+        for path in self.recent.iter() {
+            writer.write_value("recent", path)?;
+        }
+        if let Some(last) = &self.last {
+            writer.write_value("last", last)?;
+        }
+        for underlay in self.underlays.iter() {
+            writer.write_value("underlay", underlay)?;
+        }
+
+        if let Some(dir) = self.file.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(&self.file, writer.into_inner())?;
+
+        Ok(())
+    }
+
+    /// Moves `path` to the top of the recent files.
+    ///
+    /// Paths are made absolute, so the same file is only listed once.
+    pub fn add_recent(&mut self, path: PathBuf) {
+        let path = fs::canonicalize(&path).unwrap_or(path);
+        self.recent.retain(|p| *p != path);
+        self.recent.insert(0, path);
+        self.recent.truncate(MAX_RECENT);
+
+        // only recent maps are remembered
+        let recent = &self.recent;
+        self.underlays
+            .retain(|underlay| recent.contains(&underlay.map));
+    }
+
+    /// Remembers where the camera is in the open map.
+    fn remember_view(&mut self, path: &Path, transform: &Transform, scale: f32) {
+        self.last = Some(LastMap {
+            path: path.to_owned(),
+            x: transform.translation.x,
+            y: transform.translation.y,
+            scale,
+        });
+    }
+}
+
+impl Default for Session {
+    fn default() -> Session {
+        Session::new(config_dir().join("session.udmf"))
+    }
+}
+
+/// A WAD and its first map, read by [`load_map`].
+pub type Loaded = Result<(Archive, Map), Error>;
+
+/// Reads a WAD and its first map, reporting to `progress`.
+pub fn load_map(path: &Path, progress: &Progress) -> Loaded {
+    progress.set_message("Reading WAD");
+    let file = ProgressReader::new(BufReader::new(File::open(path)?), progress, (0.0, 0.5))?;
+    let wad = if is_pk3(path) {
+        read_pk3(file)?
+    } else {
+        Wad::from_reader(file)?
+    };
+    let name = wad.maps().next().ok_or(Error::NoTextMap)?;
+
+    progress.set_message(format!("Reading {}", name));
+    let mut archive = Archive::new(path, wad);
+    let (map, diagnostics) = archive.read_map(&name)?;
+    archive.map = Some(name);
+    archive.diagnostics = diagnostics;
+    if archive.is_read_only() {
+        archive.status = Some("Opened read-only, use Save As to keep changes".into());
+    }
+    progress.set_fraction(1.0);
+
+    Ok((archive, map))
+}
+
+/// Reads the [`Session`] at startup.
+pub fn load_session(mut session: ResMut<Session>) {
+    if let Err(err) = session.load() {
+        warn!(
+            "failed to load session from {}: {}",
+            session.file().display(),
+            err
+        );
+    }
+}
+
+/// Starts opening maps on [`OpenMap`].
+pub fn open_map(mut events: EventReader<OpenMap>, mut tasks: ResMut<BackgroundTasks>) {
+    // only the last request matters
+    let Some(OpenMap(path)) = events.read().last() else {
+        return;
+    };
+    // the session knows maps by their absolute path
+    let path = fs::canonicalize(path).unwrap_or_else(|_| path.clone());
+
+    tasks.spawn_latest(
+        OPEN_TASK,
+        format!("Opening {}", path.display()),
+        move |progress| {
+            let loaded = load_map(&path, progress);
+
+            Box::new(move |world: &mut World| {
+                world.run_system_once_with((path, loaded), map_loaded)
+            })
+        },
+    );
+}
+
+/// Replaces the open map with a map read by [`open_map`].
+///
+/// If the map is the last opened map, the camera is put back where it was.
+fn map_loaded(
+    In((path, loaded)): In<(PathBuf, Loaded)>,
+    mut commands: Commands,
+    mut session: ResMut<Session>,
+    editors: Query<(Entity, Option<&MapFile>), With<Editor>>,
+    mut cameras: Query<(&mut Transform, &mut OrthographicProjection), With<EditorCamera>>,
+) {
+    let path = &path;
+
+    let (archive, map) = match loaded {
+        Ok(loaded) => loaded,
+        Err(err) => {
+            error!("failed to open {}: {}", path.display(), err);
+            session.error = Some(format!("Failed to open {}: {}", path.display(), err));
+            return;
+        }
+    };
+
+    let camera = cameras.get_single_mut().ok();
+
+    if let Some((mut transform, mut projection)) = camera {
+        // leave the old map where it was
+        if let Ok((_, Some(MapFile(old_path)))) = editors.get_single() {
+            session.remember_view(old_path, &transform, projection.scale);
+        }
+
+        match &session.last {
+            Some(last) if last.path == *path => {
+                transform.translation.x = last.x;
+                transform.translation.y = last.y;
+                projection.scale = last.scale;
+            }
+            _ => {
+                transform.translation.x = 0.0;
+                transform.translation.y = 0.0;
+                projection.scale = 1.0;
+            }
+        }
+
+        session.remember_view(path, &transform, projection.scale);
+    }
+
+    for (entity, _) in editors.iter() {
+        commands.entity(entity).despawn();
+    }
+    commands.spawn((EditorBundle::new(map), MapFile(path.clone())));
+    commands.insert_resource(archive);
+
+    session.add_recent(path.clone());
+    session.error = None;
+
+    if let Err(err) = session.save() {
+        warn!(
+            "failed to save session to {}: {}",
+            session.file().display(),
+            err
+        );
+    }
+}
+
+/// Saves the [`Session`] when the editor exits.
+pub fn save_session(
+    mut exits: EventReader<AppExit>,
+    mut session: ResMut<Session>,
+    editors: Query<&MapFile, With<Editor>>,
+    cameras: Query<(&Transform, &OrthographicProjection), With<EditorCamera>>,
+) {
+    if exits.read().last().is_none() {
+        return;
+    }
+
+    if let (Ok(MapFile(path)), Ok((transform, projection))) =
+        (editors.get_single(), cameras.get_single())
+    {
+        session.remember_view(path, transform, projection.scale);
+    }
+
+    if let Err(err) = session.save() {
+        warn!(
+            "failed to save session to {}: {}",
+            session.file().display(),
+            err
+        );
+    }
+}
+
+/// An error for opening maps and sessions.
+#[derive(Debug)]
+pub enum Error {
+    Io(io::Error),
+    Wad(wad::Error),
+    Read(udmf::de::Error),
+    Write(udmf::ser::Error),
+    NoTextMap,
+    ReadOnly,
+    LumpNameTooLong(String),
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Io(err) => Display::fmt(err, f),
+            Error::Wad(err) => Display::fmt(err, f),
+            Error::Read(err) => Display::fmt(err, f),
+            Error::Write(err) => Display::fmt(err, f),
+            Error::NoTextMap => write!(f, "no TEXTMAP in wad file"),
+            Error::ReadOnly => write!(f, "wad file is read-only"),
+            Error::LumpNameTooLong(name) => write!(
+                f,
+                "can't be saved as a wad file, lump name \"{}\" is longer than 8 characters",
+                name
+            ),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Error {
+        Error::Io(e)
+    }
+}
+
+impl From<wad::Error> for Error {
+    fn from(e: wad::Error) -> Error {
+        Error::Wad(e)
+    }
+}
+
+impl From<udmf::de::Error> for Error {
+    fn from(e: udmf::de::Error) -> Error {
+        Error::Read(e)
+    }
+}
+
+impl From<udmf::ser::Error> for Error {
+    fn from(e: udmf::ser::Error) -> Error {
+        Error::Write(e)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn session_round_trip() {
+        let file = std::env::temp_dir().join(format!("rrmap-session-{}.udmf", std::process::id()));
+        let mut session = Session::new(&file);
+
+        session
+            .underlays
+            .push(UnderlaySettings::new("map0.wad", "old.png"));
+        for i in 0..=MAX_RECENT {
+            session.add_recent(format!("map{}.wad", i).into());
+        }
+        session.add_recent("map3.wad".into());
+        session.underlays.push(UnderlaySettings {
+            x: -32.5,
+            ..UnderlaySettings::new("map3.wad", "sketch.png")
+        });
+        session.last = Some(LastMap {
+            path: "map3.wad".into(),
+            x: 128.0,
+            y: -64.0,
+            scale: 2.0,
+        });
+        session.save().unwrap();
+
+        let mut loaded = Session::new(&file);
+        loaded.load().unwrap();
+        fs::remove_file(&file).unwrap();
+
+        assert_eq!(loaded.recent.len(), MAX_RECENT);
+        assert_eq!(loaded.recent[0], PathBuf::from("map3.wad"));
+        assert_eq!(loaded.recent[1], PathBuf::from("map10.wad"));
+
+        let last = loaded.last.unwrap();
+        assert_eq!(last.path, PathBuf::from("map3.wad"));
+        assert_eq!((last.x, last.y, last.scale), (128.0, -64.0, 2.0));
+
+        // map0 fell off the recent files
+        assert_eq!(loaded.underlays.len(), 1);
+        assert_eq!(loaded.underlays, session.underlays);
+    }
+
+    #[test]
+    fn recent_paths_are_absolute() {
+        let dir = std::env::temp_dir();
+        let name = format!("rrmap-recent-{}.wad", std::process::id());
+        fs::write(dir.join(&name), []).unwrap();
+
+        let mut session = Session::new(dir.join("session.udmf"));
+        session.add_recent(dir.join(&name));
+        session.add_recent(dir.join(".").join(&name));
+        fs::remove_file(dir.join(&name)).unwrap();
+
+        assert_eq!(session.recent.len(), 1);
+        assert!(session.recent[0].is_absolute());
+    }
+}
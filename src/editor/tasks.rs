@@ -0,0 +1,287 @@
+//! Long running work, off the main thread.
+
+use std::collections::HashMap;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::{Arc, Mutex};
+
+use bevy::prelude::*;
+use bevy::tasks::futures_lite::future;
+use bevy::tasks::{AsyncComputeTaskPool, Task};
+
+/// What to do with the world once a task is done.
+pub type Finish = Box<dyn FnOnce(&mut World) + Send>;
+
+/// The work that is running in the background.
+///
+/// Tasks run on the [`AsyncComputeTaskPool`] and report their [`Progress`],
+/// which is shown in a popup until they finish. When a task is done, the
+/// [`Finish`] it returns is run on the world, so it can apply its results.
+#[derive(Resource, Default)]
+pub struct BackgroundTasks {
+    tasks: Vec<BackgroundTask>,
+    /// The generation of the latest task started for each key, see
+    /// [`BackgroundTasks::spawn_latest`].
+    generations: HashMap<&'static str, u64>,
+}
+
+/// A task in [`BackgroundTasks`].
+pub struct BackgroundTask {
+    /// What the task is doing, as shown in the UI.
+    pub name: String,
+    pub progress: Arc<Progress>,
+    task: Task<Finish>,
+    /// The key the task was started with, and its generation.
+    generation: Option<(&'static str, u64)>,
+}
+
+impl BackgroundTasks {
+    /// Starts running `f` in the background.
+    ///
+    /// `f` should check [`Progress::is_cancelled`] now and then, and stop
+    /// early when it is.
+    pub fn spawn<F>(&mut self, name: impl Into<String>, f: F)
+    where
+        F: FnOnce(&Progress) -> Finish + Send + 'static,
+    {
+        self.push(name.into(), None, f);
+    }
+
+    /// Starts running `f` in the background, replacing the tasks started
+    /// with the same `key`.
+    ///
+    /// Each task is tagged with a new generation of `key`. Older tasks are
+    /// cancelled, and only the results of the latest generation are applied,
+    /// so tasks finishing out of order can't undo newer ones.
+    pub fn spawn_latest<F>(&mut self, key: &'static str, name: impl Into<String>, f: F)
+    where
+        F: FnOnce(&Progress) -> Finish + Send + 'static,
+    {
+        let generation = self.generations.entry(key).or_default();
+        *generation += 1;
+        let generation = *generation;
+
+        for task in self.tasks.iter() {
+            if task.generation.is_some_and(|(k, _)| k == key) {
+                task.progress.cancelled.store(true, Ordering::Relaxed);
+            }
+        }
+        self.push(name.into(), Some((key, generation)), f);
+    }
+
+    fn push<F>(&mut self, name: String, generation: Option<(&'static str, u64)>, f: F)
+    where
+        F: FnOnce(&Progress) -> Finish + Send + 'static,
+    {
+        let progress = Arc::new(Progress::default());
+        let task = AsyncComputeTaskPool::get().spawn({
+            let progress = progress.clone();
+            async move { f(&progress) }
+        });
+
+        self.tasks.push(BackgroundTask {
+            name,
+            progress,
+            task,
+            generation,
+        });
+    }
+
+    /// Whether the results of `task` are out of date.
+    fn is_stale(&self, task: &BackgroundTask) -> bool {
+        task.progress.is_cancelled()
+            || task
+                .generation
+                .is_some_and(|(key, generation)| self.generations.get(key) != Some(&generation))
+    }
+
+    /// The running tasks, oldest first.
+    pub fn iter(&self) -> impl Iterator<Item = &BackgroundTask> + '_ {
+        self.tasks.iter()
+    }
+
+    /// Whether no tasks are running.
+    pub fn is_empty(&self) -> bool {
+        self.tasks.is_empty()
+    }
+
+    /// Cancels the task at `idx`.
+    ///
+    /// Its results are thrown away, even if it still finishes.
+    pub fn cancel(&mut self, idx: usize) {
+        if idx < self.tasks.len() {
+            let task = self.tasks.remove(idx);
+            task.progress.cancelled.store(true, Ordering::Relaxed);
+        }
+    }
+}
+
+/// How far along a [`BackgroundTask`] is.
+#[derive(Debug, Default)]
+pub struct Progress {
+    /// The fraction done, as the bits of an `f32`.
+    fraction: AtomicU32,
+    message: Mutex<String>,
+    cancelled: AtomicBool,
+}
+
+impl Progress {
+    /// How much of the task is done, from `0.0` to `1.0`.
+    pub fn fraction(&self) -> f32 {
+        f32::from_bits(self.fraction.load(Ordering::Relaxed))
+    }
+
+    /// Sets how much of the task is done.
+    pub fn set_fraction(&self, fraction: f32) {
+        self.fraction
+            .store(fraction.clamp(0.0, 1.0).to_bits(), Ordering::Relaxed);
+    }
+
+    /// What the task is doing right now.
+    pub fn message(&self) -> String {
+        self.message.lock().unwrap().clone()
+    }
+
+    /// Sets what the task is doing right now.
+    pub fn set_message(&self, message: impl Into<String>) {
+        *self.message.lock().unwrap() = message.into();
+    }
+
+    /// Whether the task was cancelled.
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+}
+
+/// A reader that reports how much of it was read.
+///
+/// Reading fails once the task is cancelled.
+pub struct ProgressReader<'a, R> {
+    inner: R,
+    progress: &'a Progress,
+    /// The part of the task's progress that reading covers.
+    range: (f32, f32),
+    len: u64,
+}
+
+impl<'a, R: Read + Seek> ProgressReader<'a, R> {
+    /// Wraps `inner`, moving `progress` through `range` as it is read.
+    pub fn new(mut inner: R, progress: &'a Progress, range: (f32, f32)) -> io::Result<Self> {
+        let len = inner.seek(SeekFrom::End(0))?;
+        inner.rewind()?;
+
+        Ok(ProgressReader {
+            inner,
+            progress,
+            range,
+            len,
+        })
+    }
+
+    fn report(&mut self) -> io::Result<()> {
+        if self.progress.is_cancelled() {
+            return Err(io::Error::other("cancelled"));
+        }
+
+        let pos = self.inner.stream_position()?;
+        let (start, end) = self.range;
+        let done = if self.len > 0 {
+            pos as f32 / self.len as f32
+        } else {
+            1.0
+        };
+        self.progress.set_fraction(start + (end - start) * done);
+
+        Ok(())
+    }
+}
+
+impl<R: Read + Seek> Read for ProgressReader<'_, R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        self.report()?;
+        Ok(read)
+    }
+}
+
+impl<R: Read + Seek> Seek for ProgressReader<'_, R> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let pos = self.inner.seek(pos)?;
+        self.report()?;
+        Ok(pos)
+    }
+}
+
+/// Applies the results of finished tasks.
+pub fn poll_tasks(world: &mut World) {
+    let finished = {
+        let mut tasks = world.resource_mut::<BackgroundTasks>();
+        let mut running = std::mem::take(&mut tasks.tasks);
+        let mut finished = Vec::new();
+
+        running.retain_mut(
+            |task| match future::block_on(future::poll_once(&mut task.task)) {
+                Some(finish) => {
+                    if !tasks.is_stale(task) {
+                        finished.push(finish);
+                    }
+                    false
+                }
+                // stale tasks are dropped, which cancels them
+                None => !tasks.is_stale(task),
+            },
+        );
+
+        // tasks can't be started while polling, so nothing is lost
+        tasks.tasks = running;
+        finished
+    };
+
+    for finish in finished {
+        finish(world);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::io::Cursor;
+
+    #[test]
+    fn reports_reading() {
+        let progress = Progress::default();
+        let mut reader =
+            ProgressReader::new(Cursor::new(vec![0u8; 100]), &progress, (0.0, 0.5)).unwrap();
+
+        reader.read_exact(&mut [0u8; 50]).unwrap();
+        assert_eq!(progress.fraction(), 0.25);
+
+        progress.cancelled.store(true, Ordering::Relaxed);
+        assert!(reader.read_exact(&mut [0u8; 10]).is_err());
+    }
+
+    #[test]
+    fn drops_stale_results() {
+        AsyncComputeTaskPool::get_or_init(Default::default);
+
+        let mut world = World::new();
+        world.init_resource::<BackgroundTasks>();
+        world.init_resource::<Opened>();
+
+        #[derive(Resource, Default)]
+        struct Opened(Vec<u32>);
+
+        let mut tasks = world.resource_mut::<BackgroundTasks>();
+        for n in [1, 2] {
+            tasks.spawn_latest("open", "Opening", move |_| {
+                Box::new(move |world: &mut World| world.resource_mut::<Opened>().0.push(n))
+            });
+        }
+
+        while !world.resource::<BackgroundTasks>().is_empty() {
+            poll_tasks(&mut world);
+        }
+        assert_eq!(world.resource::<Opened>().0, [2]);
+    }
+}
@@ -0,0 +1,282 @@
+//! A reference drawn under the map.
+//!
+//! The underlay is another map or an image, like a layout sketch or a
+//! screenshot of an older version of the track, drawn faintly under the map
+//! to trace over. It is remembered for each map in the [`Session`].
+
+use std::path::{Path, PathBuf};
+
+use bevy::prelude::*;
+use bevy::render::render_asset::RenderAssetUsages;
+use bevy::render::texture::{CompressedImageFormats, ImageSampler, ImageType};
+use bevy::sprite::{Anchor, MaterialMesh2dBundle, Mesh2dHandle};
+use serde::{Deserialize, Serialize};
+
+use super::lod;
+use super::session::{self, MapFile, Session};
+use super::tasks::BackgroundTasks;
+use crate::map::query::Segment;
+
+/// Where the underlay is drawn.
+///
+/// The 2D camera doesn't draw anything below zero, so this is as low as the
+/// underlay can go while still being under the linedefs.
+const UNDERLAY_Z: f32 = -0.05;
+
+/// The color of the linedefs of map underlays.
+const MAP_COLOR: Color = Color::rgb(0.5, 0.7, 1.0);
+
+/// What is drawn under a map.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct UnderlaySettings {
+    /// The map file it is drawn under.
+    pub map: PathBuf,
+    /// The file drawn, a WAD or an image.
+    pub path: PathBuf,
+    /// Where the bottom left corner of an image, or the origin of a map, is.
+    pub x: f32,
+    pub y: f32,
+    /// Map units per pixel of an image, or how much a map is scaled.
+    pub scale: f32,
+    pub opacity: f32,
+}
+
+impl UnderlaySettings {
+    /// Creates settings drawing `path` under `map`, untouched.
+    pub fn new(map: impl Into<PathBuf>, path: impl Into<PathBuf>) -> UnderlaySettings {
+        UnderlaySettings {
+            map: map.into(),
+            path: path.into(),
+            x: 0.0,
+            y: 0.0,
+            scale: 1.0,
+            opacity: 0.5,
+        }
+    }
+
+    /// Whether the underlay is a map, instead of an image.
+    pub fn is_map(&self) -> bool {
+        is_wad(&self.path)
+    }
+}
+
+/// The underlay of the open map.
+#[derive(Resource, Debug, Default)]
+pub struct Underlay {
+    pub settings: Option<UnderlaySettings>,
+    /// Why the underlay couldn't be read.
+    pub error: Option<String>,
+    /// The file that was last read.
+    read: Option<PathBuf>,
+    drawn: Option<Drawn>,
+}
+
+impl Underlay {
+    /// Whether the underlay is still being read.
+    pub fn is_loading(&self) -> bool {
+        self.settings.is_some() && self.drawn.is_none() && self.error.is_none()
+    }
+}
+
+/// A read underlay, ready to draw.
+#[derive(Debug)]
+enum Drawn {
+    Image(Handle<Image>),
+    Lines(Handle<Mesh>),
+}
+
+/// Tag for the underlay entity.
+#[derive(Component, Clone, Copy, Debug, Default)]
+pub struct UnderlayEntity;
+
+/// Brings back the underlay of maps as they are opened.
+///
+/// Maps saved to a new file keep their underlay.
+pub fn restore_underlay(
+    files: Query<Ref<MapFile>, Changed<MapFile>>,
+    session: Res<Session>,
+    mut underlay: ResMut<Underlay>,
+) {
+    let Ok(file) = files.get_single() else {
+        return;
+    };
+
+    if file.is_added() {
+        underlay.settings = session
+            .underlays
+            .iter()
+            .find(|settings| settings.map == file.0)
+            .cloned();
+    } else if let Some(settings) = &mut underlay.settings {
+        settings.map = file.0.clone();
+    }
+}
+
+/// Keeps the [`Session`] up to date with the underlay of the open map.
+pub fn remember_underlay(
+    underlay: Res<Underlay>,
+    files: Query<&MapFile>,
+    mut session: ResMut<Session>,
+) {
+    if !underlay.is_changed() {
+        return;
+    }
+    let Ok(MapFile(map)) = files.get_single() else {
+        return;
+    };
+
+    session.underlays.retain(|settings| settings.map != *map);
+    if let Some(settings) = underlay.settings.as_ref().filter(|s| s.map == *map) {
+        session.underlays.push(settings.clone());
+    }
+}
+
+/// Starts reading the underlay when its file changes.
+pub fn load_underlay(mut underlay: ResMut<Underlay>, mut tasks: ResMut<BackgroundTasks>) {
+    let path = underlay.settings.as_ref().map(|s| s.path.clone());
+
+    if underlay.read == path {
+        return;
+    }
+
+    underlay.read = path.clone();
+    underlay.drawn = None;
+    underlay.error = None;
+
+    let Some(path) = path else {
+        return;
+    };
+
+    tasks.spawn(
+        format!("Reading underlay {}", path.display()),
+        move |progress| {
+            let read = if is_wad(&path) {
+                session::load_map(&path, progress)
+                    .map(|(_, map)| {
+                        let lines = map
+                            .linedefs
+                            .iter()
+                            .filter_map(|linedef| map.linedef_segment(linedef))
+                            .collect::<Vec<Segment>>();
+                        Read::Lines(lod::line_mesh(lines))
+                    })
+                    .map_err(|err| err.to_string())
+            } else {
+                progress.set_message("Reading image");
+                read_image(&path).map(Read::Image)
+            };
+
+            Box::new(move |world: &mut World| {
+                // the underlay may have changed since
+                if world.resource::<Underlay>().read.as_ref() != Some(&path) {
+                    return;
+                }
+
+                let drawn = match read {
+                    Ok(Read::Image(image)) => Ok(Drawn::Image(
+                        world.resource_mut::<Assets<Image>>().add(image),
+                    )),
+                    Ok(Read::Lines(mesh)) => {
+                        Ok(Drawn::Lines(world.resource_mut::<Assets<Mesh>>().add(mesh)))
+                    }
+                    Err(err) => Err(err),
+                };
+
+                let mut underlay = world.resource_mut::<Underlay>();
+                match drawn {
+                    Ok(drawn) => underlay.drawn = Some(drawn),
+                    Err(err) => {
+                        warn!("failed to read underlay {}: {}", path.display(), err);
+                        underlay.error = Some(err);
+                    }
+                }
+            })
+        },
+    );
+}
+
+/// An underlay read in the background.
+enum Read {
+    Image(Image),
+    Lines(Mesh),
+}
+
+/// Redraws the underlay when it changes.
+pub fn draw_underlay(
+    mut commands: Commands,
+    underlay: Res<Underlay>,
+    entities: Query<Entity, With<UnderlayEntity>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+) {
+    if !underlay.is_changed() {
+        return;
+    }
+
+    for entity in entities.iter() {
+        commands.entity(entity).despawn();
+    }
+
+    let (Some(settings), Some(drawn)) = (&underlay.settings, &underlay.drawn) else {
+        return;
+    };
+
+    let transform = Transform::from_xyz(settings.x, settings.y, UNDERLAY_Z).with_scale(Vec3::new(
+        settings.scale,
+        settings.scale,
+        1.0,
+    ));
+
+    match drawn {
+        Drawn::Image(image) => {
+            commands.spawn((
+                SpriteBundle {
+                    texture: image.clone(),
+                    sprite: Sprite {
+                        color: Color::WHITE.with_a(settings.opacity),
+                        anchor: Anchor::BottomLeft,
+                        ..default()
+                    },
+                    transform,
+                    ..default()
+                },
+                UnderlayEntity,
+            ));
+        }
+        Drawn::Lines(mesh) => {
+            commands.spawn((
+                MaterialMesh2dBundle {
+                    mesh: Mesh2dHandle(mesh.clone()),
+                    material: materials
+                        .add(ColorMaterial::from(MAP_COLOR.with_a(settings.opacity))),
+                    transform,
+                    ..default()
+                },
+                UnderlayEntity,
+            ));
+        }
+    }
+}
+
+/// Reads an image file, like a PNG.
+fn read_image(path: &Path) -> Result<Image, String> {
+    let bytes = std::fs::read(path).map_err(|err| err.to_string())?;
+    let extension = path
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .unwrap_or("png");
+
+    Image::from_buffer(
+        &bytes,
+        ImageType::Extension(extension),
+        CompressedImageFormats::NONE,
+        true,
+        ImageSampler::Default,
+        RenderAssetUsages::RENDER_WORLD,
+    )
+    .map_err(|err| err.to_string())
+}
+
+fn is_wad(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("wad"))
+}
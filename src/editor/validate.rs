@@ -0,0 +1,135 @@
+//! Checking the map for problems in the background.
+
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use bevy::prelude::*;
+use bevy::tasks::futures_lite::future;
+use bevy::tasks::{AsyncComputeTaskPool, Task};
+
+use super::history::History;
+use super::Editor;
+use crate::map::validate::{self, Problem, CHECKS};
+use crate::map::Map;
+
+/// How long the map has to stay unchanged before it is checked again.
+pub const DEBOUNCE: Duration = Duration::from_millis(300);
+
+/// The problems in the open map.
+///
+/// The map is checked in the background a little while after it changes, so
+/// big maps never hold up a frame. Each of the [`CHECKS`] replaces its own
+/// problems as soon as it finishes, so the list stays useful while the rest
+/// are still running.
+#[derive(Resource, Default)]
+pub struct Validation {
+    /// The problems found by each check.
+    found: Vec<Vec<Problem>>,
+    /// Every problem found, most severe first.
+    problems: Vec<Problem>,
+    /// When the map last changed, if it hasn't been checked since.
+    changed_at: Option<Duration>,
+    run: Option<Run>,
+}
+
+/// The problems of each check, as they finish.
+type Results = Arc<Mutex<Vec<(usize, Vec<Problem>)>>>;
+
+/// A check of the map running in the background.
+struct Run {
+    task: Task<()>,
+    results: Results,
+}
+
+impl Validation {
+    /// Every problem found, most severe first.
+    pub fn problems(&self) -> &[Problem] {
+        &self.problems
+    }
+
+    /// Whether the map is being checked, or is about to be.
+    pub fn is_checking(&self) -> bool {
+        self.changed_at.is_some() || self.run.is_some()
+    }
+
+    /// Starts checking `map` in the background.
+    ///
+    /// This cancels the last check, if it is still running.
+    fn start(&mut self, map: Arc<Map>) {
+        let results = Results::default();
+        let task = AsyncComputeTaskPool::get().spawn({
+            let results = results.clone();
+
+            async move {
+                for (idx, check) in CHECKS.into_iter().enumerate() {
+                    let mut problems = Vec::new();
+                    check(&map, &mut problems);
+                    results.lock().unwrap().push((idx, problems));
+
+                    // give the task a chance to be cancelled
+                    future::yield_now().await;
+                }
+            }
+        });
+
+        self.changed_at = None;
+        self.run = Some(Run { task, results });
+    }
+}
+
+/// Checks the map again when it changes.
+///
+/// The map is checked from the copy the [`History`] keeps, so it isn't copied
+/// again for every check.
+pub fn validate(
+    time: Res<Time>,
+    history: Res<History>,
+    mut validation: ResMut<Validation>,
+    editors: Query<Ref<Editor>>,
+) {
+    let Ok(editor) = editors.get_single() else {
+        if validation.is_checking() || !validation.problems.is_empty() {
+            *validation = Validation::default();
+        }
+        return;
+    };
+
+    let now = time.elapsed();
+
+    if editor.is_changed() {
+        // whatever is running is checking an old map
+        validation.changed_at = Some(now);
+        validation.run = None;
+    }
+
+    if let Some(changed_at) = validation.changed_at {
+        if now.saturating_sub(changed_at) >= DEBOUNCE {
+            if let Some(map) = history.snapshot() {
+                validation.start(map);
+            }
+        }
+    }
+
+    let Some(run) = &validation.run else {
+        return;
+    };
+
+    // see if it's finished first, so no results are missed
+    let finished = run.task.is_finished();
+    let results = std::mem::take(&mut *run.results.lock().unwrap());
+
+    if !results.is_empty() {
+        validation.found.resize_with(CHECKS.len(), Vec::new);
+        for (idx, problems) in results {
+            validation.found[idx] = problems;
+        }
+
+        let mut problems = validation.found.concat();
+        validate::sort(&mut problems);
+        validation.problems = problems;
+    }
+
+    if finished {
+        validation.run = None;
+    }
+}
@@ -0,0 +1,487 @@
+//! 3D preview of the walls of the map.
+//!
+//! The walls are drawn with a checker pattern in place of their textures, so
+//! that texture offsets can be seen changing while they are nudged.
+
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+use bevy::prelude::*;
+use bevy::render::camera::RenderTarget;
+use bevy::render::mesh::{Indices, PrimitiveTopology};
+use bevy::render::render_asset::RenderAssetUsages;
+use bevy::render::render_resource::{
+    Extent3d, TextureDescriptor, TextureDimension, TextureFormat, TextureUsages,
+};
+use bevy::render::texture::{ImageAddressMode, ImageSampler, ImageSamplerDescriptor};
+use bevy::render::view::NoFrustumCulling;
+
+use super::align::selected_sidedefs;
+use super::{Editor, Selection, SELECTED_COLOR};
+use crate::format::udmf::Value;
+use crate::map::{LineDef, Map, Sector, SideDef};
+
+/// How many map units one tile of the checker pattern covers.
+const TILE: f32 = 64.0;
+
+/// How many pixels one tile of the checker pattern has.
+const TILE_PIXELS: u32 = 8;
+
+/// The size of the preview before it is shown.
+const DEFAULT_SIZE: UVec2 = UVec2::new(640, 360);
+
+/// The 3D preview.
+#[derive(Resource, Debug)]
+pub struct View3d {
+    /// The image the preview is rendered to.
+    pub image: Handle<Image>,
+    /// The size the preview is shown at, in pixels.
+    ///
+    /// The image is resized to this.
+    pub size: UVec2,
+    /// If the preview is shown this frame.
+    ///
+    /// This is set by the UI, and the preview is only rendered while it is.
+    pub shown: bool,
+    mesh: Handle<Mesh>,
+    material: Handle<StandardMaterial>,
+}
+
+impl FromWorld for View3d {
+    fn from_world(world: &mut World) -> View3d {
+        let mut images = world.resource_mut::<Assets<Image>>();
+        let image = images.add(render_target(DEFAULT_SIZE));
+        let checker = images.add(checker());
+
+        let mesh = world.resource_mut::<Assets<Mesh>>().add(wall_mesh(&[]));
+        let material = world
+            .resource_mut::<Assets<StandardMaterial>>()
+            .add(StandardMaterial {
+                base_color_texture: Some(checker),
+                unlit: true,
+                ..default()
+            });
+
+        View3d {
+            image,
+            size: DEFAULT_SIZE,
+            shown: false,
+            mesh,
+            material,
+        }
+    }
+}
+
+/// Tag for the camera of the [`View3d`].
+#[derive(Component, Clone, Copy, Debug, Default)]
+pub struct View3dCamera;
+
+/// Tag for the wall mesh of the [`View3d`].
+#[derive(Component, Clone, Copy, Debug, Default)]
+pub struct View3dWalls;
+
+/// Spawns the camera and walls of the [`View3d`].
+pub fn setup_view3d(mut commands: Commands, view: Res<View3d>) {
+    commands.spawn((
+        Camera3dBundle {
+            camera: Camera {
+                target: RenderTarget::Image(view.image.clone()),
+                // render before the main camera, which shows the image
+                order: -1,
+                is_active: false,
+                clear_color: ClearColorConfig::Custom(Color::rgb(0.1, 0.1, 0.1)),
+                ..default()
+            },
+            ..default()
+        },
+        View3dCamera,
+    ));
+
+    commands.spawn((
+        PbrBundle {
+            mesh: view.mesh.clone(),
+            material: view.material.clone(),
+            // until there are walls to draw
+            visibility: Visibility::Hidden,
+            ..default()
+        },
+        // the bounds are not recomputed as the mesh is rebuilt
+        NoFrustumCulling,
+        View3dWalls,
+    ));
+}
+
+/// Only renders the preview while it is shown, and keeps the image the size
+/// it is shown at.
+pub fn update_view3d(
+    mut view: ResMut<View3d>,
+    mut images: ResMut<Assets<Image>>,
+    mut cameras: Query<&mut Camera, With<View3dCamera>>,
+) {
+    let shown = std::mem::take(&mut view.bypass_change_detection().shown);
+
+    for mut camera in cameras.iter_mut() {
+        camera.is_active = shown;
+    }
+
+    if !shown {
+        return;
+    }
+
+    let size = view.size.max(UVec2::ONE);
+    if let Some(image) = images.get_mut(&view.image) {
+        if image.size() != size {
+            image.resize(extent(size));
+        }
+    }
+}
+
+/// Rebuilds the walls when the map or selection changes.
+pub fn build_walls(
+    view: Res<View3d>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    editors: Query<(Ref<Editor>, Ref<Selection>)>,
+    mut walls: Query<&mut Visibility, With<View3dWalls>>,
+) {
+    let Ok(mut visibility) = walls.get_single_mut() else {
+        return;
+    };
+
+    let Ok((editor, selection)) = editors.get_single() else {
+        *visibility = Visibility::Hidden;
+        return;
+    };
+
+    if !editor.is_changed() && !selection.is_changed() {
+        return;
+    }
+
+    let map = Editor::map(&editor);
+    let selected = selected_sidedefs(map, &selection);
+    let parts = map
+        .linedefs
+        .iter()
+        .flat_map(|linedef| wall_parts(map, linedef))
+        .collect::<Vec<_>>();
+
+    // an empty mesh can't be drawn
+    if parts.is_empty() {
+        *visibility = Visibility::Hidden;
+        return;
+    }
+
+    *visibility = Visibility::Inherited;
+    let parts = parts
+        .into_iter()
+        .map(|part| {
+            let color = if selected.contains(&part.sidedef) {
+                SELECTED_COLOR
+            } else {
+                texture_color(&part.texture)
+            };
+            (part, color)
+        })
+        .collect::<Vec<_>>();
+
+    if let Some(mesh) = meshes.get_mut(&view.mesh) {
+        *mesh = wall_mesh(&parts);
+    }
+}
+
+/// Points the camera at the first selected wall when the selection changes.
+pub fn aim_camera(
+    editors: Query<(&Editor, Ref<Selection>)>,
+    mut cameras: Query<&mut Transform, With<View3dCamera>>,
+) {
+    let Ok((editor, selection)) = editors.get_single() else {
+        return;
+    };
+    let Ok(mut transform) = cameras.get_single_mut() else {
+        return;
+    };
+
+    if !selection.is_changed() {
+        return;
+    }
+
+    let map = editor.map();
+    let Some(part) = selected_sidedefs(map, &selection)
+        .into_iter()
+        .find_map(|sidedef| sidedef_part(map, sidedef))
+    else {
+        return;
+    };
+
+    let along = part.end - part.start;
+    let length = along.length();
+    if length == 0.0 {
+        return;
+    }
+
+    // the front of a wall is on the right of the way its texture runs
+    let normal = Vec2::new(along.y, -along.x) / length;
+    let middle = (part.start + part.end) / 2.0;
+    let height = (part.bottom + part.top) / 2.0;
+    let distance = length.max(part.top - part.bottom) * 0.9 + TILE;
+
+    let target = to_view(middle, height);
+    *transform = Transform::from_translation(to_view(middle + normal * distance, height))
+        .looking_at(target, Vec3::Y);
+}
+
+/// A part of a wall, between two heights.
+#[derive(Clone, Debug, PartialEq)]
+struct WallPart {
+    sidedef: usize,
+    /// Where the texture starts, in map units.
+    start: Vec2,
+    /// Where the texture ends, in map units.
+    end: Vec2,
+    bottom: f32,
+    top: f32,
+    offset: Vec2,
+    texture: String,
+}
+
+/// The parts of the walls of both sides of `linedef`.
+///
+/// One-sided walls are a middle texture from floor to ceiling. Two-sided walls
+/// have a lower texture up to the floor of the other side, an upper texture
+/// down to its ceiling, and a middle texture in between if it is set.
+fn wall_parts(map: &Map, linedef: &LineDef) -> Vec<WallPart> {
+    let (Some(v1), Some(v2)) = (
+        map.vertices.get(linedef.v1 as usize),
+        map.vertices.get(linedef.v2 as usize),
+    ) else {
+        return Vec::new();
+    };
+    let (v1, v2) = (Vec2::new(v1.x, v1.y), Vec2::new(v2.x, v2.y));
+
+    let front = (linedef.side_front, linedef.side_back, v1, v2);
+    let back = linedef
+        .side_back
+        .map(|side| (side, Some(linedef.side_front), v2, v1));
+
+    let mut parts = Vec::new();
+
+    for (side, other, start, end) in std::iter::once(front).chain(back) {
+        let Some((sidedef, sector)) = side_sector(map, side) else {
+            continue;
+        };
+        let (floor, ceiling) = (sector.height_floor as f32, sector.height_ceiling as f32);
+
+        let mut part = |field: &str, bottom: f32, top: f32| {
+            let texture = match sidedef.extras.get(field) {
+                Some(Value::String(texture)) if texture != "-" => texture,
+                _ => return,
+            };
+            if top <= bottom {
+                return;
+            }
+
+            parts.push(WallPart {
+                sidedef: side as usize,
+                start,
+                end,
+                bottom,
+                top,
+                offset: Vec2::new(sidedef.offset_x as f32, sidedef.offset_y as f32),
+                texture: texture.clone(),
+            });
+        };
+
+        match other.and_then(|other| side_sector(map, other)) {
+            Some((_, other)) => {
+                let (other_floor, other_ceiling) =
+                    (other.height_floor as f32, other.height_ceiling as f32);
+
+                part("texturebottom", floor, other_floor);
+                part("texturetop", other_ceiling, ceiling);
+                part(
+                    "texturemiddle",
+                    floor.max(other_floor),
+                    ceiling.min(other_ceiling),
+                );
+            }
+            None => part("texturemiddle", floor, ceiling),
+        }
+    }
+
+    parts
+}
+
+/// The sidedef `side`, and the sector it faces.
+fn side_sector(map: &Map, side: i32) -> Option<(&SideDef, &Sector)> {
+    let sidedef = map.sidedefs.get(side as usize)?;
+    let sector = map.sectors.get(sidedef.sector as usize)?;
+    Some((sidedef, sector))
+}
+
+/// The tallest part of the wall of `sidedef`.
+fn sidedef_part(map: &Map, sidedef: usize) -> Option<WallPart> {
+    let linedef = map.linedefs.iter().find(|linedef| {
+        linedef.side_front as usize == sidedef
+            || linedef
+                .side_back
+                .is_some_and(|side| side as usize == sidedef)
+    })?;
+
+    wall_parts(map, linedef)
+        .into_iter()
+        .filter(|part| part.sidedef == sidedef)
+        .max_by(|a, b| (a.top - a.bottom).total_cmp(&(b.top - b.bottom)))
+}
+
+/// Converts a point on the map and a height to the space of the preview.
+fn to_view(point: Vec2, height: f32) -> Vec3 {
+    Vec3::new(point.x, height, -point.y)
+}
+
+/// Builds the mesh of the wall parts, tinting each with its color.
+fn wall_mesh(parts: &[(WallPart, Color)]) -> Mesh {
+    let mut positions = Vec::with_capacity(parts.len() * 4);
+    let mut normals = Vec::with_capacity(parts.len() * 4);
+    let mut uvs = Vec::with_capacity(parts.len() * 4);
+    let mut colors = Vec::with_capacity(parts.len() * 4);
+    let mut indices = Vec::with_capacity(parts.len() * 6);
+
+    for (part, color) in parts {
+        let along = part.end - part.start;
+        let length = along.length();
+        let normal = Vec2::new(along.y, -along.x).normalize_or_zero();
+
+        // textures hang down from the top of the part
+        let corners = [
+            (part.start, part.bottom, 0.0),
+            (part.end, part.bottom, length),
+            (part.end, part.top, length),
+            (part.start, part.top, 0.0),
+        ];
+
+        let first = positions.len() as u32;
+        for (point, height, u) in corners {
+            positions.push(to_view(point, height).to_array());
+            normals.push(to_view(normal, 0.0).to_array());
+            uvs.push([
+                (u + part.offset.x) / TILE,
+                (part.top - height + part.offset.y) / TILE,
+            ]);
+            colors.push(color.as_linear_rgba_f32());
+        }
+        // counter-clockwise seen from the front
+        indices.extend([0, 1, 2, 0, 2, 3].map(|i| first + i));
+    }
+
+    Mesh::new(
+        PrimitiveTopology::TriangleList,
+        RenderAssetUsages::RENDER_WORLD,
+    )
+    .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions)
+    .with_inserted_attribute(Mesh::ATTRIBUTE_NORMAL, normals)
+    .with_inserted_attribute(Mesh::ATTRIBUTE_UV_0, uvs)
+    .with_inserted_attribute(Mesh::ATTRIBUTE_COLOR, colors)
+    .with_inserted_indices(Indices::U32(indices))
+}
+
+/// A color for each texture, so walls with the same texture look the same.
+fn texture_color(texture: &str) -> Color {
+    let mut hasher = DefaultHasher::new();
+    texture.to_ascii_uppercase().hash(&mut hasher);
+    let hue = (hasher.finish() % 360) as f32;
+
+    Color::hsl(hue, 0.5, 0.7)
+}
+
+fn extent(size: UVec2) -> Extent3d {
+    Extent3d {
+        width: size.x,
+        height: size.y,
+        depth_or_array_layers: 1,
+    }
+}
+
+/// An image a camera can render to, and egui can show.
+fn render_target(size: UVec2) -> Image {
+    let mut image = Image {
+        texture_descriptor: TextureDescriptor {
+            label: None,
+            size: extent(size),
+            dimension: TextureDimension::D2,
+            format: TextureFormat::Bgra8UnormSrgb,
+            mip_level_count: 1,
+            sample_count: 1,
+            usage: TextureUsages::TEXTURE_BINDING
+                | TextureUsages::COPY_DST
+                | TextureUsages::RENDER_ATTACHMENT,
+            view_formats: &[],
+        },
+        ..default()
+    };
+    image.resize(extent(size));
+    image
+}
+
+/// A repeating two by two checker pattern of tiles.
+fn checker() -> Image {
+    let size = TILE_PIXELS * 2;
+    let data = (0..size * size)
+        .flat_map(|i| {
+            let (x, y) = (i % size, i / size);
+            let value = if (x / TILE_PIXELS + y / TILE_PIXELS).is_multiple_of(2) {
+                255
+            } else {
+                160
+            };
+            [value, value, value, 255]
+        })
+        .collect();
+
+    let mut image = Image::new(
+        extent(UVec2::splat(size)),
+        TextureDimension::D2,
+        data,
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::RENDER_WORLD,
+    );
+    image.sampler = ImageSampler::Descriptor(ImageSamplerDescriptor {
+        address_mode_u: ImageAddressMode::Repeat,
+        address_mode_v: ImageAddressMode::Repeat,
+        ..ImageSamplerDescriptor::nearest()
+    });
+    image
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wall_parts_of_step() {
+        let map = Map::from_str(
+            r#"
+            namespace = "ringracers";
+            version = 1;
+
+            vertex { x = 0.0; y = 0.0; }
+            vertex { x = 0.0; y = 64.0; }
+
+            linedef { v1 = 0; v2 = 1; sidefront = 0; sideback = 1; twosided = true; }
+
+            sidedef { sector = 0; texturebottom = "STEP"; texturetop = "-"; offsetx = 8; }
+            sidedef { sector = 1; texturebottom = "STEP"; }
+
+            sector { heightfloor = 0; heightceiling = 128; texturefloor = "F"; textureceiling = "C"; }
+            sector { heightfloor = 32; heightceiling = 128; texturefloor = "F"; textureceiling = "C"; }
+            "#,
+        )
+        .unwrap();
+
+        let parts = wall_parts(&map, &map.linedefs[0]);
+        // only the front has a step up
+        assert_eq!(parts.len(), 1);
+        assert_eq!(parts[0].sidedef, 0);
+        assert_eq!((parts[0].bottom, parts[0].top), (0.0, 32.0));
+        assert_eq!(parts[0].offset, Vec2::new(8.0, 0.0));
+        assert_eq!(parts[0].texture, "STEP");
+    }
+}
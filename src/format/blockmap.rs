@@ -0,0 +1,105 @@
+//! The `BLOCKMAP` lump.
+//!
+//! The blockmap splits the map into a grid of 128 unit cells, each listing the
+//! linedefs that touch it, to speed up collision checks.
+
+use std::fmt::{self, Display, Formatter};
+
+use super::wad::ByteRead;
+
+/// The size of a blockmap cell, in map units.
+pub const CELL_SIZE: f32 = 128.0;
+
+/// A map's blockmap.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Blockmap {
+    /// The bottom left corner of the grid, in map units.
+    pub origin_x: i16,
+    pub origin_y: i16,
+    pub columns: usize,
+    pub rows: usize,
+    /// The linedefs in each cell, row by row from the bottom.
+    pub cells: Vec<Vec<usize>>,
+}
+
+impl Blockmap {
+    /// Reads the contents of a `BLOCKMAP` lump.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Blockmap, Error> {
+        // everything is made of 16-bit words
+        let words = u16::read_all(bytes).map_err(|_| Error::UnexpectedEof)?;
+
+        let [origin_x, origin_y, columns, rows, ..] = words[..] else {
+            return Err(Error::UnexpectedEof);
+        };
+        let (columns, rows) = (columns as usize, rows as usize);
+
+        let offsets = words
+            .get(4..4 + columns * rows)
+            .ok_or(Error::UnexpectedEof)?;
+        let cells = offsets
+            .iter()
+            .map(|&offset| {
+                let list = words
+                    .get(offset as usize..)
+                    .ok_or(Error::BadOffset(offset))?;
+                let end = list
+                    .iter()
+                    .position(|&word| word == 0xffff)
+                    .ok_or(Error::UnexpectedEof)?;
+
+                // lists start with a 0 that isn't a linedef
+                Ok(list[..end]
+                    .iter()
+                    .skip(1)
+                    .map(|&linedef| linedef as usize)
+                    .collect())
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+
+        Ok(Blockmap {
+            origin_x: origin_x as i16,
+            origin_y: origin_y as i16,
+            columns,
+            rows,
+            cells,
+        })
+    }
+}
+
+/// An error for reading blockmaps.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    UnexpectedEof,
+    BadOffset(u16),
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::UnexpectedEof => write!(f, "got eof"),
+            Error::BadOffset(offset) => write!(f, "block list out of bounds: {}", offset),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn read_blockmap() {
+        let words: [i16; 12] = [-64, -128, 2, 1, 6, 9, 0, 3, -1, 0, 1, -1];
+        let bytes = words
+            .iter()
+            .flat_map(|word| word.to_le_bytes())
+            .collect::<Vec<_>>();
+
+        let blockmap = Blockmap::from_bytes(&bytes).unwrap();
+
+        assert_eq!((blockmap.origin_x, blockmap.origin_y), (-64, -128));
+        assert_eq!((blockmap.columns, blockmap.rows), (2, 1));
+        assert_eq!(blockmap.cells, [vec![3], vec![1]]);
+    }
+}
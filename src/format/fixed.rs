@@ -0,0 +1,49 @@
+//! 16.16 fixed point numbers.
+
+use std::fmt::{self, Display, Formatter};
+use std::io::{Read, Seek};
+
+use super::wad::{self, ByteRead};
+
+/// A 16.16 fixed point number.
+///
+/// Doom stores positions in binary lumps like this, with the top 16 bits the
+/// whole part and the bottom 16 the fraction.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Fixed(pub i32);
+
+impl Fixed {
+    /// One whole unit.
+    pub const ONE: Fixed = Fixed(1 << 16);
+
+    /// Converts a float, rounding to the nearest fraction.
+    pub fn from_f32(value: f32) -> Fixed {
+        Fixed((value * Fixed::ONE.0 as f32).round() as i32)
+    }
+
+    /// Converts to a float.
+    pub fn to_f32(self) -> f32 {
+        self.0 as f32 / Fixed::ONE.0 as f32
+    }
+}
+
+impl From<Fixed> for f32 {
+    fn from(value: Fixed) -> f32 {
+        value.to_f32()
+    }
+}
+
+impl Display for Fixed {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        Display::fmt(&self.to_f32(), f)
+    }
+}
+
+impl ByteRead for Fixed {
+    fn read<R>(r: R) -> Result<Fixed, wad::Error>
+    where
+        R: Read + Seek,
+    {
+        i32::read(r).map(Fixed)
+    }
+}
@@ -1,4 +1,8 @@
 //! Special text/binary formats.
 
+pub mod blockmap;
+pub mod fixed;
+pub mod nodes;
+pub mod pk3;
 pub mod udmf;
 pub mod wad;
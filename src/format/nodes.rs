@@ -0,0 +1,333 @@
+//! BSP nodes in the `ZNODES` lump.
+//!
+//! Nodebuilders like ZDBSP write extended nodes for UDMF maps. Only the
+//! uncompressed formats are read here: `XNOD`, `XGLN`, `XGL2` and `XGL3`.
+
+use std::fmt::{self, Display, Formatter};
+use std::io::Cursor;
+use std::ops::Range;
+
+use super::fixed::Fixed;
+use super::wad::{self, byte_record, ByteRead};
+
+/// The bit set on a [`Node`] child that points to a subsector.
+const SUBSECTOR_BIT: u32 = 0x8000_0000;
+
+/// The BSP tree of a map.
+#[derive(Clone, Debug, Default)]
+pub struct Nodes {
+    /// How many vertices of the map the nodes were built with.
+    ///
+    /// Vertex indices past these are in [`Nodes::vertices`].
+    pub map_vertices: usize,
+    /// The vertices the nodebuilder added, in map units.
+    pub vertices: Vec<[f32; 2]>,
+    /// The segs of each subsector.
+    pub subsectors: Vec<Range<usize>>,
+    pub segs: Vec<Seg>,
+    /// The nodes, with the root last.
+    pub nodes: Vec<Node>,
+}
+
+/// Part of the edge of a subsector.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Seg {
+    pub v1: usize,
+    pub v2: usize,
+    /// The linedef the seg runs along, or `None` for minisegs.
+    pub linedef: Option<usize>,
+}
+
+/// A split of the map into two halves.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Node {
+    /// A point on the partition line, in map units.
+    pub x: f32,
+    pub y: f32,
+    /// The direction of the partition line.
+    pub dx: f32,
+    pub dy: f32,
+    /// The bounding boxes of the right and left children, as top, bottom,
+    /// left and right.
+    pub bbox: [[f32; 4]; 2],
+    /// The right and left children.
+    pub children: [Child; 2],
+}
+
+/// What is on one side of a [`Node`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Child {
+    Node(usize),
+    Subsector(usize),
+}
+
+/// The kinds of extended nodes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Format {
+    /// Normal nodes.
+    Xnod,
+    /// GL nodes, with 16-bit linedef indices.
+    Xgln,
+    /// GL nodes, with 32-bit linedef indices.
+    Xgl2,
+    /// GL nodes, with fixed point partition lines.
+    Xgl3,
+}
+
+impl Nodes {
+    /// Reads the contents of a `ZNODES` lump.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Nodes, Error> {
+        let mut r = Cursor::new(bytes);
+
+        let format = match &<[u8; 4]>::read(&mut r)? {
+            b"XNOD" => Format::Xnod,
+            b"XGLN" => Format::Xgln,
+            b"XGL2" => Format::Xgl2,
+            b"XGL3" => Format::Xgl3,
+            ident @ (b"ZNOD" | b"ZGLN" | b"ZGL2" | b"ZGL3") => {
+                return Err(Error::Compressed(
+                    String::from_utf8_lossy(ident).into_owned(),
+                ))
+            }
+            ident => {
+                return Err(Error::UnknownFormat(
+                    String::from_utf8_lossy(ident).into_owned(),
+                ))
+            }
+        };
+
+        let map_vertices = u32::read(&mut r)? as usize;
+        let vertices = (0..u32::read(&mut r)?)
+            .map(|_| Ok(<[Fixed; 2]>::read(&mut r)?.map(Fixed::to_f32)))
+            .collect::<Result<Vec<_>, Error>>()?;
+
+        let mut start = 0;
+        let subsectors = (0..u32::read(&mut r)?)
+            .map(|_| {
+                let end = start + u32::read(&mut r)? as usize;
+                let segs = start..end;
+                start = end;
+                Ok(segs)
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+
+        let mut segs = Vec::new();
+        for _ in 0..u32::read(&mut r)? {
+            // gl segs have their partner seg, on the other side of the line,
+            // where other segs have their second vertex
+            let (v1, v2, linedef) = match format {
+                Format::Xnod | Format::Xgln => {
+                    let seg = SegRecord::read(&mut r)?;
+                    let linedef = Some(seg.linedef as u32).filter(|&l| l != 0xffff);
+                    (seg.v1, seg.v2, linedef)
+                }
+                Format::Xgl2 | Format::Xgl3 => {
+                    let seg = WideSegRecord::read(&mut r)?;
+                    let linedef = Some(seg.linedef).filter(|&l| l != 0xffff_ffff);
+                    (seg.v1, seg.v2, linedef)
+                }
+            };
+
+            segs.push(Seg {
+                v1: v1 as usize,
+                v2: v2 as usize,
+                linedef: linedef.map(|l| l as usize),
+            });
+        }
+
+        // the second vertex of gl segs is the first of the next seg
+        if format != Format::Xnod {
+            for subsector in subsectors.iter() {
+                for seg in subsector.clone() {
+                    let next = if seg + 1 < subsector.end {
+                        seg + 1
+                    } else {
+                        subsector.start
+                    };
+
+                    match (segs.get(next), segs.get(seg)) {
+                        (Some(&Seg { v1, .. }), Some(_)) => segs[seg].v2 = v1,
+                        _ => return Err(Error::UnexpectedEof),
+                    }
+                }
+            }
+        }
+
+        let mut nodes = Vec::new();
+        for _ in 0..u32::read(&mut r)? {
+            let partition = match format {
+                Format::Xgl3 => <[Fixed; 4]>::read(&mut r)?.map(Fixed::to_f32),
+                _ => <[i16; 4]>::read(&mut r)?.map(f32::from),
+            };
+            let bbox = <[[i16; 4]; 2]>::read(&mut r)?.map(|bbox| bbox.map(f32::from));
+            let children = <[u32; 2]>::read(&mut r)?.map(|idx| {
+                if idx & SUBSECTOR_BIT != 0 {
+                    Child::Subsector((idx & !SUBSECTOR_BIT) as usize)
+                } else {
+                    Child::Node(idx as usize)
+                }
+            });
+
+            let [x, y, dx, dy] = partition;
+            nodes.push(Node {
+                x,
+                y,
+                dx,
+                dy,
+                bbox,
+                children,
+            });
+        }
+
+        Ok(Nodes {
+            map_vertices,
+            vertices,
+            subsectors,
+            segs,
+            nodes,
+        })
+    }
+
+    /// The root node.
+    pub fn root(&self) -> Option<usize> {
+        self.nodes.len().checked_sub(1)
+    }
+
+    /// How many levels deep the tree goes.
+    pub fn depth(&self) -> usize {
+        let mut depth = 0;
+        let mut level = Vec::from_iter(self.root());
+
+        while !level.is_empty() {
+            depth += 1;
+            level = level
+                .into_iter()
+                .filter_map(|idx| self.nodes.get(idx))
+                .flat_map(|node| node.children)
+                .filter_map(|child| match child {
+                    Child::Node(idx) => Some(idx),
+                    Child::Subsector(_) => None,
+                })
+                .collect();
+
+            // a broken tree could loop forever
+            if depth > self.nodes.len() {
+                break;
+            }
+        }
+
+        depth
+    }
+}
+
+byte_record! {
+    /// A seg of `XNOD` and `XGLN` nodes.
+    struct SegRecord {
+        v1: u32,
+        /// The second vertex, or the partner seg of gl segs.
+        v2: u32,
+        linedef: u16,
+        _side: u8,
+    }
+}
+
+byte_record! {
+    /// A seg of `XGL2` and `XGL3` nodes, with a 32-bit linedef.
+    struct WideSegRecord {
+        v1: u32,
+        /// The partner seg.
+        v2: u32,
+        linedef: u32,
+        _side: u8,
+    }
+}
+
+/// An error for reading nodes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    UnexpectedEof,
+    UnknownFormat(String),
+    Compressed(String),
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::UnexpectedEof => write!(f, "got eof"),
+            Error::UnknownFormat(ident) => write!(f, "unknown nodes format: \"{}\"", ident),
+            Error::Compressed(ident) => {
+                write!(f, "compressed nodes are not supported: \"{}\"", ident)
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<wad::Error> for Error {
+    fn from(_: wad::Error) -> Error {
+        // reading from memory can only run out
+        Error::UnexpectedEof
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn read_gl_nodes() {
+        let mut bytes = b"XGLN".to_vec();
+        // four map vertices, one new one at (32, 0)
+        bytes.extend(4u32.to_le_bytes());
+        bytes.extend(1u32.to_le_bytes());
+        bytes.extend((32i32 << 16).to_le_bytes());
+        bytes.extend(0i32.to_le_bytes());
+        // two subsectors of three segs
+        bytes.extend(2u32.to_le_bytes());
+        bytes.extend(3u32.to_le_bytes());
+        bytes.extend(3u32.to_le_bytes());
+        bytes.extend(6u32.to_le_bytes());
+        for (v1, linedef) in [(0, 0), (4, 0xffff), (3, 3), (4, 0), (1, 1), (2, 0xffff)] {
+            bytes.extend((v1 as u32).to_le_bytes());
+            bytes.extend(0u32.to_le_bytes());
+            bytes.extend((linedef as u16).to_le_bytes());
+            bytes.push(0);
+        }
+        // one node splitting them
+        bytes.extend(1u32.to_le_bytes());
+        for value in [32i16, 0, 0, 64, 64, 0, 0, 32, 64, 0, 32, 64] {
+            bytes.extend(value.to_le_bytes());
+        }
+        bytes.extend((SUBSECTOR_BIT | 1).to_le_bytes());
+        bytes.extend(SUBSECTOR_BIT.to_le_bytes());
+
+        let nodes = Nodes::from_bytes(&bytes).unwrap();
+
+        assert_eq!(nodes.map_vertices, 4);
+        assert_eq!(nodes.vertices, [[32.0, 0.0]]);
+        assert_eq!(nodes.subsectors, [0..3, 3..6]);
+        assert_eq!(
+            nodes.segs[1],
+            Seg {
+                v1: 4,
+                v2: 3,
+                linedef: None
+            }
+        );
+        // wraps around to the start of the subsector
+        assert_eq!(nodes.segs[5].v2, 4);
+        assert_eq!(nodes.root(), Some(0));
+        assert_eq!(nodes.depth(), 1);
+        assert_eq!(
+            nodes.nodes[0].children,
+            [Child::Subsector(1), Child::Subsector(0)]
+        );
+        assert_eq!(nodes.nodes[0].dy, 64.0);
+
+        assert_eq!(
+            Nodes::from_bytes(b"ZNOD").unwrap_err(),
+            Error::Compressed("ZNOD".into())
+        );
+    }
+}
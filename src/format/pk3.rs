@@ -0,0 +1,377 @@
+//! Reading PK3 archives.
+//!
+//! A PK3 is a zip file, with folders in place of the markers of a WAD. It is
+//! read into a [`Wad`] laid out the way the same lumps would be in a WAD, so
+//! it can be looked at like one:
+//!
+//! * Files at the root are lumps of their own.
+//! * WADs in `maps/` have their lumps put in as they are, so their maps are
+//!   found by their markers.
+//! * Every other folder is put between `X_START` and `X_END` markers, like
+//!   `S_START` for `sprites/`.
+//!
+//! Lumps are named after their files, without the extension, and keep their
+//! full names.
+
+use std::collections::BTreeMap;
+use std::io::{Cursor, Read, Seek, SeekFrom};
+use std::path::Path;
+
+use flate2::read::DeflateDecoder;
+
+use super::wad::{byte_record, ByteRead, Error, Wad, WadType};
+
+/// The signature of the end of the central directory.
+const END_SIGNATURE: u32 = 0x06054b50;
+
+/// The signature of a central directory entry.
+const ENTRY_SIGNATURE: u32 = 0x02014b50;
+
+/// The signature of the header before each file.
+const LOCAL_SIGNATURE: u32 = 0x04034b50;
+
+/// How far from the end the end of the central directory can be, with the
+/// longest comment.
+const MAX_END_SIZE: u64 = 22 + u16::MAX as u64;
+
+/// The most that is set aside up front for what the zip says is coming.
+///
+/// Sizes in a zip can't be trusted, so anything past this grows as it is
+/// actually read.
+const MAX_PREALLOC: usize = 1 << 20;
+
+byte_record! {
+    /// The end of the central directory, after its signature.
+    struct EndRecord {
+        _disk: u16,
+        _directory_disk: u16,
+        _disk_entries: u16,
+        entries: u16,
+        _directory_size: u32,
+        directory_offset: u32,
+        _comment_len: u16,
+    }
+}
+
+byte_record! {
+    /// A central directory entry, after its signature.
+    struct EntryRecord {
+        _version_made_by: u16,
+        _version_needed: u16,
+        flags: u16,
+        method: u16,
+        _time: u16,
+        _date: u16,
+        _crc: u32,
+        compressed_size: u32,
+        size: u32,
+        name_len: u16,
+        extra_len: u16,
+        comment_len: u16,
+        _disk: u16,
+        _internal_attributes: u16,
+        _external_attributes: u32,
+        offset: u32,
+    }
+}
+
+byte_record! {
+    /// The header before each file, after its signature.
+    struct LocalRecord {
+        _version_needed: u16,
+        _flags: u16,
+        _method: u16,
+        _time: u16,
+        _date: u16,
+        _crc: u32,
+        _compressed_size: u32,
+        _size: u32,
+        name_len: u16,
+        extra_len: u16,
+    }
+}
+
+/// A file in a PK3.
+struct File {
+    /// The path of the file, with `/` between folders.
+    path: String,
+    data: Vec<u8>,
+}
+
+/// Reads a PK3 into a [`Wad`], see the [module docs](self).
+///
+/// Only stored and deflated files are read, which is what every tool writes.
+pub fn read_pk3<R>(mut r: R) -> Result<Wad, Error>
+where
+    R: Read + Seek,
+{
+    let files = read_files(&mut r)?;
+
+    let mut root = Vec::new();
+    let mut maps = Vec::new();
+    let mut folders = BTreeMap::<String, Vec<File>>::new();
+
+    for file in files {
+        match file.path.split_once('/') {
+            None => root.push(file),
+            Some((folder, _)) if folder.eq_ignore_ascii_case("maps") => maps.push(file),
+            Some((folder, _)) => folders
+                .entry(folder.to_ascii_lowercase())
+                .or_default()
+                .push(file),
+        }
+    }
+
+    let mut wad = Wad::new(WadType::Pwad);
+
+    for file in root {
+        wad.push(lump_name(&file.path), file.data);
+    }
+
+    for file in maps {
+        match Wad::from_reader(Cursor::new(&file.data)) {
+            Ok(map) => {
+                for lump in map.lumps() {
+                    wad.push(lump.name(), lump.data());
+                }
+            }
+            // not every file in maps is a map
+            Err(_) => wad.push(lump_name(&file.path), file.data),
+        }
+    }
+
+    for (folder, files) in folders {
+        let prefix = namespace_prefix(&folder);
+
+        wad.push(format!("{}_START", prefix), Vec::new());
+        for file in files {
+            wad.push(lump_name(&file.path), file.data);
+        }
+        wad.push(format!("{}_END", prefix), Vec::new());
+    }
+
+    Ok(wad)
+}
+
+/// Whether the file at `path` is a PK3, by its extension.
+pub fn is_pk3(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("pk3"))
+}
+
+/// Reads every file of a zip, skipping folders.
+fn read_files<R>(mut r: R) -> Result<Vec<File>, Error>
+where
+    R: Read + Seek,
+{
+    let end = read_end(&mut r)?;
+
+    r.seek(SeekFrom::Start(end.directory_offset as u64))?;
+    let mut entries = Vec::with_capacity(
+        (end.entries as usize).min(MAX_PREALLOC / std::mem::size_of::<(String, EntryRecord)>()),
+    );
+
+    for _ in 0..end.entries {
+        if u32::read(&mut r)? != ENTRY_SIGNATURE {
+            return Err(Error::InvalidPk3("bad central directory"));
+        }
+
+        let entry = EntryRecord::read(&mut r)?;
+        let path = read_name(&mut r, entry.name_len)?;
+        r.seek(SeekFrom::Current(
+            entry.extra_len as i64 + entry.comment_len as i64,
+        ))?;
+
+        entries.push((path, entry));
+    }
+
+    let mut files = Vec::new();
+
+    for (path, entry) in entries {
+        if path.ends_with('/') {
+            continue;
+        }
+        if entry.flags & 1 != 0 {
+            return Err(Error::InvalidPk3("encrypted files can't be read"));
+        }
+
+        r.seek(SeekFrom::Start(entry.offset as u64))?;
+        if u32::read(&mut r)? != LOCAL_SIGNATURE {
+            return Err(Error::InvalidPk3("bad file header"));
+        }
+        let local = LocalRecord::read(&mut r)?;
+        r.seek(SeekFrom::Current(
+            local.name_len as i64 + local.extra_len as i64,
+        ))?;
+
+        let compressed = (&mut r).take(entry.compressed_size as u64);
+        let mut data = Vec::with_capacity((entry.size as usize).min(MAX_PREALLOC));
+        // one byte more, to tell if there is more than the zip says
+        let limit = entry.size as u64 + 1;
+
+        match entry.method {
+            0 => compressed.take(limit).read_to_end(&mut data)?,
+            8 => DeflateDecoder::new(compressed)
+                .take(limit)
+                .read_to_end(&mut data)?,
+            _ => return Err(Error::InvalidPk3("unsupported compression")),
+        };
+
+        if data.len() != entry.size as usize {
+            return Err(Error::InvalidPk3("file size doesn't match"));
+        }
+
+        files.push(File { path, data });
+    }
+
+    Ok(files)
+}
+
+/// Finds and reads the end of the central directory.
+fn read_end<R>(mut r: R) -> Result<EndRecord, Error>
+where
+    R: Read + Seek,
+{
+    let len = r.seek(SeekFrom::End(0))?;
+    let start = len.saturating_sub(MAX_END_SIZE);
+
+    let mut tail = Vec::new();
+    r.seek(SeekFrom::Start(start))?;
+    r.read_to_end(&mut tail)?;
+
+    // the comment comes after, so look from the back
+    let signature = END_SIGNATURE.to_le_bytes();
+    let pos = tail
+        .windows(4)
+        .rposition(|window| window == signature)
+        .ok_or(Error::InvalidPk3("not a zip file"))?;
+
+    EndRecord::read(Cursor::new(&tail[pos + 4..]))
+}
+
+fn read_name<R>(mut r: R, len: u16) -> Result<String, Error>
+where
+    R: Read,
+{
+    let mut bytes = vec![0; len as usize];
+    r.read_exact(&mut bytes)?;
+
+    String::from_utf8(bytes).map_err(|err| Error::Utf8(err.utf8_error()))
+}
+
+/// The lump name of a file, which is its name without the extension.
+fn lump_name(path: &str) -> String {
+    let name = path.rsplit('/').next().unwrap_or(path);
+    let stem = name.split_once('.').map_or(name, |(stem, _)| stem);
+
+    stem.to_ascii_uppercase()
+}
+
+/// The marker prefix of a folder.
+fn namespace_prefix(folder: &str) -> String {
+    match folder {
+        "sprites" => "S".into(),
+        "flats" => "F".into(),
+        "patches" => "P".into(),
+        "textures" => "TX".into(),
+        "colormaps" => "C".into(),
+        folder => folder.to_ascii_uppercase(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::format::wad::Section;
+
+    /// Writes a zip of stored files.
+    fn zip(files: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        let mut directory = Vec::new();
+
+        for (path, data) in files {
+            let offset = bytes.len() as u32;
+            let sizes = [data.len() as u32; 2];
+
+            bytes.extend(LOCAL_SIGNATURE.to_le_bytes());
+            bytes.extend([0u8; 10]);
+            bytes.extend(0u32.to_le_bytes());
+            bytes.extend(sizes.iter().flat_map(|size| size.to_le_bytes()));
+            bytes.extend((path.len() as u16).to_le_bytes());
+            bytes.extend(0u16.to_le_bytes());
+            bytes.extend(path.as_bytes());
+            bytes.extend(*data);
+
+            directory.extend(ENTRY_SIGNATURE.to_le_bytes());
+            directory.extend([0u8; 12]);
+            directory.extend(0u32.to_le_bytes());
+            directory.extend(sizes.iter().flat_map(|size| size.to_le_bytes()));
+            directory.extend((path.len() as u16).to_le_bytes());
+            directory.extend([0u8; 12]);
+            directory.extend(offset.to_le_bytes());
+            directory.extend(path.as_bytes());
+        }
+
+        let directory_offset = bytes.len() as u32;
+        bytes.extend(&directory);
+        bytes.extend(END_SIGNATURE.to_le_bytes());
+        bytes.extend([0u8; 4]);
+        bytes.extend((files.len() as u16).to_le_bytes());
+        bytes.extend((files.len() as u16).to_le_bytes());
+        bytes.extend((directory.len() as u32).to_le_bytes());
+        bytes.extend(directory_offset.to_le_bytes());
+        bytes.extend(0u16.to_le_bytes());
+        bytes
+    }
+
+    #[test]
+    fn read_pk3() {
+        let mut map = Wad::new(WadType::Pwad);
+        map.push("MAP01", Vec::new());
+        map.push("TEXTMAP", "abcd");
+        map.push("ENDMAP", Vec::new());
+        let mut map_bytes = Vec::new();
+        map.write(&mut map_bytes).unwrap();
+
+        let bytes = zip(&[
+            ("sprites/", b""),
+            ("sprites/PLAYA0.png", b"png"),
+            ("maps/MAP01.wad", &map_bytes),
+            ("SOC_MAIN.txt", b"soc"),
+        ]);
+        let wad = super::read_pk3(Cursor::new(bytes)).unwrap();
+
+        let names = wad
+            .lumps()
+            .map(|lump| lump.name().to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            names,
+            vec!["SOC_MAIN", "MAP01", "TEXTMAP", "ENDMAP", "S_START", "PLAYA0", "S_END"]
+        );
+        assert_eq!(wad.map_lump("MAP01", "TEXTMAP").unwrap().data(), b"abcd");
+        assert_eq!(
+            wad.sections()[2],
+            Section::Namespace {
+                name: "Sprites".to_string(),
+                lumps: 4..7,
+            }
+        );
+    }
+
+    #[test]
+    fn wrong_size() {
+        let mut bytes = zip(&[("SOC_MAIN.txt", b"soc")]);
+
+        // say the file is smaller than it is
+        let directory = 30 + 12 + 3;
+        let size = directory + 24;
+        bytes[size..size + 4].copy_from_slice(&2u32.to_le_bytes());
+
+        assert!(matches!(
+            super::read_pk3(Cursor::new(bytes)),
+            Err(Error::InvalidPk3("file size doesn't match"))
+        ));
+    }
+}
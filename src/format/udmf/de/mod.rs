@@ -2,6 +2,7 @@
 
 mod serde_impl;
 
+use std::cell::Cell;
 use std::fmt::{self, Display, Formatter};
 
 use serde::{de::DeserializeSeed, Deserialize};
@@ -57,86 +58,237 @@ impl<'de> Parser<'de> {
         let deserializer = serde_impl::TopLevelDeserializer::new(&mut self.tokenizer);
         T::deserialize(deserializer)
     }
+
+    /// The input that has not been parsed yet.
+    pub fn remaining(&self) -> &'de str {
+        self.tokenizer.remaining()
+    }
+
+    /// Skips the next key and its value without reading them.
+    ///
+    /// This is for getting past values that failed to read. Blocks can't be
+    /// nested, so a block or assignment that is missing its end stops at the
+    /// key of the next block.
+    pub fn skip_value(&mut self) {
+        let input = self.tokenizer.remaining();
+        let is_ident = |ch: char| matches!(ch, 'A'..='Z' | 'a'..='z' | '0'..='9' | '_');
+
+        let mut in_block = false;
+        let mut assigned = false;
+        let mut in_string = false;
+        let mut escaped = false;
+        let mut ident_start = None;
+        let mut prev = ' ';
+        let mut end = input.len();
+
+        for (idx, ch) in input.char_indices() {
+            if in_string {
+                match ch {
+                    _ if escaped => escaped = false,
+                    '\\' => escaped = true,
+                    '"' => in_string = false,
+                    _ => (),
+                }
+            } else {
+                match ch {
+                    '"' => in_string = true,
+                    ';' if !in_block => {
+                        end = idx + 1;
+                        break;
+                    }
+                    '}' => {
+                        end = idx + 1;
+                        break;
+                    }
+                    '{' if in_block || assigned => {
+                        // the start of the next block
+                        if let Some(start) = ident_start {
+                            end = start;
+                            break;
+                        }
+                        in_block = true;
+                    }
+                    '{' => {
+                        in_block = true;
+                        ident_start = None;
+                    }
+                    '=' if !in_block => {
+                        assigned = true;
+                        ident_start = None;
+                    }
+                    'A'..='Z' | 'a'..='z' | '_' if !is_ident(prev) => {
+                        ident_start = Some(idx);
+                    }
+                    _ => (),
+                }
+            }
+
+            prev = ch;
+        }
+
+        self.tokenizer = Tokenizer::new(&input[end..]);
+    }
 }
 
 /// `udmf` tokenizer.
 #[derive(Debug)]
 pub struct Tokenizer<'de> {
     input: &'de str,
+    /// The token found by [`Tokenizer::peek_token`], and the input after it.
+    peeked: Cell<Option<(Token<'de>, &'de str)>>,
 }
 
 impl<'de> Tokenizer<'de> {
     /// Creates a new `Tokenizer`.
     pub fn new(input: &'de str) -> Tokenizer<'de> {
-        Tokenizer { input }
+        Tokenizer {
+            input,
+            peeked: Cell::new(None),
+        }
+    }
+
+    /// The input that has not been read yet.
+    pub fn remaining(&self) -> &'de str {
+        self.input
     }
 
     /// Peeks the next token without advancing the reader.
+    ///
+    /// The token is kept, so the next [`Tokenizer::next_token`] doesn't scan
+    /// it again.
     pub fn peek_token(&self) -> Result<Token<'de>, Error> {
-        Tokenizer::new(self.input).next_token()
+        if let Some((token, _)) = self.peeked.get() {
+            return Ok(token);
+        }
+
+        let mut tokenizer = Tokenizer::new(self.input);
+        let token = tokenizer.next_token()?;
+        self.peeked.set(Some((token, tokenizer.input)));
+
+        Ok(token)
     }
 
     /// Returns the next token.
     pub fn next_token(&mut self) -> Result<Token<'de>, Error> {
+        if let Some((token, rest)) = self.peeked.take() {
+            self.input = rest;
+            return Ok(token);
+        }
+
         // skip any whitespace
         self.skip_whitespace();
 
-        let out = self.peek_char().and_then(|ch| Token::try_from(ch));
+        let token = match self.input.as_bytes().first() {
+            Some(b'=') => Token::Assignment,
+            Some(b'{') => Token::StartBlock,
+            Some(b'}') => Token::EndBlock,
+            Some(b';') => Token::Seperator,
+            // try reading it as an ident
+            _ => return Ok(Token::Ident(self.next_ident()?)),
+        };
 
-        match out {
-            Ok(token) => {
-                self.next_char().expect("valid read");
-                Ok(token)
-            }
-            Err(_err) => {
-                // try reading it as an ident
-                Ok(Token::Ident(self.next_ident()?))
+        self.input = &self.input[1..];
+        Ok(token)
+    }
+
+    /// Returns the key of the next field in a block, after reading the
+    /// assignment that follows it, or `None` at the end of the block.
+    ///
+    /// Blocks are most of a map, so this reads a key and its assignment in one
+    /// go, and only goes through [`Tokenizer::next_token`] for anything else.
+    fn next_field(&mut self) -> Result<Option<&'de str>, Error> {
+        if self.peeked.get().is_none() {
+            self.skip_whitespace();
+
+            let bytes = self.input.as_bytes();
+            if matches!(bytes.first(), Some(b'A'..=b'Z' | b'a'..=b'z' | b'_')) {
+                let end = self.span(is_ident_byte);
+                let assignment =
+                    end + Tokenizer::new(&self.input[end..]).span(|b| b.is_ascii_whitespace());
+
+                if bytes.get(assignment) == Some(&b'=') {
+                    let key = &self.input[..end];
+                    self.input = &self.input[(assignment + 1)..];
+                    return Ok(Some(key));
+                }
             }
         }
+
+        match self.next_token()? {
+            Token::Ident(key) => match self.next_token()? {
+                Token::Assignment => Ok(Some(key)),
+                // TODO: better error thing
+                _ => Err(serde::de::Error::custom("expected assignment token")),
+            },
+            Token::EndBlock => Ok(None),
+            // TODO: better error thing
+            _ => Err(serde::de::Error::custom("unexpected token")),
+        }
+    }
+
+    /// Returns the value of a field, after reading the seperator that
+    /// follows it.
+    fn next_field_value(&mut self) -> Result<Value, Error> {
+        let value = self.next_value()?;
+        self.skip_whitespace();
+
+        if self.input.as_bytes().first() == Some(&b';') {
+            self.input = &self.input[1..];
+            return Ok(value);
+        }
+
+        match self.next_token()? {
+            Token::Seperator => Ok(value),
+            _ => Err(Error::expected_seperator()),
+        }
     }
 
     /// Returns the next value.
     pub fn next_value(&mut self) -> Result<Value, Error> {
+        // a peeked token would be read as the start of the value anyway
+        self.peeked.set(None);
+
         // skip any whitespace
         self.skip_whitespace();
 
-        let ch = self.peek_char()?;
+        let ch = *self.input.as_bytes().first().ok_or_else(Error::eof)?;
 
-        if ch == '"' {
+        if ch == b'"' {
             // start of string, read as string
             // eat char
-            self.next_char().expect("remaining input");
+            self.input = &self.input[1..];
 
-            // we read until end quote
+            // we read until an unescaped end quote, skipping over whatever
+            // each backslash escapes
+            let bytes = self.input.as_bytes();
             let mut end = 0;
+            let mut escaped = false;
 
-            while end < self.input.len() {
-                let next_quote = self.input[end..].find('"');
-
-                if let Some(idx) = next_quote {
-                    // if this quote isn't escaped, we're fine
-                    let char_before = self.input[end..(end + idx)].chars().last();
-
-                    if char_before == Some('\\') {
-                        // keep scanning
-                        end += idx + '"'.len_utf8();
-                    } else {
-                        // we found an unescaped quote!
-                        end += idx;
-                        break;
-                    }
-                } else {
+            loop {
+                let Some(idx) = bytes[end..].iter().position(|&b| matches!(b, b'"' | b'\\')) else {
                     // found an unquoted string!
                     return Err(Error::unquoted_string());
+                };
+
+                end += idx;
+                if bytes[end] == b'"' {
+                    break;
                 }
+
+                escaped = true;
+                end = (end + 2).min(bytes.len());
             }
 
             let output = &self.input[..end];
             // skip over quote
             self.input = &self.input[(end + '"'.len_utf8())..];
 
-            Ok(Value::String(unescape_string(output)))
-        } else if ch.is_ascii_digit() || matches!(ch, '+' | '-') {
+            if escaped {
+                Ok(Value::String(unescape_string(output)))
+            } else {
+                Ok(Value::String(output.to_owned()))
+            }
+        } else if ch.is_ascii_digit() || matches!(ch, b'+' | b'-') {
             // this is the start of an unsigned/hex integer
             self.read_number()
         } else {
@@ -145,10 +297,12 @@ impl<'de> Tokenizer<'de> {
             // writing udmfs by hand
 
             // this is a keyword
-            let end = self
-                .input
-                .find(&['^', '{', '}', '(', ')', ';', '"', '\'', '\n', '\t', ' '])
-                .unwrap_or_else(|| self.input.len());
+            let end = self.span(|b| {
+                !matches!(
+                    b,
+                    b'^' | b'{' | b'}' | b'(' | b')' | b';' | b'"' | b'\'' | b'\n' | b'\t' | b' '
+                )
+            });
 
             let keyword = &self.input[..end];
             self.input = &self.input[end..];
@@ -173,27 +327,23 @@ impl<'de> Tokenizer<'de> {
 
         if matches!(ch, 'A'..='Z' | 'a'..='z' | '_') {
             // find char where it stops
-            let end = self
-                .input
-                .find(|c: char| !matches!(c, 'A'..='Z' | 'a'..='z' | '0'..='9' | '_'));
-
-            if let Some(idx) = end {
-                let out = &self.input[..idx];
-                self.input = &self.input[idx..];
+            let end = self.span(is_ident_byte);
 
-                Ok(out)
-            } else {
-                let out = self.input;
-                self.input = "";
+            let out = &self.input[..end];
+            self.input = &self.input[end..];
 
-                Ok(out)
-            }
+            Ok(out)
         } else {
             Err(Error::unexpected_char(ch))
         }
     }
 
     fn read_number(&mut self) -> Result<Value, Error> {
+        if let Some((value, len)) = read_short_number(self.input.as_bytes()) {
+            self.input = &self.input[len..];
+            return Ok(value);
+        }
+
         // get sign
         let sign = self.peek_char()?;
 
@@ -202,18 +352,12 @@ impl<'de> Tokenizer<'de> {
         }
 
         // read until nondigit character
-        let end = self
-            .input
-            .find(|c: char| !c.is_ascii_digit())
-            .unwrap_or_else(|| self.input.len());
+        let end = self.span(|b| b.is_ascii_digit());
 
-        let next_char = self.input[end..].chars().next();
-        if next_char == Some('.') {
+        if self.input.as_bytes().get(end) == Some(&b'.') {
             // this is a float! read to end
-            let end = self.input[(end + '.'.len_utf8())..]
-                .find(|c: char| !c.is_ascii_digit())
-                .map(|e| e + end + '.'.len_utf8())
-                .unwrap_or_else(|| self.input.len());
+            let end =
+                end + 1 + Tokenizer::new(&self.input[(end + 1)..]).span(|b| b.is_ascii_digit());
 
             // got float
             let output = self.input[..end]
@@ -249,10 +393,7 @@ impl<'de> Tokenizer<'de> {
                     }
 
                     // get digits
-                    let end = self
-                        .input
-                        .find(|c: char| !c.is_ascii_digit())
-                        .unwrap_or_else(|| self.input.len());
+                    let end = self.span(|b| b.is_ascii_digit());
 
                     let exp = self.input[..end]
                         .parse::<i32>()
@@ -341,16 +482,73 @@ impl<'de> Tokenizer<'de> {
     }
 
     fn skip_whitespace(&mut self) {
-        // get next non_whitespace character
-        let next_char = self.input.find(|c: char| !c.is_ascii_whitespace());
+        let end = self.span(|b| b.is_ascii_whitespace());
+        self.input = &self.input[end..];
+    }
 
-        if let Some(idx) = next_char {
-            self.input = &self.input[idx..];
-        } else {
-            // we have reached the end of the stream
-            self.input = "";
+    /// The length of the run of bytes at the start of the input that match
+    /// `pred`.
+    ///
+    /// Everything this looks for is ASCII, so scanning bytes instead of
+    /// decoding chars always stops on a char boundary.
+    fn span(&self, pred: impl Fn(u8) -> bool) -> usize {
+        self.input
+            .bytes()
+            .position(|b| !pred(b))
+            .unwrap_or(self.input.len())
+    }
+}
+
+/// Whether `b` can be part of an identifier.
+fn is_ident_byte(b: u8) -> bool {
+    matches!(b, b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'_')
+}
+
+/// Reads a number with few enough digits to be read exactly without
+/// [`str::parse`], returning it and its length.
+///
+/// These are whole numbers of up to nine digits, and floats of up to seven
+/// digits without an exponent, which is nearly every number in a map.
+fn read_short_number(bytes: &[u8]) -> Option<(Value, usize)> {
+    /// Powers of ten, which are all exact as `f32`s.
+    const POWERS: [f32; 8] = [1e0, 1e1, 1e2, 1e3, 1e4, 1e5, 1e6, 1e7];
+
+    let negative = bytes.first() == Some(&b'-');
+    let mut idx = usize::from(matches!(bytes.first(), Some(b'+' | b'-')));
+    let mut mantissa = 0u32;
+    let mut read_digits = |idx: &mut usize| {
+        let start = *idx;
+        while let Some(&digit @ b'0'..=b'9') = bytes.get(*idx) {
+            mantissa = mantissa
+                .wrapping_mul(10)
+                .wrapping_add(u32::from(digit - b'0'));
+            *idx += 1;
         }
+        *idx - start
+    };
+
+    let whole = read_digits(&mut idx);
+
+    if bytes.get(idx) != Some(&b'.') {
+        if whole == 0 || whole > 9 {
+            return None;
+        }
+
+        let value = mantissa as i32;
+        return Some((Value::Integer(if negative { -value } else { value }), idx));
     }
+
+    idx += 1;
+    let fraction = read_digits(&mut idx);
+
+    if whole + fraction == 0 || whole + fraction > 7 || matches!(bytes.get(idx), Some(b'e' | b'E'))
+    {
+        return None;
+    }
+
+    // both are exact, so this rounds the same as parsing
+    let value = mantissa as f32 / POWERS[fraction];
+    Some((Value::Float(if negative { -value } else { value }), idx))
 }
 
 /// Unescapes a string.
@@ -374,6 +572,10 @@ pub fn unescape_string(mut s: &str) -> String {
                     // for escaping quotes
                     out.push('"');
                 }
+                Some('\\') => {
+                    // for escaping backslashes
+                    out.push('\\');
+                }
                 Some(ch) => {
                     // push unedited chars
                     out.push('\\');
@@ -559,6 +761,26 @@ mod tests {
         assert_eq!(input.next_value().unwrap(), Value::Float(-0.02));
     }
 
+    #[test]
+    fn read_short_float_exactly() {
+        // these take the fast path, which has to round like the slow one
+        for input in [
+            "0.1",
+            "-0.3",
+            "1234.567",
+            "0.000001",
+            "765.4321",
+            "+9.999999",
+        ] {
+            let expected = Value::Float(input.parse().unwrap());
+            assert_eq!(
+                Tokenizer::new(input).next_value().unwrap(),
+                expected,
+                "{input}"
+            );
+        }
+    }
+
     #[test]
     fn read_int() {
         let input = r#"
@@ -579,6 +801,7 @@ mod tests {
         "Hey Paisanos!"
         "Welcome to the \"Super Mario Bros. Super Show\"!"
         "Do Do Do Do"
+        "C:\\" "D:\\"
         "#;
         let mut input = Tokenizer::new(input);
 
@@ -594,6 +817,24 @@ mod tests {
             input.next_value().unwrap(),
             Value::String("Do Do Do Do".into())
         );
+        assert_eq!(input.next_value().unwrap(), Value::String("C:\\".into()));
+        assert_eq!(input.next_value().unwrap(), Value::String("D:\\".into()));
+    }
+
+    #[test]
+    fn peek_token() {
+        let mut input = Tokenizer::new("twosided = true;");
+
+        assert_eq!(input.peek_token().unwrap(), Token::Ident("twosided"));
+        assert_eq!(input.peek_token().unwrap(), Token::Ident("twosided"));
+        assert_eq!(input.next_token().unwrap(), Token::Ident("twosided"));
+        assert_eq!(input.next_token().unwrap(), Token::Assignment);
+
+        // values are read from where the peeked token starts
+        assert_eq!(input.peek_token().unwrap(), Token::Ident("true"));
+        assert_eq!(input.next_value().unwrap(), Value::Boolean(true));
+        assert_eq!(input.peek_token().unwrap(), Token::Seperator);
+        assert_eq!(input.remaining(), ";");
     }
 
     #[test]
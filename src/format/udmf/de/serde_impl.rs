@@ -6,7 +6,7 @@ use serde::de::{
         BoolDeserializer, BorrowedStrDeserializer, F32Deserializer, I32Deserializer,
         StringDeserializer,
     },
-    DeserializeSeed, Error as _, MapAccess, Visitor,
+    DeserializeSeed, MapAccess, Visitor,
 };
 use serde::forward_to_deserialize_any;
 
@@ -20,23 +20,11 @@ impl<'a, 'de> MapAccess<'de> for BlockAccess<'a, 'de> {
     where
         K: DeserializeSeed<'de>,
     {
-        match self.0.next_token()? {
-            Token::Ident(ident) => {
-                let ident = seed
-                    .deserialize(BorrowedStrDeserializer::new(ident))
-                    .map(Some)?;
-
-                // consume assignment
-                if let Token::Assignment = self.0.next_token()? {
-                    Ok(ident)
-                } else {
-                    // TODO: better error thing
-                    Err(Error::custom("expected assignment token"))
-                }
-            }
-            Token::EndBlock => Ok(None),
-            // TODO: better error thing
-            _ => Err(Error::custom("unexpected token")),
+        match self.0.next_field()? {
+            Some(key) => seed
+                .deserialize(BorrowedStrDeserializer::new(key))
+                .map(Some),
+            None => Ok(None),
         }
     }
 
@@ -112,18 +100,12 @@ impl<'a, 'de> de::Deserializer<'de> for ValueDeserializer<'a, 'de> {
     where
         V: Visitor<'de>,
     {
-        let res = match self.t.next_value()? {
+        match self.t.next_field_value()? {
             Value::Boolean(b) => visitor.visit_bool(b),
             Value::Integer(int) => visitor.visit_i32(int),
             Value::Float(fl) => visitor.visit_f32(fl),
             Value::String(s) => visitor.visit_string(s),
             Value::Nil => visitor.visit_none(),
-        };
-
-        if let Token::Seperator = self.t.next_token()? {
-            res
-        } else {
-            Err(Error::expected_seperator())
         }
     }
 
@@ -131,18 +113,12 @@ impl<'a, 'de> de::Deserializer<'de> for ValueDeserializer<'a, 'de> {
     where
         V: Visitor<'de>,
     {
-        let res = match self.t.next_value()? {
+        match self.t.next_field_value()? {
             Value::Boolean(b) => visitor.visit_some(BoolDeserializer::new(b)),
             Value::Integer(int) => visitor.visit_some(I32Deserializer::new(int)),
             Value::Float(fl) => visitor.visit_some(F32Deserializer::new(fl)),
             Value::String(s) => visitor.visit_some(StringDeserializer::new(s)),
             Value::Nil => visitor.visit_none(),
-        };
-
-        if let Token::Seperator = self.t.next_token()? {
-            res
-        } else {
-            Err(Error::expected_seperator())
         }
     }
 
@@ -4,6 +4,8 @@
 //! For higher level access with [`serde`] batteries included, see:
 //! * **Deserialization**  
 //!   [`de::Parser`]
+//! * **Serialization**  
+//!   [`ser::Writer`]
 //!
 //! ## Low Level
 //! For lower level access:
@@ -11,6 +13,7 @@
 //!   [`de::Tokenizer`]
 
 pub mod de;
+pub mod ser;
 
 use serde::de::{Deserialize, Visitor};
 use serde::ser::Serialize;
@@ -36,14 +39,111 @@ pub enum Value {
 impl Value {
     /// Gets the name of the type contained.
     pub fn type_name(&self) -> &'static str {
+        self.value_type().name()
+    }
+
+    /// Gets the type contained.
+    pub fn value_type(&self) -> Type {
         match self {
-            Value::Boolean(_) => "boolean",
-            Value::Integer(_) => "integer",
-            Value::Float(_) => "float",
-            Value::String(_) => "string",
-            Value::Nil => "nil",
+            Value::Boolean(_) => Type::Boolean,
+            Value::Integer(_) => Type::Integer,
+            Value::Float(_) => Type::Float,
+            Value::String(_) => Type::String,
+            Value::Nil => Type::Nil,
         }
     }
+
+    /// Converts the value to another type.
+    ///
+    /// Numbers are nonzero when true, floats are rounded to integers, and
+    /// strings are parsed. Nil converts to the default of any type. Returns
+    /// `None` if the value has no equivalent, like a string that isn't a
+    /// number.
+    pub fn coerce(&self, ty: Type) -> Option<Value> {
+        let value = match (self, ty) {
+            (value, ty) if value.value_type() == ty => value.clone(),
+            (_, Type::Nil) => Value::Nil,
+            (Value::Nil, ty) => ty.default_value(),
+            (_, Type::String) => Value::String(match self {
+                Value::Boolean(v) => v.to_string(),
+                Value::Integer(v) => v.to_string(),
+                Value::Float(v) => v.to_string(),
+                _ => unreachable!(),
+            }),
+            (Value::Boolean(v), Type::Integer) => Value::Integer(*v as i32),
+            (Value::Boolean(v), Type::Float) => Value::Float(*v as i32 as f32),
+            (Value::Integer(v), Type::Boolean) => Value::Boolean(*v != 0),
+            (Value::Integer(v), Type::Float) => Value::Float(*v as f32),
+            (Value::Float(v), Type::Boolean) => Value::Boolean(*v != 0.0),
+            (Value::Float(v), Type::Integer) => {
+                let rounded = v.round();
+                if !(i32::MIN as f32..=i32::MAX as f32).contains(&rounded) {
+                    return None;
+                }
+                Value::Integer(rounded as i32)
+            }
+            (Value::String(v), Type::Boolean) => Value::Boolean(v.trim().parse().ok()?),
+            (Value::String(v), Type::Integer) => Value::Integer(v.trim().parse().ok()?),
+            (Value::String(v), Type::Float) => Value::Float(v.trim().parse().ok()?),
+            _ => unreachable!(),
+        };
+
+        Some(value)
+    }
+}
+
+/// The type of a [`Value`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Type {
+    Boolean,
+    Integer,
+    Float,
+    String,
+    Nil,
+}
+
+impl Type {
+    /// Every type.
+    pub const ALL: [Type; 5] = [
+        Type::Boolean,
+        Type::Integer,
+        Type::Float,
+        Type::String,
+        Type::Nil,
+    ];
+
+    /// Gets the name of the type.
+    pub fn name(self) -> &'static str {
+        match self {
+            Type::Boolean => "boolean",
+            Type::Integer => "integer",
+            Type::Float => "float",
+            Type::String => "string",
+            Type::Nil => "nil",
+        }
+    }
+
+    /// The value new fields of the type start with.
+    pub fn default_value(self) -> Value {
+        match self {
+            Type::Boolean => Value::Boolean(false),
+            Type::Integer => Value::Integer(0),
+            Type::Float => Value::Float(0.0),
+            Type::String => Value::String(String::new()),
+            Type::Nil => Value::Nil,
+        }
+    }
+}
+
+/// Checks if `str` can be used as a key.
+///
+/// Keys start with a letter or underscore, followed by letters, digits and
+/// underscores.
+pub fn is_identifier(str: &str) -> bool {
+    str.starts_with(|c: char| matches!(c, 'A'..='Z' | 'a'..='z' | '_'))
+        && str
+            .chars()
+            .all(|c| matches!(c, 'A'..='Z' | 'a'..='z' | '0'..='9' | '_'))
 }
 
 impl Serialize for Value {
@@ -156,3 +256,35 @@ where
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn coerce() {
+        assert_eq!(
+            Value::Float(2.6).coerce(Type::Integer),
+            Some(Value::Integer(3))
+        );
+        assert_eq!(
+            Value::Integer(0).coerce(Type::Boolean),
+            Some(Value::Boolean(false))
+        );
+        assert_eq!(
+            Value::from(" 1.5").coerce(Type::Float),
+            Some(Value::Float(1.5))
+        );
+        assert_eq!(Value::from("fast").coerce(Type::Integer), None);
+        assert_eq!(Value::Float(f32::NAN).coerce(Type::Integer), None);
+        assert_eq!(
+            Value::Boolean(true).coerce(Type::String),
+            Some(Value::from("true"))
+        );
+        assert_eq!(Value::Nil.coerce(Type::Integer), Some(Value::Integer(0)));
+
+        assert!(is_identifier("user_color2"));
+        assert!(!is_identifier("2fast"));
+        assert!(!is_identifier(""));
+    }
+}
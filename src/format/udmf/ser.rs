@@ -0,0 +1,781 @@
+//! `udmf` serialization functions and structs.
+
+use std::fmt::{self, Display, Formatter, Write};
+
+use serde::ser::{self, Impossible, Serialize};
+
+/// `udmf` high level writer.
+///
+/// The counterpart to [`Parser`](super::de::Parser).
+pub struct Writer<W> {
+    out: W,
+}
+
+impl<W> Writer<W>
+where
+    W: Write,
+{
+    /// Creates a new `Writer`.
+    pub fn new(out: W) -> Writer<W> {
+        Writer { out }
+    }
+
+    /// Writes a top level key and its value.
+    ///
+    /// Structs and maps are written as blocks, everything else is written as
+    /// an assignment. Nil values are not written at all.
+    pub fn write_value<T>(&mut self, key: &str, value: &T) -> Result<(), Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        value.serialize(TopLevelSerializer {
+            out: &mut self.out,
+            key,
+        })
+    }
+
+    /// Consumes the writer, returning the output.
+    pub fn into_inner(self) -> W {
+        self.out
+    }
+}
+
+/// Formats a float so it is always read back as a float.
+pub fn format_float(f: f32) -> Result<String, Error> {
+    if !f.is_finite() {
+        return Err(Error {
+            kind: ErrorKind::NonFiniteFloat,
+        });
+    }
+
+    let mut out = f.to_string();
+
+    if !out.contains('.') {
+        out.push_str(".0");
+    }
+
+    Ok(out)
+}
+
+/// Escapes a string, including the surrounding quotes.
+pub fn escape_string(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+
+    out.push('"');
+    for ch in s.chars() {
+        if matches!(ch, '"' | '\\') {
+            out.push('\\');
+        }
+        out.push(ch);
+    }
+    out.push('"');
+
+    out
+}
+
+/// `udmf` top level serializer.
+struct TopLevelSerializer<'a, W> {
+    out: &'a mut W,
+    key: &'a str,
+}
+
+impl<'a, W> TopLevelSerializer<'a, W>
+where
+    W: Write,
+{
+    fn assign(self, value: Option<String>) -> Result<(), Error> {
+        if let Some(value) = value {
+            writeln!(self.out, "{} = {};", self.key, value)?;
+        }
+
+        Ok(())
+    }
+
+    fn start_block(self) -> Result<BlockSerializer<'a, W>, Error> {
+        write!(self.out, "{}\n{{\n", self.key)?;
+
+        Ok(BlockSerializer {
+            out: self.out,
+            key: None,
+        })
+    }
+}
+
+macro_rules! forward_to_value {
+    ($($method:ident: $ty:ty,)*) => {
+        $(
+            fn $method(self, v: $ty) -> Result<Self::Ok, Self::Error> {
+                let value = ValueSerializer.$method(v)?;
+                self.assign(value)
+            }
+        )*
+    };
+}
+
+impl<'a, W> ser::Serializer for TopLevelSerializer<'a, W>
+where
+    W: Write,
+{
+    type Ok = ();
+    type Error = Error;
+
+    type SerializeSeq = Impossible<(), Error>;
+    type SerializeTuple = Impossible<(), Error>;
+    type SerializeTupleStruct = Impossible<(), Error>;
+    type SerializeTupleVariant = Impossible<(), Error>;
+    type SerializeMap = BlockSerializer<'a, W>;
+    type SerializeStruct = BlockSerializer<'a, W>;
+    type SerializeStructVariant = Impossible<(), Error>;
+
+    forward_to_value! {
+        serialize_bool: bool,
+        serialize_i8: i8,
+        serialize_i16: i16,
+        serialize_i32: i32,
+        serialize_i64: i64,
+        serialize_u8: u8,
+        serialize_u16: u16,
+        serialize_u32: u32,
+        serialize_u64: u64,
+        serialize_f32: f32,
+        serialize_f64: f64,
+        serialize_char: char,
+        serialize_str: &str,
+        serialize_bytes: &[u8],
+    }
+
+    fn serialize_none(self) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn serialize_some<T>(self, value: &T) -> Result<(), Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> Result<(), Error> {
+        self.serialize_str(variant)
+    }
+
+    fn serialize_newtype_struct<T>(self, _name: &'static str, value: &T) -> Result<(), Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<(), Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        Err(Error::unsupported("newtype variant"))
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq, Error> {
+        Err(Error::unsupported("sequence"))
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple, Error> {
+        Err(Error::unsupported("tuple"))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleStruct, Error> {
+        Err(Error::unsupported("tuple struct"))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant, Error> {
+        Err(Error::unsupported("tuple variant"))
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, Error> {
+        self.start_block()
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStruct, Error> {
+        self.start_block()
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant, Error> {
+        Err(Error::unsupported("struct variant"))
+    }
+}
+
+/// `udmf` block serializer.
+struct BlockSerializer<'a, W> {
+    out: &'a mut W,
+    key: Option<String>,
+}
+
+impl<'a, W> BlockSerializer<'a, W>
+where
+    W: Write,
+{
+    fn assign<T>(&mut self, key: &str, value: &T) -> Result<(), Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        if let Some(value) = value.serialize(ValueSerializer)? {
+            writeln!(self.out, "{} = {};", key, value)?;
+        }
+
+        Ok(())
+    }
+
+    fn end_block(self) -> Result<(), Error> {
+        self.out.write_str("}\n\n")?;
+        Ok(())
+    }
+}
+
+impl<'a, W> ser::SerializeStruct for BlockSerializer<'a, W>
+where
+    W: Write,
+{
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> Result<(), Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.assign(key, value)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        self.end_block()
+    }
+}
+
+impl<'a, W> ser::SerializeMap for BlockSerializer<'a, W>
+where
+    W: Write,
+{
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_key<T>(&mut self, key: &T) -> Result<(), Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.key = Some(key.serialize(KeySerializer)?);
+        Ok(())
+    }
+
+    fn serialize_value<T>(&mut self, value: &T) -> Result<(), Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        let key = self
+            .key
+            .take()
+            .ok_or_else(|| <Error as ser::Error>::custom("value without key"))?;
+
+        self.assign(&key, value)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        self.end_block()
+    }
+}
+
+/// Serializes a value, returning `None` if there is nothing to write.
+struct ValueSerializer;
+
+impl ser::Serializer for ValueSerializer {
+    type Ok = Option<String>;
+    type Error = Error;
+
+    type SerializeSeq = Impossible<Option<String>, Error>;
+    type SerializeTuple = Impossible<Option<String>, Error>;
+    type SerializeTupleStruct = Impossible<Option<String>, Error>;
+    type SerializeTupleVariant = Impossible<Option<String>, Error>;
+    type SerializeMap = Impossible<Option<String>, Error>;
+    type SerializeStruct = Impossible<Option<String>, Error>;
+    type SerializeStructVariant = Impossible<Option<String>, Error>;
+
+    fn serialize_bool(self, v: bool) -> Result<Self::Ok, Error> {
+        Ok(Some(v.to_string()))
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<Self::Ok, Error> {
+        Ok(Some(v.to_string()))
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<Self::Ok, Error> {
+        Ok(Some(v.to_string()))
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<Self::Ok, Error> {
+        Ok(Some(v.to_string()))
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<Self::Ok, Error> {
+        Ok(Some(v.to_string()))
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<Self::Ok, Error> {
+        Ok(Some(v.to_string()))
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<Self::Ok, Error> {
+        Ok(Some(v.to_string()))
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<Self::Ok, Error> {
+        Ok(Some(v.to_string()))
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<Self::Ok, Error> {
+        Ok(Some(v.to_string()))
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<Self::Ok, Error> {
+        format_float(v).map(Some)
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<Self::Ok, Error> {
+        // udmf floats are read as `f32` anyways
+        format_float(v as f32).map(Some)
+    }
+
+    fn serialize_char(self, v: char) -> Result<Self::Ok, Error> {
+        Ok(Some(escape_string(v.encode_utf8(&mut [0u8; 4]))))
+    }
+
+    fn serialize_str(self, v: &str) -> Result<Self::Ok, Error> {
+        Ok(Some(escape_string(v)))
+    }
+
+    fn serialize_bytes(self, _v: &[u8]) -> Result<Self::Ok, Error> {
+        Err(Error::unsupported("bytes"))
+    }
+
+    fn serialize_none(self) -> Result<Self::Ok, Error> {
+        Ok(None)
+    }
+
+    fn serialize_some<T>(self, value: &T) -> Result<Self::Ok, Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<Self::Ok, Error> {
+        Ok(None)
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<Self::Ok, Error> {
+        Ok(None)
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> Result<Self::Ok, Error> {
+        self.serialize_str(variant)
+    }
+
+    fn serialize_newtype_struct<T>(self, _name: &'static str, value: &T) -> Result<Self::Ok, Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<Self::Ok, Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        Err(Error::unsupported("newtype variant"))
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq, Error> {
+        Err(Error::unsupported("sequence"))
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple, Error> {
+        Err(Error::unsupported("tuple"))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleStruct, Error> {
+        Err(Error::unsupported("tuple struct"))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant, Error> {
+        Err(Error::unsupported("tuple variant"))
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, Error> {
+        Err(Error::unsupported("nested block"))
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStruct, Error> {
+        Err(Error::unsupported("nested block"))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant, Error> {
+        Err(Error::unsupported("struct variant"))
+    }
+}
+
+/// Serializes keys of a block, which must be identifiers.
+struct KeySerializer;
+
+impl KeySerializer {
+    fn invalid() -> Error {
+        Error {
+            kind: ErrorKind::InvalidKey,
+        }
+    }
+}
+
+impl ser::Serializer for KeySerializer {
+    type Ok = String;
+    type Error = Error;
+
+    type SerializeSeq = Impossible<String, Error>;
+    type SerializeTuple = Impossible<String, Error>;
+    type SerializeTupleStruct = Impossible<String, Error>;
+    type SerializeTupleVariant = Impossible<String, Error>;
+    type SerializeMap = Impossible<String, Error>;
+    type SerializeStruct = Impossible<String, Error>;
+    type SerializeStructVariant = Impossible<String, Error>;
+
+    fn serialize_str(self, v: &str) -> Result<String, Error> {
+        if super::is_identifier(v) {
+            Ok(v.to_owned())
+        } else {
+            Err(KeySerializer::invalid())
+        }
+    }
+
+    fn serialize_bool(self, _v: bool) -> Result<String, Error> {
+        Err(KeySerializer::invalid())
+    }
+
+    fn serialize_i8(self, _v: i8) -> Result<String, Error> {
+        Err(KeySerializer::invalid())
+    }
+
+    fn serialize_i16(self, _v: i16) -> Result<String, Error> {
+        Err(KeySerializer::invalid())
+    }
+
+    fn serialize_i32(self, _v: i32) -> Result<String, Error> {
+        Err(KeySerializer::invalid())
+    }
+
+    fn serialize_i64(self, _v: i64) -> Result<String, Error> {
+        Err(KeySerializer::invalid())
+    }
+
+    fn serialize_u8(self, _v: u8) -> Result<String, Error> {
+        Err(KeySerializer::invalid())
+    }
+
+    fn serialize_u16(self, _v: u16) -> Result<String, Error> {
+        Err(KeySerializer::invalid())
+    }
+
+    fn serialize_u32(self, _v: u32) -> Result<String, Error> {
+        Err(KeySerializer::invalid())
+    }
+
+    fn serialize_u64(self, _v: u64) -> Result<String, Error> {
+        Err(KeySerializer::invalid())
+    }
+
+    fn serialize_f32(self, _v: f32) -> Result<String, Error> {
+        Err(KeySerializer::invalid())
+    }
+
+    fn serialize_f64(self, _v: f64) -> Result<String, Error> {
+        Err(KeySerializer::invalid())
+    }
+
+    fn serialize_char(self, v: char) -> Result<String, Error> {
+        self.serialize_str(v.encode_utf8(&mut [0u8; 4]))
+    }
+
+    fn serialize_bytes(self, _v: &[u8]) -> Result<String, Error> {
+        Err(KeySerializer::invalid())
+    }
+
+    fn serialize_none(self) -> Result<String, Error> {
+        Err(KeySerializer::invalid())
+    }
+
+    fn serialize_some<T>(self, _value: &T) -> Result<String, Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        Err(KeySerializer::invalid())
+    }
+
+    fn serialize_unit(self) -> Result<String, Error> {
+        Err(KeySerializer::invalid())
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<String, Error> {
+        Err(KeySerializer::invalid())
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> Result<String, Error> {
+        self.serialize_str(variant)
+    }
+
+    fn serialize_newtype_struct<T>(self, _name: &'static str, value: &T) -> Result<String, Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<String, Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        Err(KeySerializer::invalid())
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq, Error> {
+        Err(KeySerializer::invalid())
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple, Error> {
+        Err(KeySerializer::invalid())
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleStruct, Error> {
+        Err(KeySerializer::invalid())
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant, Error> {
+        Err(KeySerializer::invalid())
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, Error> {
+        Err(KeySerializer::invalid())
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStruct, Error> {
+        Err(KeySerializer::invalid())
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant, Error> {
+        Err(KeySerializer::invalid())
+    }
+}
+
+/// An error that occurs during serialization.
+#[derive(Debug)]
+pub struct Error {
+    kind: ErrorKind,
+}
+
+impl Error {
+    fn unsupported(name: &'static str) -> Error {
+        Error {
+            kind: ErrorKind::Unsupported(name),
+        }
+    }
+}
+
+/// Inner details about the error.
+#[derive(Debug)]
+pub enum ErrorKind {
+    Unsupported(&'static str),
+    InvalidKey,
+    NonFiniteFloat,
+    Fmt,
+    Message(String),
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match &self.kind {
+            ErrorKind::Unsupported(name) => write!(f, "unsupported type: {}", name),
+            ErrorKind::InvalidKey => write!(f, "keys must be identifiers"),
+            ErrorKind::NonFiniteFloat => write!(f, "float is not finite"),
+            ErrorKind::Fmt => write!(f, "failed to write output"),
+            ErrorKind::Message(s) => f.write_str(s),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<fmt::Error> for Error {
+    fn from(_: fmt::Error) -> Error {
+        Error {
+            kind: ErrorKind::Fmt,
+        }
+    }
+}
+
+impl ser::Error for Error {
+    fn custom<T>(msg: T) -> Self
+    where
+        T: Display,
+    {
+        Error {
+            kind: ErrorKind::Message(msg.to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::format::udmf::de::Parser;
+
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Thing {
+        x: f32,
+        y: f32,
+        #[serde(default)]
+        height: Option<f32>,
+        angle: i32,
+        arg0: String,
+        arg1: bool,
+    }
+
+    #[test]
+    fn write_float() {
+        assert_eq!(format_float(4.0).unwrap(), "4.0");
+        assert_eq!(format_float(-0.25).unwrap(), "-0.25");
+        assert_eq!(format_float(4e9).unwrap(), "4000000000.0");
+        assert!(format_float(f32::NAN).is_err());
+    }
+
+    #[test]
+    fn round_trip() {
+        let thing = Thing {
+            x: 43.0,
+            y: -459.5,
+            height: None,
+            angle: 30,
+            arg0: "Welcome to the \"Super Show\"!".into(),
+            arg1: true,
+        };
+
+        let mut writer = Writer::new(String::new());
+        writer.write_value("namespace", "ringracers").unwrap();
+        writer.write_value("thing", &thing).unwrap();
+        let output = writer.into_inner();
+
+        assert!(!output.contains("height"));
+
+        let mut parser = Parser::new(&output);
+
+        assert_eq!(parser.next_key().unwrap(), Some("namespace"));
+        assert_eq!(parser.next_value::<String>().unwrap(), "ringracers");
+        assert_eq!(parser.next_key().unwrap(), Some("thing"));
+        assert_eq!(parser.next_value::<Thing>().unwrap(), thing);
+        assert_eq!(parser.next_key().unwrap(), None);
+    }
+}
@@ -1,18 +1,75 @@
 //! Lower level WAD stuff.
 
 use std::fmt::{self, Debug, Formatter};
-use std::io::{self, Read, Seek, SeekFrom};
+use std::io::{self, Cursor, Read, Seek, SeekFrom, Write};
+use std::ops::Range;
 
 /// Allows a type to be read as bytes.
 ///
-/// Useful for a strictly defined structure like WADs.
+/// Useful for a strictly defined structure like WADs. Numbers are read little
+/// endian, and packed records of them can be declared with `byte_record!`.
 pub trait ByteRead: Sized {
     /// Reads the type from an IO stream.
     fn read<R>(r: R) -> Result<Self, Error>
     where
         R: Read + Seek;
+
+    /// Reads values one after another until `bytes` runs out.
+    ///
+    /// This is how most lumps are laid out.
+    fn read_all(bytes: &[u8]) -> Result<Vec<Self>, Error> {
+        let mut r = Cursor::new(bytes);
+        let mut values = Vec::new();
+
+        while (r.position() as usize) < bytes.len() {
+            values.push(Self::read(&mut r)?);
+        }
+
+        Ok(values)
+    }
+}
+
+/// Declares a struct that is read field by field with [`ByteRead`].
+///
+/// Every field must be [`ByteRead`], and they are read in the order they are
+/// declared, with no padding between them.
+///
+/// ```ignore
+/// byte_record! {
+///     /// A vertex of a binary map.
+///     pub struct Vertex {
+///         pub x: i16,
+///         pub y: i16,
+///     }
+/// }
+/// ```
+macro_rules! byte_record {
+    (
+        $(#[$meta:meta])*
+        $vis:vis struct $name:ident {
+            $($(#[$field_meta:meta])* $field_vis:vis $field:ident: $ty:ty),* $(,)?
+        }
+    ) => {
+        $(#[$meta])*
+        $vis struct $name {
+            $($(#[$field_meta])* $field_vis $field: $ty,)*
+        }
+
+        impl $crate::format::wad::ByteRead for $name {
+            fn read<R>(mut r: R) -> Result<Self, $crate::format::wad::Error>
+            where
+                R: std::io::Read + std::io::Seek,
+            {
+                Ok($name {
+                    $($field: <$ty as $crate::format::wad::ByteRead>::read(&mut r)?,)*
+                })
+            }
+        }
+    };
 }
 
+pub(crate) use byte_record;
+
 /// Represents an in-memory WAD file.
 ///
 /// WAD files are typically small enough so this isn't insane.
@@ -21,9 +78,29 @@ pub struct Wad {
     header: Header,
     lump_infos: Vec<LumpInfo>,
     lump_data: Vec<LumpData>,
+    /// Whether lumps were renamed or removed since the WAD was read or saved.
+    directory_modified: bool,
+    /// How many lumps the directory in the file has, which only changes when
+    /// the WAD is saved.
+    saved_lumps: usize,
 }
 
 impl Wad {
+    /// Creates an empty WAD.
+    pub fn new(ident: WadType) -> Wad {
+        Wad {
+            header: Header {
+                ident,
+                num_lumps: 0,
+                info_table_offset: 0,
+            },
+            lump_infos: Vec::new(),
+            lump_data: Vec::new(),
+            directory_modified: false,
+            saved_lumps: 0,
+        }
+    }
+
     /// Reads a WAD file from a reader.
     pub fn from_reader<R>(mut r: R) -> Result<Wad, Error>
     where
@@ -35,9 +112,11 @@ impl Wad {
         let lump_data = LumpData::read_of(&mut r, &lump_infos)?;
 
         Ok(Wad {
+            saved_lumps: header.num_lumps,
             header,
             lump_infos,
             lump_data,
+            directory_modified: false,
         })
     }
 
@@ -46,6 +125,13 @@ impl Wad {
         &self.header
     }
 
+    /// Changes whether the WAD is an IWAD or a PWAD.
+    ///
+    /// This only changes how the WAD is written by [`Wad::write`].
+    pub fn set_wad_type(&mut self, ident: WadType) {
+        self.header.ident = ident;
+    }
+
     /// Gets all the lumps in the WAD as an iterator.
     pub fn lumps(&self) -> impl Iterator<Item = Lump<'_>> + '_ {
         self.lump_infos
@@ -63,6 +149,231 @@ impl Wad {
 
         self.lumps().find(|l| l.name() == name)
     }
+
+    /// Gets a specific lump by name, mutably.
+    pub fn lump_mut(&mut self, name: impl AsRef<str>) -> Option<LumpMut<'_>> {
+        let name = name.as_ref();
+        let idx = self.lump_infos.iter().position(|l| l.name == name)?;
+
+        self.lump_at_mut(idx)
+    }
+
+    /// Gets a lump by its index in the directory.
+    pub fn lump_at(&self, idx: usize) -> Option<Lump<'_>> {
+        Some(Lump {
+            lump_info: self.lump_infos.get(idx)?,
+            lump_data: self.lump_data.get(idx)?,
+        })
+    }
+
+    /// Gets a lump by its index in the directory, mutably.
+    pub fn lump_at_mut(&mut self, idx: usize) -> Option<LumpMut<'_>> {
+        Some(LumpMut {
+            lump_info: self.lump_infos.get_mut(idx)?,
+            lump_data: self.lump_data.get_mut(idx)?,
+            directory_modified: &mut self.directory_modified,
+        })
+    }
+
+    /// Adds a lump to the end of the WAD.
+    ///
+    /// The name isn't checked, so lumps read from elsewhere can keep their
+    /// full names, but [`Wad::write`] fails on names longer than 8 bytes.
+    pub fn push(&mut self, name: impl Into<String>, data: impl Into<Vec<u8>>) {
+        self.lump_infos.push(LumpInfo {
+            file_pos: 0,
+            size: 0,
+            name: name.into(),
+            modified: true,
+        });
+        self.lump_data.push(LumpData(data.into()));
+        self.header.num_lumps = self.lump_infos.len();
+        self.directory_modified = true;
+    }
+
+    /// Removes a lump from the WAD.
+    ///
+    /// # Panics
+    /// Panics if `idx` is out of bounds.
+    pub fn remove(&mut self, idx: usize) {
+        self.lump_infos.remove(idx);
+        self.lump_data.remove(idx);
+        self.header.num_lumps = self.lump_infos.len();
+        self.directory_modified = true;
+    }
+
+    /// Writes the WAD file to a writer.
+    ///
+    /// The lumps are written one after another, followed by the directory.
+    pub fn write<W>(&self, mut w: W) -> Result<(), Error>
+    where
+        W: Write,
+    {
+        const HEADER_SIZE: usize = 12;
+
+        let data_size = self
+            .lump_data
+            .iter()
+            .map(|lump_data| lump_data.as_ref().len())
+            .sum::<usize>();
+
+        // write header
+        w.write_all(match self.header.ident {
+            WadType::Iwad => b"IWAD",
+            WadType::Pwad => b"PWAD",
+        })?;
+        write_i32(&mut w, self.lump_infos.len())?;
+        write_i32(&mut w, HEADER_SIZE + data_size)?;
+
+        // write data
+        for lump_data in self.lump_data.iter() {
+            w.write_all(lump_data.as_ref())?;
+        }
+
+        // write directory
+        let mut file_pos = HEADER_SIZE;
+
+        for (lump_info, lump_data) in self.lump_infos.iter().zip(self.lump_data.iter()) {
+            let size = lump_data.as_ref().len();
+
+            write_i32(&mut w, if size > 0 { file_pos } else { 0 })?;
+            write_i32(&mut w, size)?;
+            write_string::<8, _>(&mut w, &lump_info.name)?;
+
+            file_pos += size;
+        }
+
+        Ok(())
+    }
+
+    /// Whether any lump has been changed since the WAD was read or saved.
+    pub fn is_modified(&self) -> bool {
+        self.directory_modified || self.lump_infos.iter().any(|lump_info| lump_info.modified)
+    }
+
+    /// Saves the changed lumps back into the file the WAD was read from.
+    ///
+    /// Unlike [`Wad::write`], this leaves every unchanged lump where it is, so
+    /// the rest of the file stays byte-identical. A changed lump is rewritten
+    /// in place if it still fits, with the leftover space zeroed, and
+    /// otherwise moved to the end of the file.
+    ///
+    /// Moved lumps and the new directory never go over the old directory,
+    /// and the header is pointed at the new directory last, so the file has
+    /// a whole directory if saving fails partway through.
+    pub fn write_changes<F>(&mut self, mut f: F) -> Result<(), Error>
+    where
+        F: Write + Seek,
+    {
+        const DIRECTORY_ENTRY_SIZE: usize = 16;
+
+        if !self.is_modified() {
+            return Ok(());
+        }
+
+        let data_end = self
+            .lump_infos
+            .iter()
+            .map(|lump_info| lump_info.file_pos + lump_info.size)
+            .max()
+            .unwrap_or(0);
+        let dir_start = self.header.info_table_offset;
+        let dir_end = dir_start + self.saved_lumps * DIRECTORY_ENTRY_SIZE;
+
+        // the lumps are only updated once everything is written
+        let mut lump_infos = self.lump_infos.clone();
+        let mut in_place = Vec::new();
+        let mut moved = Vec::new();
+
+        for (idx, lump_info) in lump_infos.iter_mut().enumerate() {
+            if !lump_info.modified {
+                continue;
+            }
+
+            let data = self.lump_data[idx].as_ref();
+
+            if data.is_empty() {
+                lump_info.file_pos = 0;
+            } else if data.len() <= lump_info.size && !self.is_shared(idx) {
+                in_place.push((idx, lump_info.size));
+            } else {
+                moved.push(idx);
+            }
+
+            lump_info.size = data.len();
+            lump_info.modified = false;
+        }
+
+        // use the space before the old directory if there is enough, so
+        // saving again doesn't keep growing the file
+        let needed = moved.iter().map(|&idx| lump_infos[idx].size).sum::<usize>()
+            + lump_infos.len() * DIRECTORY_ENTRY_SIZE;
+        let mut end = if data_end + needed <= dir_start {
+            data_end
+        } else {
+            data_end.max(dir_end)
+        };
+
+        for &idx in moved.iter() {
+            let data = self.lump_data[idx].as_ref();
+
+            f.seek(SeekFrom::Start(end as u64))?;
+            f.write_all(data)?;
+
+            lump_infos[idx].file_pos = end;
+            end += data.len();
+        }
+
+        // write directory
+        let dir_start = end;
+
+        f.seek(SeekFrom::Start(dir_start as u64))?;
+        for lump_info in lump_infos.iter() {
+            write_i32(&mut f, lump_info.file_pos)?;
+            write_i32(&mut f, lump_info.size)?;
+            write_string::<8, _>(&mut f, &lump_info.name)?;
+        }
+
+        for (idx, old_size) in in_place {
+            let data = self.lump_data[idx].as_ref();
+
+            // zero what is left of the old data
+            f.seek(SeekFrom::Start(lump_infos[idx].file_pos as u64))?;
+            f.write_all(data)?;
+            f.write_all(&vec![0; old_size - data.len()])?;
+        }
+        f.flush()?;
+
+        // point header to the directory
+        f.seek(SeekFrom::Start(4))?;
+        write_i32(&mut f, lump_infos.len())?;
+        write_i32(&mut f, dir_start)?;
+        f.flush()?;
+
+        self.header.num_lumps = lump_infos.len();
+        self.header.info_table_offset = dir_start;
+        self.saved_lumps = lump_infos.len();
+        self.lump_infos = lump_infos;
+        self.directory_modified = false;
+
+        Ok(())
+    }
+
+    /// Whether the data of a lump overlaps with any other lump in the file.
+    fn is_shared(&self, idx: usize) -> bool {
+        let lump_info = &self.lump_infos[idx];
+        let range = lump_info.file_pos..lump_info.file_pos + lump_info.size;
+
+        self.lump_infos
+            .iter()
+            .enumerate()
+            .any(|(other_idx, other)| {
+                other_idx != idx
+                    && other.size > 0
+                    && other.file_pos < range.end
+                    && range.start < other.file_pos + other.size
+            })
+    }
 }
 
 /// A single immutable reference to a lump in a WAD.
@@ -77,12 +388,184 @@ impl<'a> Lump<'a> {
         &self.lump_info.name
     }
 
+    /// Where the lump is in the file it was read from.
+    ///
+    /// This is `0` for virtual lumps, like map markers.
+    pub fn file_pos(&self) -> usize {
+        self.lump_info.file_pos
+    }
+
+    /// Whether the lump was changed since the WAD was read or saved.
+    pub fn is_modified(&self) -> bool {
+        self.lump_info.modified
+    }
+
     /// The lump data.
     pub fn data(&self) -> &[u8] {
         self.lump_data.as_ref()
     }
 }
 
+/// A single mutable reference to a lump in a WAD.
+pub struct LumpMut<'a> {
+    lump_info: &'a mut LumpInfo,
+    lump_data: &'a mut LumpData,
+    directory_modified: &'a mut bool,
+}
+
+impl<'a> LumpMut<'a> {
+    /// The name of the lump.
+    pub fn name(&self) -> &str {
+        &self.lump_info.name
+    }
+
+    /// The lump data.
+    pub fn data(&self) -> &[u8] {
+        self.lump_data.as_ref()
+    }
+
+    /// Replaces the lump data.
+    pub fn set_data(&mut self, data: impl Into<Vec<u8>>) {
+        *self.lump_data = LumpData(data.into());
+        self.lump_info.modified = true;
+    }
+
+    /// Renames the lump.
+    ///
+    /// Names are at most 8 bytes long.
+    pub fn rename(&mut self, name: impl Into<String>) -> Result<(), Error> {
+        let name = name.into();
+
+        if name.len() > 8 {
+            return Err(Error::NameTooLong(name));
+        }
+
+        self.lump_info.name = name;
+        *self.directory_modified = true;
+
+        Ok(())
+    }
+}
+
+/// The lumps that make up a map, after its marker.
+pub const MAP_LUMPS: &[&str] = &[
+    "TEXTMAP", "ZNODES", "REJECT", "BLOCKMAP", "BEHAVIOR", "SCRIPTS", "DIALOGUE", "ENDMAP",
+    "THINGS", "LINEDEFS", "SIDEDEFS", "VERTEXES", "SEGS", "SSECTORS", "NODES", "SECTORS",
+];
+
+/// A part of a WAD, found by its markers.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Section {
+    /// A map, from its marker to its last lump.
+    Map { name: String, lumps: Range<usize> },
+    /// The lumps between `X_START` and `X_END` markers, markers included.
+    Namespace { name: String, lumps: Range<usize> },
+    /// A lump outside of any section.
+    Lump(usize),
+}
+
+impl Wad {
+    /// Splits the lumps of the WAD into sections by their markers.
+    pub fn sections(&self) -> Vec<Section> {
+        let names = self
+            .lump_infos
+            .iter()
+            .map(|lump_info| lump_info.name.as_str())
+            .collect::<Vec<_>>();
+        let mut sections = Vec::new();
+        let mut idx = 0;
+
+        while idx < names.len() {
+            let name = names[idx];
+
+            // a map marker is followed by the map's lumps, starting with its
+            // TEXTMAP or THINGS
+            let is_map = names
+                .get(idx + 1)
+                .is_some_and(|next| matches!(*next, "TEXTMAP" | "THINGS"));
+
+            if is_map {
+                let map_end = idx
+                    + 1
+                    + names[idx + 1..]
+                        .iter()
+                        .take_while(|name| MAP_LUMPS.contains(name))
+                        .count();
+
+                sections.push(Section::Map {
+                    name: name.to_string(),
+                    lumps: idx..map_end,
+                });
+                idx = map_end;
+                continue;
+            }
+
+            if let Some(prefix) = name.strip_suffix("_START") {
+                let end_marker = format!("{}_END", prefix);
+                let end = names[idx + 1..]
+                    .iter()
+                    .position(|name| *name == end_marker)
+                    .map(|end| idx + 1 + end);
+
+                if let Some(end) = end {
+                    sections.push(Section::Namespace {
+                        name: namespace_name(prefix).to_string(),
+                        lumps: idx..end + 1,
+                    });
+                    idx = end + 1;
+                    continue;
+                }
+            }
+
+            sections.push(Section::Lump(idx));
+            idx += 1;
+        }
+
+        sections
+    }
+
+    /// The names of the maps in the WAD.
+    pub fn maps(&self) -> impl Iterator<Item = String> {
+        self.sections()
+            .into_iter()
+            .filter_map(|section| match section {
+                Section::Map { name, .. } => Some(name),
+                _ => None,
+            })
+    }
+
+    /// Gets a lump of a map, like its `TEXTMAP`.
+    pub fn map_lump(&self, map: &str, name: &str) -> Option<Lump<'_>> {
+        self.map_lump_index(map, name)
+            .and_then(|idx| self.lump_at(idx))
+    }
+
+    /// Gets the index of a lump of a map.
+    pub fn map_lump_index(&self, map: &str, name: &str) -> Option<usize> {
+        self.sections()
+            .into_iter()
+            .find_map(|section| match section {
+                Section::Map {
+                    name: map_name,
+                    mut lumps,
+                } if map_name == map => lumps.find(|&idx| self.lump_infos[idx].name == name),
+                _ => None,
+            })
+    }
+}
+
+/// The readable name of a namespace.
+fn namespace_name(prefix: &str) -> &str {
+    match prefix {
+        "S" | "SS" => "Sprites",
+        "F" | "FF" => "Flats",
+        "P" | "PP" => "Patches",
+        "TX" => "Textures",
+        "C" => "Colormaps",
+        prefix => prefix,
+    }
+}
+
 /// The header of a WAD file.
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Header {
@@ -138,6 +621,8 @@ struct LumpInfo {
     file_pos: usize,
     size: usize,
     name: String,
+    /// Whether the data was changed since it was read or saved.
+    modified: bool,
 }
 
 impl LumpInfo {
@@ -174,6 +659,7 @@ impl ByteRead for LumpInfo {
             file_pos: i32::read(&mut r)? as usize,
             size: i32::read(&mut r)? as usize,
             name: read_string::<8, _>(&mut r)?,
+            modified: false,
         })
     }
 }
@@ -258,8 +744,27 @@ pub enum Error {
     InvalidWadType(String),
     Io(io::Error),
     UnexpectedEof,
+    TooLarge,
+    NameTooLong(String),
+    InvalidPk3(&'static str),
 }
 
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Utf8(err) => write!(f, "invalid lump name: {}", err),
+            Error::InvalidWadType(ident) => write!(f, "invalid wad type: \"{}\"", ident),
+            Error::Io(err) => fmt::Display::fmt(err, f),
+            Error::UnexpectedEof => write!(f, "got eof"),
+            Error::TooLarge => write!(f, "wad is too large"),
+            Error::NameTooLong(name) => write!(f, "lump name too long: \"{}\"", name),
+            Error::InvalidPk3(reason) => write!(f, "invalid pk3: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
 impl From<io::Error> for Error {
     fn from(e: io::Error) -> Error {
         Error::Io(e)
@@ -287,18 +792,236 @@ where
     }
 }
 
+fn write_string<const N: usize, W>(mut w: W, s: &str) -> Result<(), Error>
+where
+    W: Write,
+{
+    if s.len() > N {
+        return Err(Error::NameTooLong(s.to_owned()));
+    }
+
+    let mut bytes = [0u8; N];
+    bytes[..s.len()].copy_from_slice(s.as_bytes());
+
+    w.write_all(&bytes)?;
+    Ok(())
+}
+
+fn write_i32<W>(mut w: W, value: usize) -> Result<(), Error>
+where
+    W: Write,
+{
+    let value = i32::try_from(value).map_err(|_| Error::TooLarge)?;
+
+    w.write_all(&value.to_le_bytes())?;
+    Ok(())
+}
+
+fn read_bytes<const N: usize, R>(mut r: R) -> Result<[u8; N], Error>
+where
+    R: Read,
+{
+    let mut bytes = [0u8; N];
+
+    match r.read_exact(&mut bytes) {
+        Ok(()) => Ok(bytes),
+        Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => Err(Error::UnexpectedEof),
+        Err(err) => Err(Error::Io(err)),
+    }
+}
+
 // INFO: primitive ByteRead impls
-impl ByteRead for i32 {
-    fn read<R>(mut r: R) -> Result<i32, Error>
+macro_rules! impl_byte_read {
+    ($($ty:ty,)*) => {
+        $(
+            impl ByteRead for $ty {
+                fn read<R>(r: R) -> Result<$ty, Error>
+                where
+                    R: Read + Seek,
+                {
+                    read_bytes(r).map(<$ty>::from_le_bytes)
+                }
+            }
+        )*
+    };
+}
+
+impl_byte_read! {
+    u8,
+    i16,
+    u16,
+    i32,
+    u32,
+}
+
+/// Arrays are read element by element, so `[u8; 8]` reads a lump name.
+impl<T, const N: usize> ByteRead for [T; N]
+where
+    T: ByteRead,
+{
+    fn read<R>(mut r: R) -> Result<[T; N], Error>
     where
         R: Read + Seek,
     {
-        let mut bytes = [0u8; 4];
+        let values = (0..N)
+            .map(|_| T::read(&mut r))
+            .collect::<Result<Vec<T>, Error>>()?;
 
-        if r.read(&mut bytes)? == 4 {
-            Ok(i32::from_le_bytes(bytes))
-        } else {
-            Err(Error::UnexpectedEof)
+        match values.try_into() {
+            Ok(values) => Ok(values),
+            Err(_) => unreachable!("read exactly N values"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::io::Cursor;
+
+    /// A WAD with padding between lumps: `MAP01` (marker), `TEXTMAP` at 12,
+    /// `ENDMAP` (marker) and `PALETTE` at 20, with the directory at 28.
+    fn padded_wad() -> Vec<u8> {
+        let mut bytes = b"PWAD".to_vec();
+        bytes.extend(4i32.to_le_bytes());
+        bytes.extend(28i32.to_le_bytes());
+        bytes.extend(b"abcd\0\0\0\0");
+        bytes.extend(b"wxyz\xff\xff\xff\xff");
+
+        for (file_pos, size, name) in [
+            (0i32, 0i32, &b"MAP01\0\0\0"[..]),
+            (12, 4, b"TEXTMAP\0"),
+            (0, 0, b"ENDMAP\0\0"),
+            (20, 4, b"PALETTE\0"),
+        ] {
+            bytes.extend(file_pos.to_le_bytes());
+            bytes.extend(size.to_le_bytes());
+            bytes.extend(name);
+        }
+
+        bytes
+    }
+
+    #[test]
+    fn write_changes() {
+        let original = padded_wad();
+        let mut file = Cursor::new(original.clone());
+        let mut wad = Wad::from_reader(&mut file).unwrap();
+
+        // nothing changed, nothing written
+        wad.write_changes(&mut file).unwrap();
+        assert_eq!(file.get_ref(), &original);
+
+        // fits in place
+        wad.lump_mut("TEXTMAP").unwrap().set_data("ab");
+        wad.write_changes(&mut file).unwrap();
+        assert_eq!(&file.get_ref()[12..20], b"ab\0\0\0\0\0\0");
+        assert_eq!(&file.get_ref()[20..28], &original[20..28]);
+        // the old directory is left alone
+        assert_eq!(&file.get_ref()[28..92], &original[28..92]);
+
+        // moved to the end
+        let saved = file.get_ref().clone();
+        wad.lump_mut("TEXTMAP").unwrap().set_data("a longer map");
+        wad.write_changes(&mut file).unwrap();
+        assert_eq!(&file.get_ref()[..4], b"PWAD");
+        assert_eq!(&file.get_ref()[20..28], &original[20..28]);
+        assert_eq!(&file.get_ref()[92..156], &saved[92..156]);
+
+        let reread = Wad::from_reader(Cursor::new(file.get_ref())).unwrap();
+        let lumps = reread
+            .lumps()
+            .map(|lump| (lump.name().to_string(), lump.data().to_vec()))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            lumps,
+            vec![
+                ("MAP01".to_string(), vec![]),
+                ("TEXTMAP".to_string(), b"a longer map".to_vec()),
+                ("ENDMAP".to_string(), vec![]),
+                ("PALETTE".to_string(), b"wxyz".to_vec()),
+            ]
+        );
+
+        // removed from the directory
+        wad.remove(3);
+        assert_eq!(wad.header().num_lumps, 3);
+        wad.write_changes(&mut file).unwrap();
+
+        let reread = Wad::from_reader(Cursor::new(file.get_ref())).unwrap();
+        assert_eq!(reread.header().num_lumps, 3);
+        assert_eq!(reread.lump("TEXTMAP").unwrap().data(), b"a longer map");
+        assert!(reread.lump("PALETTE").is_none());
+    }
+
+    #[test]
+    fn sections() {
+        let mut file = Cursor::new(padded_wad());
+        let mut wad = Wad::from_reader(&mut file).unwrap();
+
+        assert_eq!(
+            wad.sections(),
+            vec![
+                Section::Map {
+                    name: "MAP01".to_string(),
+                    lumps: 0..3,
+                },
+                Section::Lump(3),
+            ]
+        );
+        assert_eq!(wad.map_lump("MAP01", "TEXTMAP").unwrap().data(), b"abcd");
+
+        wad.lump_at_mut(0).unwrap().rename("MAP02").unwrap();
+        wad.remove(3);
+        assert!(wad.is_modified());
+        wad.write_changes(&mut file).unwrap();
+
+        let reread = Wad::from_reader(Cursor::new(file.get_ref())).unwrap();
+        assert_eq!(reread.maps().collect::<Vec<_>>(), vec!["MAP02".to_string()]);
+        assert_eq!(reread.lumps().count(), 3);
+
+        // only a TEXTMAP or THINGS starts a map
+        let mut wad = Wad::new(WadType::Pwad);
+        wad.push("LOGO", "png");
+        wad.push("SCRIPTS", "lua");
+        assert_eq!(wad.sections(), vec![Section::Lump(0), Section::Lump(1)]);
+    }
+
+    #[test]
+    fn read_records() {
+        use crate::format::fixed::Fixed;
+
+        byte_record! {
+            #[derive(Debug, PartialEq)]
+            struct Vertex {
+                x: Fixed,
+                y: Fixed,
+                flags: u16,
+                name: [u8; 4],
+            }
+        }
+
+        let mut bytes = Vec::new();
+        for (x, y) in [(-1.5f32, 32.0f32), (0.25, 0.0)] {
+            bytes.extend(Fixed::from_f32(x).0.to_le_bytes());
+            bytes.extend(Fixed::from_f32(y).0.to_le_bytes());
+            bytes.extend(0x8001u16.to_le_bytes());
+            bytes.extend(b"ABCD");
         }
+
+        let vertices = Vertex::read_all(&bytes).unwrap();
+        assert_eq!(vertices.len(), 2);
+        assert_eq!(vertices[0].x.to_f32(), -1.5);
+        assert_eq!(vertices[0].y, Fixed(32 << 16));
+        assert_eq!(vertices[1].flags, 0x8001);
+        assert_eq!(&vertices[1].name, b"ABCD");
+
+        assert_eq!(i16::read_all(&[0xff, 0xff, 2, 0]).unwrap(), [-1, 2]);
+        assert!(matches!(
+            Vertex::read_all(&bytes[..bytes.len() - 1]),
+            Err(Error::UnexpectedEof)
+        ));
     }
 }
@@ -1,20 +1,83 @@
 //! Ring Racers map and WAD tools.
+//!
+//! The editor is behind the `gui` feature, which is on by default. Without
+//! it, [`format`] and [`map`] can read, check and write maps without pulling
+//! in [`bevy`](https://bevyengine.org).
 
+#[cfg(feature = "gui")]
 pub mod editor;
 pub mod format;
 pub mod map;
+#[cfg(feature = "scripting")]
+pub mod script;
+#[cfg(feature = "gui")]
 pub mod ui;
 
+#[cfg(feature = "gui")]
+use std::borrow::Cow;
+
+#[cfg(feature = "gui")]
 use bevy::app::PluginGroupBuilder;
+#[cfg(feature = "gui")]
 use bevy::prelude::*;
 
 /// Editor plugins for [`bevy`].
+#[cfg(feature = "gui")]
 pub struct EditorPlugins;
 
+#[cfg(feature = "gui")]
 impl PluginGroup for EditorPlugins {
     fn build(self) -> PluginGroupBuilder {
         PluginGroupBuilder::start::<Self>()
             .add(bevy_egui::EguiPlugin)
+            .add(bevy_prototype_lyon::plugin::ShapePlugin)
+            .add(editor::EditorPlugin)
             .add(ui::UiPlugin)
     }
 }
+
+/// Extends the editor from other plugins.
+///
+/// These can be called before or after [`EditorPlugins`] is added.
+#[cfg(feature = "gui")]
+pub trait EditorAppExt {
+    /// Adds a tab to the dock.
+    ///
+    /// New tabs are put next to the map overview.
+    fn add_editor_tab(&mut self, tab: impl ui::Tab) -> &mut Self;
+
+    /// Adds an edit mode, see [`editor::mode`].
+    fn add_edit_mode(&mut self, id: &'static str, name: impl Into<Cow<'static, str>>) -> &mut Self;
+
+    /// Adds a command to the edit menu, see [`editor::command`].
+    fn add_map_command<F>(&mut self, name: impl Into<Cow<'static, str>>, f: F) -> &mut Self
+    where
+        F: Fn(&mut map::Map, &mut map::Selection) + Send + Sync + 'static;
+}
+
+#[cfg(feature = "gui")]
+impl EditorAppExt for App {
+    fn add_editor_tab(&mut self, tab: impl ui::Tab) -> &mut Self {
+        self.world
+            .get_resource_or_insert_with(ui::NewTabs::default)
+            .push(Box::new(tab));
+        self
+    }
+
+    fn add_edit_mode(&mut self, id: &'static str, name: impl Into<Cow<'static, str>>) -> &mut Self {
+        self.world
+            .get_resource_or_insert_with(editor::EditModes::default)
+            .add(id, name);
+        self
+    }
+
+    fn add_map_command<F>(&mut self, name: impl Into<Cow<'static, str>>, f: F) -> &mut Self
+    where
+        F: Fn(&mut map::Map, &mut map::Selection) + Send + Sync + 'static,
+    {
+        self.world
+            .get_resource_or_insert_with(editor::MapCommands::default)
+            .add(name, f);
+        self
+    }
+}
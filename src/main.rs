@@ -1,25 +1,150 @@
-use rrmap::editor::EditorCamera;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+#[cfg(feature = "gui")]
+use std::path::PathBuf;
+
+#[cfg(feature = "gui")]
+use rrmap::editor::{session::OpenMap, EditorCamera};
+use rrmap::format::pk3::{is_pk3, read_pk3};
 use rrmap::format::wad::Wad;
 use rrmap::map::Map;
 
+#[cfg(feature = "gui")]
 use bevy::prelude::*;
 
 fn main() {
-    let file = std::env::args()
-        .nth(1)
-        .expect("Pass wad file as first argument!");
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+
+    match args.iter().map(String::as_str).collect::<Vec<_>>()[..] {
+        ["info", file] => info(file, None),
+        ["info", file, map] => info(file, Some(map)),
+        ["script", script, file] => run_script(script, file, None),
+        ["script", script, file, map] => run_script(script, file, Some(map)),
+        [] => run_editor(None),
+        [file] => run_editor(Some(file)),
+        _ => {
+            eprintln!("usage: rrmap [map.wad]");
+            eprintln!("       rrmap info <map.wad|map.pk3> [MAP01]");
+            eprintln!("       rrmap script <script.rhai> <map.wad> [MAP01]");
+            eprintln!();
+            eprintln!("Without a map marker, the first map in the file is used.");
+            std::process::exit(1);
+        }
+    }
+}
+
+fn info(file: &str, map: Option<&str>) {
+    let wad = read_wad(file);
+    let (_, map) = read_map(&wad, map);
+    print!("{}", map.stats());
+}
+
+/// Reads a WAD, or a PK3 laid out like one.
+fn read_wad(file: &str) -> Wad {
+    let r = BufReader::new(File::open(file).expect("Failed to open wad file"));
+
+    if is_pk3(Path::new(file)) {
+        read_pk3(r).expect("Failed to read pk3 file")
+    } else {
+        Wad::from_reader(r).expect("Failed to read wad file")
+    }
+}
+
+/// Reads the map with the marker `name`, or the first map, returning the
+/// index of its `TEXTMAP` with it.
+fn read_map(wad: &Wad, name: Option<&str>) -> (usize, Map) {
+    let name = match name {
+        Some(name) => name.to_owned(),
+        None => wad.maps().next().expect("No maps in wad file"),
+    };
+    let Some(idx) = wad.map_lump_index(&name, "TEXTMAP") else {
+        let maps = wad.maps().collect::<Vec<_>>();
+        eprintln!(
+            "No map {} with a TEXTMAP, maps are: {}",
+            name,
+            maps.join(", ")
+        );
+        std::process::exit(1);
+    };
+
+    let textmap = wad.lump_at(idx).expect("TEXTMAP is in the wad");
+    let map = Map::from_str_preserving(&String::from_utf8_lossy(textmap.data()))
+        .expect("Invalid TEXTMAP");
+
+    (idx, map)
+}
 
+/// Runs a script on a map, and saves the map back.
+///
+/// PK3s are only read, so they can't be scripted.
+#[cfg(feature = "scripting")]
+fn run_script(script: &str, file: &str, map: Option<&str>) {
+    if is_pk3(Path::new(file)) {
+        eprintln!("pk3 files can't be saved, so they can't be scripted");
+        std::process::exit(1);
+    }
+
+    let source = std::fs::read_to_string(script).expect("Failed to read script");
+
+    let mut wad = read_wad(file);
+    let (idx, mut map) = read_map(&wad, map);
+
+    if let Err(err) = rrmap::script::run(&source, &mut map, |s| println!("{}", s)) {
+        eprintln!("{}: {}", script, err);
+        std::process::exit(1);
+    }
+
+    wad.lump_at_mut(idx)
+        .expect("TEXTMAP is in the wad")
+        .set_data(map.to_string());
+    let mut output = std::fs::OpenOptions::new()
+        .write(true)
+        .open(file)
+        .expect("Failed to open wad file");
+    wad.write_changes(&mut output)
+        .expect("Failed to write wad file");
+}
+
+#[cfg(not(feature = "scripting"))]
+fn run_script(_script: &str, _file: &str, _map: Option<&str>) {
+    eprintln!("rrmap was built without scripting, rebuild with `--features scripting`");
+    std::process::exit(1);
+}
+
+#[cfg(feature = "gui")]
+fn run_editor(file: Option<&str>) {
     App::new()
         .add_plugins(DefaultPlugins)
         .add_plugins(rrmap::EditorPlugins)
+        .insert_resource(StartupMap(file.map(PathBuf::from)))
         .add_systems(Startup, setup)
         .run()
 }
 
-fn setup(mut commands: Commands) {
+#[cfg(not(feature = "gui"))]
+fn run_editor(_file: Option<&str>) {
+    eprintln!("rrmap was built without the editor, rebuild with `--features gui`");
+    std::process::exit(1);
+}
+
+#[cfg(feature = "gui")]
+#[derive(Resource)]
+struct StartupMap(Option<PathBuf>);
+
+#[cfg(feature = "gui")]
+fn setup(
+    mut commands: Commands,
+    mut startup_map: ResMut<StartupMap>,
+    mut open: EventWriter<OpenMap>,
+) {
     commands.spawn((
         Camera2dBundle::default(),
         EditorCamera,
         // PickRaycastSource,
     ));
+
+    if let Some(path) = startup_map.0.take() {
+        open.send(OpenMap(path));
+    }
 }
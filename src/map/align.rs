@@ -0,0 +1,155 @@
+//! Texture alignment.
+
+use std::collections::{BTreeSet, HashMap};
+
+use super::{Map, SIDEDEF_TEXTURES};
+use crate::format::udmf::Value;
+
+impl Map {
+    /// Aligns the textures of the walls following `sidedef`.
+    ///
+    /// Starting at `sidedef`, this walks along connected sidedefs with the same
+    /// texture, setting their x offsets so the texture flows from one wall to
+    /// the next. Stops at a fork, at a wall with another texture, or when it
+    /// comes back around.
+    ///
+    /// Returns the sidedefs that were aligned, not including `sidedef`.
+    pub fn align_textures(&mut self, sidedef: usize) -> Vec<usize> {
+        let walls = Walls::new(self);
+
+        let mut aligned = Vec::new();
+        let mut visited = BTreeSet::from([sidedef]);
+        let mut current = sidedef;
+
+        while let Some((next, length)) = walls.next(self, current) {
+            if !visited.insert(next) {
+                break;
+            }
+
+            let offset = self.sidedefs[current].offset_x + length.round() as i32;
+            self.sidedefs[next].offset_x = offset;
+
+            aligned.push(next);
+            current = next;
+        }
+
+        aligned
+    }
+}
+
+/// A sidedef, with the vertex its texture runs to.
+#[derive(Clone, Copy, Debug)]
+struct Wall {
+    end: i32,
+    length: f32,
+}
+
+/// The walls of a map, indexed by sidedef and by the vertex they start at.
+struct Walls {
+    walls: HashMap<usize, Wall>,
+    by_start: HashMap<i32, Vec<usize>>,
+}
+
+impl Walls {
+    fn new(map: &Map) -> Walls {
+        let mut walls = HashMap::new();
+        let mut by_start = HashMap::<i32, Vec<usize>>::new();
+
+        for linedef in &map.linedefs {
+            let length = map
+                .linedef_segment(linedef)
+                .map(|segment| segment.a.distance(segment.b));
+
+            // textures run from v1 to v2 on the front, and the other way on
+            // the back
+            let front = (linedef.side_front, linedef.v1, linedef.v2);
+            let back = linedef.side_back.map(|side| (side, linedef.v2, linedef.v1));
+
+            for (side, start, end) in std::iter::once(front).chain(back) {
+                let side = side as usize;
+                if let Some(length) = length {
+                    walls.entry(side).or_insert(Wall { end, length });
+                }
+                by_start.entry(start).or_default().push(side);
+            }
+        }
+
+        Walls { walls, by_start }
+    }
+
+    /// Finds the wall continuing the texture of `sidedef`.
+    ///
+    /// Returns the next sidedef, and how long `sidedef` is.
+    fn next(&self, map: &Map, sidedef: usize) -> Option<(usize, f32)> {
+        let texture = wall_texture(map, sidedef)?;
+        let wall = self.walls.get(&sidedef)?;
+
+        let mut next = self
+            .by_start
+            .get(&wall.end)?
+            .iter()
+            .copied()
+            .filter(|&side| {
+                side != sidedef
+                    && wall_texture(map, side).is_some_and(|t| t.eq_ignore_ascii_case(texture))
+            });
+
+        match (next.next(), next.next()) {
+            (Some(side), None) => Some((side, wall.length)),
+            // fork, or dead end
+            _ => None,
+        }
+    }
+}
+
+/// The texture shown on a wall.
+///
+/// This is the first texture set out of the upper, middle and lower textures.
+fn wall_texture(map: &Map, sidedef: usize) -> Option<&str> {
+    let sidedef = map.sidedefs.get(sidedef)?;
+
+    SIDEDEF_TEXTURES
+        .iter()
+        .find_map(|field| match sidedef.extras.get(field) {
+            Some(Value::String(texture)) if texture != "-" => Some(texture.as_str()),
+            _ => None,
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn align_textures() {
+        let mut map = Map::from_str(
+            r#"
+            namespace = "ringracers";
+            version = 1;
+
+            vertex { x = 0.0; y = 0.0; }
+            vertex { x = 0.0; y = 64.0; }
+            vertex { x = 96.0; y = 64.0; }
+            vertex { x = 96.0; y = 0.0; }
+
+            linedef { v1 = 0; v2 = 1; sidefront = 0; }
+            linedef { v1 = 1; v2 = 2; sidefront = 1; }
+            linedef { v1 = 2; v2 = 3; sidefront = 2; }
+            linedef { v1 = 3; v2 = 0; sidefront = 3; }
+
+            sidedef { sector = 0; texturemiddle = "BRICK"; offsetx = 8; }
+            sidedef { sector = 0; texturemiddle = "BRICK"; }
+            sidedef { sector = 0; texturemiddle = "brick"; }
+            sidedef { sector = 0; texturemiddle = "WOOD"; }
+
+            sector { texturefloor = "FLOOR"; textureceiling = "CEIL"; }
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(map.align_textures(0), vec![1, 2]);
+        assert_eq!(map.sidedefs[1].offset_x, 72);
+        assert_eq!(map.sidedefs[2].offset_x, 168);
+        assert_eq!(map.sidedefs[3].offset_x, 0);
+    }
+}
@@ -0,0 +1,136 @@
+//! Extra fields of maps and map objects.
+
+use std::ops::Index;
+
+use serde::ser::{Serialize, SerializeMap, Serializer};
+use smol_str::SmolStr;
+
+use crate::format::udmf::Value;
+
+/// Extra fields.
+///
+/// Sorted by key, so maps are always written the same way.
+///
+/// Objects only have a handful of extra fields, so they are kept in a sorted
+/// list, which is much cheaper to build and copy than a tree. Keys are short,
+/// so they are kept without allocating.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Extras {
+    fields: Vec<(SmolStr, Value)>,
+}
+
+impl Extras {
+    /// Creates an empty set of fields.
+    pub fn new() -> Extras {
+        Extras::default()
+    }
+
+    /// The number of fields.
+    pub fn len(&self) -> usize {
+        self.fields.len()
+    }
+
+    /// Whether there are no fields.
+    pub fn is_empty(&self) -> bool {
+        self.fields.is_empty()
+    }
+
+    /// Gets the value of `key`.
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        let idx = self.find(key).ok()?;
+        Some(&self.fields[idx].1)
+    }
+
+    /// Gets the value of `key` to change it.
+    pub fn get_mut(&mut self, key: &str) -> Option<&mut Value> {
+        let idx = self.find(key).ok()?;
+        Some(&mut self.fields[idx].1)
+    }
+
+    /// Whether there is a field called `key`.
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.find(key).is_ok()
+    }
+
+    /// Sets `key` to `value`, returning the value it replaced.
+    pub fn insert(&mut self, key: &str, value: Value) -> Option<Value> {
+        match self.find(key) {
+            Ok(idx) => Some(std::mem::replace(&mut self.fields[idx].1, value)),
+            Err(idx) => {
+                self.fields.insert(idx, (key.into(), value));
+                None
+            }
+        }
+    }
+
+    /// Removes `key`, returning its value.
+    pub fn remove(&mut self, key: &str) -> Option<Value> {
+        let idx = self.find(key).ok()?;
+        Some(self.fields.remove(idx).1)
+    }
+
+    /// Iterates over the fields, sorted by key.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &Value)> {
+        self.fields.iter().map(|(key, value)| (key.as_str(), value))
+    }
+
+    /// Iterates over the fields to change their values, sorted by key.
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (&str, &mut Value)> {
+        self.fields
+            .iter_mut()
+            .map(|(key, value)| (key.as_str(), value))
+    }
+
+    /// Iterates over the keys, sorted.
+    pub fn keys(&self) -> impl Iterator<Item = &str> {
+        self.fields.iter().map(|(key, _)| key.as_str())
+    }
+
+    fn find(&self, key: &str) -> Result<usize, usize> {
+        self.fields
+            .binary_search_by(|(field, _)| field.as_str().cmp(key))
+    }
+}
+
+impl Index<&str> for Extras {
+    type Output = Value;
+
+    fn index(&self, key: &str) -> &Value {
+        self.get(key).expect("no field with that key")
+    }
+}
+
+impl<'a> Extend<(&'a str, Value)> for Extras {
+    fn extend<T>(&mut self, iter: T)
+    where
+        T: IntoIterator<Item = (&'a str, Value)>,
+    {
+        for (key, value) in iter {
+            self.insert(key, value);
+        }
+    }
+}
+
+impl<'a> FromIterator<(&'a str, Value)> for Extras {
+    fn from_iter<T>(iter: T) -> Extras
+    where
+        T: IntoIterator<Item = (&'a str, Value)>,
+    {
+        let mut extras = Extras::new();
+        extras.extend(iter);
+        extras
+    }
+}
+
+impl Serialize for Extras {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut map = serializer.serialize_map(Some(self.len()))?;
+        for (key, value) in self.iter() {
+            map.serialize_entry(key, value)?;
+        }
+        map.end()
+    }
+}
@@ -0,0 +1,385 @@
+//! Copying parts of maps in and out of other maps.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use super::Map;
+
+/// A selection of objects in a map, by index.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Selection {
+    pub things: BTreeSet<usize>,
+    pub vertices: BTreeSet<usize>,
+    pub linedefs: BTreeSet<usize>,
+    pub sidedefs: BTreeSet<usize>,
+    pub sectors: BTreeSet<usize>,
+}
+
+impl Selection {
+    /// Checks if nothing is selected.
+    pub fn is_empty(&self) -> bool {
+        self.things.is_empty()
+            && self.vertices.is_empty()
+            && self.linedefs.is_empty()
+            && self.sidedefs.is_empty()
+            && self.sectors.is_empty()
+    }
+
+    /// Deselects everything.
+    pub fn clear(&mut self) {
+        *self = Selection::default();
+    }
+}
+
+impl Map {
+    /// Copies the selected objects into a new map.
+    ///
+    /// Anything the selection depends on is copied too, so selecting a
+    /// linedef brings its vertices, sidedefs and sectors along, and selecting
+    /// a sector brings the lines around it along. Indices are remapped to
+    /// match the new map.
+    pub fn extract(&self, selection: &Selection) -> Map {
+        // a negative side, like `sideback = -1`, is no side at all
+        let sides = |linedef: &super::LineDef| {
+            std::iter::once(linedef.side_front)
+                .chain(linedef.side_back)
+                .filter_map(|side| usize::try_from(side).ok())
+        };
+
+        // find all linedefs touching the selection
+        let linedefs = self
+            .linedefs
+            .iter()
+            .enumerate()
+            .filter(|(idx, linedef)| {
+                selection.linedefs.contains(idx)
+                    || sides(linedef).any(|side| {
+                        selection.sidedefs.contains(&side)
+                            || self.sidedefs.get(side).is_some_and(|sidedef| {
+                                selection.sectors.contains(&(sidedef.sector as usize))
+                            })
+                    })
+            })
+            .map(|(idx, _)| idx)
+            .collect::<BTreeSet<_>>();
+
+        let mut sidedefs = selection.sidedefs.clone();
+        let mut vertices = selection.vertices.clone();
+
+        for linedef in linedefs.iter().map(|&idx| &self.linedefs[idx]) {
+            sidedefs.extend(sides(linedef));
+            vertices.insert(linedef.v1 as usize);
+            vertices.insert(linedef.v2 as usize);
+        }
+
+        // sidedefs facing a sector that doesn't exist are left out, along
+        // with the linedefs they are the front of
+        sidedefs.retain(|&idx| {
+            self.sidedefs
+                .get(idx)
+                .and_then(|sidedef| usize::try_from(sidedef.sector).ok())
+                .is_some_and(|sector| sector < self.sectors.len())
+        });
+        vertices.retain(|&idx| idx < self.vertices.len());
+
+        let mut sectors = selection.sectors.clone();
+        sectors.extend(
+            sidedefs
+                .iter()
+                .map(|&idx| self.sidedefs[idx].sector as usize),
+        );
+        sectors.retain(|&idx| idx < self.sectors.len());
+
+        // remap indices
+        let remap = |set: &BTreeSet<usize>| {
+            set.iter()
+                .enumerate()
+                .map(|(new, &old)| (old as i32, new as i32))
+                .collect::<BTreeMap<i32, i32>>()
+        };
+        let vertex_map = remap(&vertices);
+        let sidedef_map = remap(&sidedefs);
+        let sector_map = remap(&sectors);
+
+        Map {
+            namespace: self.namespace.clone(),
+            version: self.version,
+            things: selection
+                .things
+                .iter()
+                .filter_map(|&idx| self.things.get(idx))
+                .cloned()
+                .collect(),
+            vertices: vertices
+                .iter()
+                .map(|&idx| self.vertices[idx].clone())
+                .collect(),
+            // linedefs missing a vertex or their front side are left out
+            linedefs: linedefs
+                .iter()
+                .filter_map(|&idx| {
+                    let mut linedef = self.linedefs[idx].clone();
+                    linedef.v1 = *vertex_map.get(&linedef.v1)?;
+                    linedef.v2 = *vertex_map.get(&linedef.v2)?;
+                    linedef.side_front = *sidedef_map.get(&linedef.side_front)?;
+                    linedef.side_back = linedef
+                        .side_back
+                        .and_then(|side| sidedef_map.get(&side).copied());
+                    Some(linedef)
+                })
+                .collect(),
+            sidedefs: sidedefs
+                .iter()
+                .map(|&idx| {
+                    let mut sidedef = self.sidedefs[idx].clone();
+                    sidedef.sector = sector_map[&sidedef.sector];
+                    sidedef
+                })
+                .collect(),
+            sectors: sectors
+                .iter()
+                .map(|&idx| self.sectors[idx].clone())
+                .collect(),
+            extras: Default::default(),
+            source: None,
+        }
+    }
+
+    /// Copies all of `fragment` into the map, offsetting it by `(x, y)`.
+    ///
+    /// Returns a selection of the newly added objects.
+    pub fn stamp(&mut self, fragment: &Map, x: f32, y: f32) -> Selection {
+        let vertex_base = self.vertices.len();
+        let linedef_base = self.linedefs.len();
+        let sidedef_base = self.sidedefs.len();
+        let sector_base = self.sectors.len();
+        let thing_base = self.things.len();
+
+        self.things.extend(fragment.things.iter().map(|thing| {
+            let mut thing = thing.clone();
+            thing.x += x;
+            thing.y += y;
+            thing
+        }));
+        self.vertices.extend(fragment.vertices.iter().map(|vertex| {
+            let mut vertex = vertex.clone();
+            vertex.x += x;
+            vertex.y += y;
+            vertex
+        }));
+        self.linedefs
+            .extend(fragment.linedefs.iter().map(|linedef| {
+                let mut linedef = linedef.clone();
+                linedef.v1 += vertex_base as i32;
+                linedef.v2 += vertex_base as i32;
+                linedef.side_front += sidedef_base as i32;
+                linedef.side_back = linedef
+                    .side_back
+                    .filter(|&side| side >= 0)
+                    .map(|side| side + sidedef_base as i32);
+                linedef
+            }));
+        self.sidedefs
+            .extend(fragment.sidedefs.iter().map(|sidedef| {
+                let mut sidedef = sidedef.clone();
+                sidedef.sector += sector_base as i32;
+                sidedef
+            }));
+        self.sectors.extend(fragment.sectors.iter().cloned());
+
+        Selection {
+            things: (thing_base..self.things.len()).collect(),
+            vertices: (vertex_base..self.vertices.len()).collect(),
+            linedefs: (linedef_base..self.linedefs.len()).collect(),
+            sidedefs: (sidedef_base..self.sidedefs.len()).collect(),
+            sectors: (sector_base..self.sectors.len()).collect(),
+        }
+    }
+
+    /// Moves every vertex and thing in the map by `(x, y)`.
+    pub fn translate(&mut self, x: f32, y: f32) {
+        for vertex in self.vertices.iter_mut() {
+            vertex.x += x;
+            vertex.y += y;
+        }
+
+        for thing in self.things.iter_mut() {
+            thing.x += x;
+            thing.y += y;
+        }
+    }
+
+    /// Moves the selected vertices and things by `(x, y)`.
+    ///
+    /// The vertices of selected linedefs are moved along with them.
+    pub fn move_selection(&mut self, selection: &Selection, x: f32, y: f32) {
+        let mut vertices = selection.vertices.clone();
+
+        for linedef in selection
+            .linedefs
+            .iter()
+            .filter_map(|&idx| self.linedefs.get(idx))
+        {
+            vertices.insert(linedef.v1 as usize);
+            vertices.insert(linedef.v2 as usize);
+        }
+
+        for idx in vertices {
+            if let Some(vertex) = self.vertices.get_mut(idx) {
+                vertex.x += x;
+                vertex.y += y;
+            }
+        }
+
+        for &idx in selection.things.iter() {
+            if let Some(thing) = self.things.get_mut(idx) {
+                thing.x += x;
+                thing.y += y;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SQUARES: &str = r#"
+    namespace = "ringracers";
+    version = 1;
+
+    vertex { x = 0.0; y = 0.0; }
+    vertex { x = 64.0; y = 0.0; }
+    vertex { x = 64.0; y = 64.0; }
+    vertex { x = 0.0; y = 64.0; }
+    vertex { x = 128.0; y = 0.0; }
+    vertex { x = 128.0; y = 64.0; }
+
+    linedef { v1 = 0; v2 = 1; sidefront = 0; }
+    linedef { v1 = 1; v2 = 2; sidefront = 1; sideback = 4; twosided = true; }
+    linedef { v1 = 2; v2 = 3; sidefront = 2; }
+    linedef { v1 = 3; v2 = 0; sidefront = 3; }
+    linedef { v1 = 1; v2 = 4; sidefront = 5; }
+    linedef { v1 = 4; v2 = 5; sidefront = 6; }
+    linedef { v1 = 5; v2 = 2; sidefront = 7; }
+
+    sidedef { sector = 0; }
+    sidedef { sector = 0; }
+    sidedef { sector = 0; }
+    sidedef { sector = 0; }
+    sidedef { sector = 1; }
+    sidedef { sector = 1; }
+    sidedef { sector = 1; }
+    sidedef { sector = 1; }
+
+    sector { texturefloor = "FLOOR"; textureceiling = "CEIL"; }
+    sector { texturefloor = "GRASS"; textureceiling = "CEIL"; heightfloor = 16; }
+    "#;
+
+    #[test]
+    fn extract_sector() {
+        let map = Map::from_str(SQUARES).unwrap();
+
+        let selection = Selection {
+            sectors: [1].into(),
+            ..Default::default()
+        };
+        let fragment = map.extract(&selection);
+
+        // the shared line brings the first sector along
+        assert_eq!(fragment.sectors.len(), 2);
+        assert_eq!(fragment.linedefs.len(), 4);
+        assert_eq!(fragment.vertices.len(), 4);
+        assert_eq!(fragment.sidedefs.len(), 5);
+
+        for linedef in fragment.linedefs.iter() {
+            assert!((linedef.v1 as usize) < fragment.vertices.len());
+            assert!((linedef.v2 as usize) < fragment.vertices.len());
+            assert!((linedef.side_front as usize) < fragment.sidedefs.len());
+        }
+    }
+
+    #[test]
+    fn stamp_remaps() {
+        let mut map = Map::from_str(SQUARES).unwrap();
+        let fragment = map.extract(&Selection {
+            linedefs: [5].into(),
+            ..Default::default()
+        });
+
+        let selection = map.stamp(&fragment, 256.0, 0.0);
+
+        assert_eq!(selection.linedefs, [7].into());
+        assert_eq!(selection.vertices, [6, 7].into());
+
+        let linedef = &map.linedefs[7];
+        assert_eq!(map.vertices[linedef.v1 as usize].x, 384.0);
+        assert_eq!(map.sidedefs[linedef.side_front as usize].sector, 2);
+        assert_eq!(map.sectors[2].texture_floor, "GRASS");
+    }
+
+    #[test]
+    fn extract_one_sided() {
+        let map = Map::from_str(
+            r#"
+            namespace = "ringracers";
+            version = 1;
+
+            vertex { x = 0.0; y = 0.0; }
+            vertex { x = 64.0; y = 0.0; }
+
+            linedef { v1 = 0; v2 = 1; sidefront = 0; sideback = -1; }
+
+            sidedef { sector = 0; }
+
+            sector { texturefloor = "FLOOR"; textureceiling = "CEIL"; }
+            "#,
+        )
+        .unwrap();
+
+        let fragment = map.extract(&Selection {
+            linedefs: [0].into(),
+            ..Default::default()
+        });
+
+        assert_eq!(fragment.linedefs.len(), 1);
+        assert_eq!(fragment.sidedefs.len(), 1);
+        assert_eq!(fragment.linedefs[0].side_back, None);
+
+        let mut stamped = map.clone();
+        let selection = stamped.stamp(&map, 128.0, 0.0);
+        let linedef = &stamped.linedefs[*selection.linedefs.first().unwrap()];
+        assert_eq!(linedef.side_front, 1);
+        assert_eq!(linedef.side_back, None);
+    }
+
+    #[test]
+    fn move_selection() {
+        let mut map = Map::from_str(SQUARES).unwrap();
+        map.move_selection(
+            &Selection {
+                vertices: [0].into(),
+                linedefs: [5].into(),
+                ..Default::default()
+            },
+            8.0,
+            -8.0,
+        );
+
+        assert_eq!((map.vertices[0].x, map.vertices[0].y), (8.0, -8.0));
+        assert_eq!((map.vertices[4].x, map.vertices[4].y), (136.0, -8.0));
+        assert_eq!((map.vertices[5].x, map.vertices[5].y), (136.0, 56.0));
+        // the shared vertex of the squares stays put
+        assert_eq!((map.vertices[1].x, map.vertices[1].y), (64.0, 0.0));
+    }
+
+    #[test]
+    fn write_round_trip() {
+        let map = Map::from_str(SQUARES).unwrap();
+        let output = map.to_string();
+        let reparsed = Map::from_str(&output).unwrap();
+
+        assert_eq!(reparsed.to_string(), output);
+        assert_eq!(reparsed.linedefs.len(), map.linedefs.len());
+        assert_eq!(reparsed.linedefs[1].side_back, Some(4));
+    }
+}
@@ -0,0 +1,410 @@
+//! Generating stairs and slopes.
+
+use std::fmt::{self, Display, Formatter};
+
+use super::query::Point;
+use super::{Extras, LineDef, Map, Selection, SideDef, Vertex};
+use crate::format::udmf::Value;
+
+/// The linedef special that slopes a plane to meet the other side of the line.
+///
+/// `arg0` picks the floor and `arg1` the ceiling to slope, with `1` for the
+/// front sector and `2` for the back sector. The far side of the sloped
+/// sector keeps its height.
+pub const PLANE_ALIGN_SPECIAL: i32 = 700;
+
+/// A side of a linedef.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Side {
+    #[default]
+    Front,
+    Back,
+}
+
+impl Map {
+    /// Fills the gap between sectors `a` and `b` with `steps` stairs.
+    ///
+    /// The stairs run between the closest walls of the two sectors, which
+    /// have to be one-sided. Their floors step evenly from the height of `a`
+    /// to the height of `b`, and everything else is copied from `a`. The new
+    /// walls only take the flags of the wall of `a`, since copying its special
+    /// or tag would make every step trigger it.
+    ///
+    /// Returns the new objects, and the two walls the stairs were built
+    /// between.
+    pub fn build_stairs(&mut self, a: usize, b: usize, steps: usize) -> Result<Selection, Error> {
+        if steps == 0 {
+            return Err(Error::NoSteps);
+        }
+        if a == b || a >= self.sectors.len() || b >= self.sectors.len() {
+            return Err(Error::NotTwoSectors);
+        }
+
+        let (wall_a, wall_b) = self.facing_walls(a, b).ok_or(Error::NoWalls)?;
+
+        let mut created = Selection {
+            linedefs: [wall_a, wall_b].into(),
+            ..Default::default()
+        };
+
+        let (p0, p1) = self.endpoints(wall_a);
+        let (mut q0, mut q1) = self.endpoints(wall_b);
+
+        // pair up the closest ends, so the steps don't cross over
+        let distance = |v1: usize, v2: usize| self.point(v1).distance(self.point(v2));
+        if distance(p0, q0) + distance(p1, q1) > distance(p0, q1) + distance(p1, q0) {
+            std::mem::swap(&mut q0, &mut q1);
+        }
+
+        // the edges between each step, from a to b
+        let mut rails = vec![(p0, p1)];
+        for i in 1..steps {
+            let t = i as f32 / steps as f32;
+            let rail = (
+                self.add_vertex(lerp(self.point(p0), self.point(q0), t)),
+                self.add_vertex(lerp(self.point(p1), self.point(q1), t)),
+            );
+
+            created.vertices.extend([rail.0, rail.1]);
+            rails.push(rail);
+        }
+        rails.push((q0, q1));
+
+        let (floor_a, floor_b) = (self.sectors[a].height_floor, self.sectors[b].height_floor);
+        let ceiling = self.sectors[a]
+            .height_ceiling
+            .max(self.sectors[b].height_ceiling);
+
+        let sector_base = self.sectors.len();
+        for step in 0..steps {
+            let t = (step + 1) as f32 / (steps + 1) as f32;
+            let mut sector = self.sectors[a].clone();
+            sector.height_floor = floor_a + ((floor_b - floor_a) as f32 * t).round() as i32;
+            sector.height_ceiling = ceiling;
+
+            self.sectors.push(sector);
+            created.sectors.insert(sector_base + step);
+        }
+
+        let wall = SideDef {
+            offset_x: 0,
+            offset_y: 0,
+            ..self.sidedefs[self.linedefs[wall_a].side_front as usize].clone()
+        };
+        let wall_flags = self.linedefs[wall_a]
+            .extras
+            .iter()
+            .filter(|(_, value)| matches!(value, Value::Boolean(_)))
+            .map(|(key, value)| (key, value.clone()))
+            .collect::<Extras>();
+        let sidedef_base = self.sidedefs.len();
+        let linedef_base = self.linedefs.len();
+
+        self.open_wall(wall_a, sector_base, &wall);
+        self.open_wall(wall_b, sector_base + steps - 1, &wall);
+
+        for (step, (&(r0, r1), &(s0, s1))) in rails.iter().zip(&rails[1..]).enumerate() {
+            let sector = sector_base + step;
+            let center = [r0, r1, s0, s1]
+                .into_iter()
+                .map(|v| self.point(v))
+                .fold(Point::default(), |sum, p| {
+                    Point::new(sum.x + p.x / 4.0, sum.y + p.y / 4.0)
+                });
+
+            // the sides of the step
+            for (v1, v2) in [(r0, s0), (r1, s1)] {
+                self.add_linedef(v1, v2, center, (sector, None), &wall, wall_flags.clone());
+            }
+
+            // the riser up from the last step
+            if step > 0 {
+                self.add_linedef(
+                    r0,
+                    r1,
+                    center,
+                    (sector, Some(sector - 1)),
+                    &wall,
+                    Extras::new(),
+                );
+            }
+        }
+
+        created.linedefs.extend(linedef_base..self.linedefs.len());
+        created.sidedefs.extend(sidedef_base..self.sidedefs.len());
+
+        Ok(created)
+    }
+
+    /// Slopes the planes on `side` of `linedef` to meet the other side.
+    ///
+    /// This uses [`PLANE_ALIGN_SPECIAL`], so the line itself controls the
+    /// slope. `linedef` has to be two-sided.
+    pub fn slope_from_line(
+        &mut self,
+        linedef: usize,
+        side: Side,
+        floor: bool,
+        ceiling: bool,
+    ) -> Result<(), Error> {
+        let linedef = self.linedefs.get_mut(linedef).ok_or(Error::NoLine)?;
+        if linedef.side_back.is_none() {
+            return Err(Error::OneSided);
+        }
+
+        let side = match side {
+            Side::Front => 1,
+            Side::Back => 2,
+        };
+        let arg = |sloped: bool| Value::Integer(if sloped { side } else { 0 });
+
+        let extras = &mut linedef.extras;
+        extras.insert("special", Value::Integer(PLANE_ALIGN_SPECIAL));
+        extras.insert("arg0", arg(floor));
+        extras.insert("arg1", arg(ceiling));
+
+        Ok(())
+    }
+
+    /// The closest one-sided walls of sectors `a` and `b`.
+    fn facing_walls(&self, a: usize, b: usize) -> Option<(usize, usize)> {
+        let walls = |sector: usize| {
+            self.linedefs
+                .iter()
+                .enumerate()
+                .filter(move |(_, linedef)| {
+                    linedef.side_back.is_none()
+                        && self
+                            .sidedefs
+                            .get(linedef.side_front as usize)
+                            .is_some_and(|side| side.sector as usize == sector)
+                })
+                .filter_map(|(idx, linedef)| {
+                    let segment = self.linedef_segment(linedef)?;
+                    Some((idx, lerp(segment.a, segment.b, 0.5)))
+                })
+        };
+
+        walls(a)
+            .flat_map(|wall_a| walls(b).map(move |wall_b| (wall_a, wall_b)))
+            .min_by(|((_, a), (_, b)), ((_, c), (_, d))| a.distance(*b).total_cmp(&c.distance(*d)))
+            .map(|((wall_a, _), (wall_b, _))| (wall_a, wall_b))
+    }
+
+    /// Turns the one-sided `linedef` into a riser up to `sector`.
+    fn open_wall(&mut self, linedef: usize, sector: usize, wall: &SideDef) {
+        let mut front = self.linedefs[linedef].side_front as usize;
+
+        // don't touch the other walls sharing the sidedef
+        let shared = self
+            .linedefs
+            .iter()
+            .flat_map(|linedef| std::iter::once(linedef.side_front).chain(linedef.side_back))
+            .filter(|&side| side as usize == front)
+            .count()
+            > 1;
+        if shared {
+            self.sidedefs.push(self.sidedefs[front].clone());
+            front = self.sidedefs.len() - 1;
+            self.linedefs[linedef].side_front = front as i32;
+        }
+
+        lower_texture(&mut self.sidedefs[front]);
+
+        let back = self.add_sidedef(sector, wall, true);
+        let linedef = &mut self.linedefs[linedef];
+        linedef.side_back = Some(back as i32);
+        linedef.two_sided = true;
+    }
+
+    /// Adds a linedef, with its front facing `inside`.
+    fn add_linedef(
+        &mut self,
+        mut v1: usize,
+        mut v2: usize,
+        inside: Point,
+        (front, back): (usize, Option<usize>),
+        wall: &SideDef,
+        extras: Extras,
+    ) {
+        let (a, b) = (self.point(v1), self.point(v2));
+
+        // the front side is on the right
+        if (b.x - a.x) * (inside.y - a.y) - (b.y - a.y) * (inside.x - a.x) > 0.0 {
+            std::mem::swap(&mut v1, &mut v2);
+        }
+
+        let two_sided = back.is_some();
+        let side_front = self.add_sidedef(front, wall, two_sided) as i32;
+        let side_back = back.map(|back| self.add_sidedef(back, wall, true) as i32);
+
+        self.linedefs.push(LineDef {
+            v1: v1 as i32,
+            v2: v2 as i32,
+            side_front,
+            side_back,
+            two_sided,
+            extras,
+        });
+    }
+
+    fn add_sidedef(&mut self, sector: usize, wall: &SideDef, two_sided: bool) -> usize {
+        let mut sidedef = SideDef {
+            sector: sector as i32,
+            ..wall.clone()
+        };
+        if two_sided {
+            lower_texture(&mut sidedef);
+        }
+
+        self.sidedefs.push(sidedef);
+        self.sidedefs.len() - 1
+    }
+
+    fn add_vertex(&mut self, point: Point) -> usize {
+        self.vertices.push(Vertex {
+            x: point.x,
+            y: point.y,
+            extras: Extras::new(),
+        });
+        self.vertices.len() - 1
+    }
+
+    fn endpoints(&self, linedef: usize) -> (usize, usize) {
+        let linedef = &self.linedefs[linedef];
+        (linedef.v1 as usize, linedef.v2 as usize)
+    }
+
+    fn point(&self, vertex: usize) -> Point {
+        let vertex = &self.vertices[vertex];
+        Point::new(vertex.x, vertex.y)
+    }
+}
+
+/// Moves the middle texture of a sidedef down to the lower texture, so a
+/// solid wall shows up as a step.
+fn lower_texture(sidedef: &mut SideDef) {
+    if let Some(texture) = sidedef.extras.remove("texturemiddle") {
+        if !sidedef.extras.contains_key("texturebottom") {
+            sidedef.extras.insert("texturebottom", texture);
+        }
+    }
+}
+
+fn lerp(a: Point, b: Point, t: f32) -> Point {
+    Point::new(a.x + (b.x - a.x) * t, a.y + (b.y - a.y) * t)
+}
+
+/// An error for generators.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Error {
+    NoSteps,
+    NotTwoSectors,
+    NoWalls,
+    NoLine,
+    OneSided,
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::NoSteps => write!(f, "there has to be at least one step"),
+            Error::NotTwoSectors => write!(f, "pick two different sectors"),
+            Error::NoWalls => write!(f, "the sectors need one-sided walls to build between"),
+            Error::NoLine => write!(f, "pick a linedef"),
+            Error::OneSided => write!(f, "the linedef needs a sector on both sides"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn build_stairs() {
+        // two rooms with a gap between them
+        let mut map = Map::from_str(
+            r#"
+            namespace = "ringracers";
+            version = 1;
+
+            vertex { x = 0.0; y = 0.0; }
+            vertex { x = 0.0; y = 64.0; }
+            vertex { x = 64.0; y = 64.0; }
+            vertex { x = 64.0; y = 0.0; }
+            vertex { x = 160.0; y = 0.0; }
+            vertex { x = 160.0; y = 64.0; }
+            vertex { x = 224.0; y = 64.0; }
+            vertex { x = 224.0; y = 0.0; }
+
+            linedef { v1 = 0; v2 = 1; sidefront = 0; }
+            linedef { v1 = 1; v2 = 2; sidefront = 0; }
+            linedef { v1 = 2; v2 = 3; sidefront = 0; special = 400; id = 5; dontpegbottom = true; }
+            linedef { v1 = 3; v2 = 0; sidefront = 0; }
+            linedef { v1 = 4; v2 = 5; sidefront = 1; }
+            linedef { v1 = 5; v2 = 6; sidefront = 1; }
+            linedef { v1 = 6; v2 = 7; sidefront = 1; }
+            linedef { v1 = 7; v2 = 4; sidefront = 1; }
+
+            sidedef { sector = 0; texturemiddle = "BRICK"; }
+            sidedef { sector = 1; texturemiddle = "BRICK"; }
+
+            sector { texturefloor = "FLOOR"; textureceiling = "CEIL"; heightceiling = 128; }
+            sector { texturefloor = "FLOOR"; textureceiling = "CEIL"; heightfloor = 64; heightceiling = 192; }
+            "#,
+        )
+        .unwrap();
+
+        let created = map.build_stairs(0, 1, 3).unwrap();
+
+        assert_eq!(created.sectors, [2, 3, 4].into());
+        let floors = created
+            .sectors
+            .iter()
+            .map(|&idx| map.sectors[idx].height_floor)
+            .collect::<Vec<_>>();
+        assert_eq!(floors, [16, 32, 48]);
+        assert_eq!(map.sectors[2].height_ceiling, 192);
+
+        // the facing walls became risers
+        for wall in [2, 4] {
+            let linedef = &map.linedefs[wall];
+            assert!(linedef.two_sided);
+            let front = &map.sidedefs[linedef.side_front as usize];
+            assert!(front.extras.contains_key("texturebottom"));
+            assert!(!front.extras.contains_key("texturemiddle"));
+        }
+
+        // without touching the walls they shared a sidedef with
+        let side = &map.sidedefs[map.linedefs[0].side_front as usize];
+        assert!(side.extras.contains_key("texturemiddle"));
+
+        // every step faces inwards
+        for &idx in created.linedefs.iter() {
+            let linedef = &map.linedefs[idx];
+            if idx >= 8 && !linedef.two_sided {
+                assert_eq!(
+                    linedef.extras.get("dontpegbottom"),
+                    Some(&Value::Boolean(true))
+                );
+                assert!(!linedef.extras.contains_key("special"));
+                assert!(!linedef.extras.contains_key("id"));
+            }
+
+            let segment = map.linedef_segment(linedef).unwrap();
+            let middle = lerp(segment.a, segment.b, 0.5);
+            let sector = map.sidedefs[linedef.side_front as usize].sector as usize;
+
+            if created.sectors.contains(&sector) {
+                let inside = Point::new(
+                    middle.x + (segment.b.y - segment.a.y) * 0.01,
+                    middle.y - (segment.b.x - segment.a.x) * 0.01,
+                );
+                assert_eq!(map.sector_at(inside.x, inside.y), Some(sector));
+            }
+        }
+    }
+}
@@ -0,0 +1,161 @@
+//! Editor-only object groups.
+//!
+//! Any object can be put in a named group, to work on big maps a piece at a
+//! time. Groups are kept in the map as a user field, so they are saved with
+//! the map but ignored by the game.
+
+use std::collections::BTreeSet;
+
+use super::{Extras, Map, Selection};
+use crate::format::udmf::Value;
+
+/// The prefix of fields reserved for the editor.
+pub const EDITOR_PREFIX: &str = "user_rrmap_";
+
+/// The field holding the group of an object.
+pub const GROUP_FIELD: &str = "user_rrmap_group";
+
+/// The group of an object, if it is in one.
+pub fn group_of(extras: &Extras) -> Option<&str> {
+    match extras.get(GROUP_FIELD) {
+        Some(Value::String(group)) => Some(group),
+        _ => None,
+    }
+}
+
+impl Map {
+    /// The names of all groups in the map.
+    pub fn groups(&self) -> BTreeSet<String> {
+        self.all_extras()
+            .filter_map(|(_, extras)| group_of(extras))
+            .map(|group| group.to_string())
+            .collect()
+    }
+
+    /// Finds all objects in any of `groups`.
+    pub fn in_groups(&self, groups: &BTreeSet<String>) -> Selection {
+        let mut selection = Selection::default();
+
+        if groups.is_empty() {
+            return selection;
+        }
+
+        for (kind, extras) in self.all_extras() {
+            if group_of(extras).is_some_and(|group| groups.contains(group)) {
+                let (set, idx) = kind.set(&mut selection);
+                set.insert(idx);
+            }
+        }
+
+        selection
+    }
+
+    /// Puts the selected objects in `group`.
+    ///
+    /// If `group` is `None`, the objects are taken out of their groups.
+    pub fn set_group(&mut self, selection: &Selection, group: Option<&str>) {
+        let set = |extras: &mut Extras| match group {
+            Some(group) => {
+                extras.insert(GROUP_FIELD, Value::String(group.into()));
+            }
+            None => {
+                extras.remove(GROUP_FIELD);
+            }
+        };
+
+        macro_rules! set_all {
+            ($($list:ident),*) => {
+                $(
+                    for &idx in selection.$list.iter() {
+                        if let Some(object) = self.$list.get_mut(idx) {
+                            set(&mut object.extras);
+                        }
+                    }
+                )*
+            };
+        }
+
+        set_all!(things, vertices, linedefs, sidedefs, sectors);
+    }
+
+    /// The extras of every object in the map.
+    pub(super) fn all_extras(&self) -> impl Iterator<Item = (ObjectIdx, &Extras)> + '_ {
+        let things = self.things.iter().map(|t| &t.extras).enumerate();
+        let vertices = self.vertices.iter().map(|v| &v.extras).enumerate();
+        let linedefs = self.linedefs.iter().map(|l| &l.extras).enumerate();
+        let sidedefs = self.sidedefs.iter().map(|s| &s.extras).enumerate();
+        let sectors = self.sectors.iter().map(|s| &s.extras).enumerate();
+
+        things
+            .map(|(idx, e)| (ObjectIdx::Thing(idx), e))
+            .chain(vertices.map(|(idx, e)| (ObjectIdx::Vertex(idx), e)))
+            .chain(linedefs.map(|(idx, e)| (ObjectIdx::LineDef(idx), e)))
+            .chain(sidedefs.map(|(idx, e)| (ObjectIdx::SideDef(idx), e)))
+            .chain(sectors.map(|(idx, e)| (ObjectIdx::Sector(idx), e)))
+    }
+}
+
+/// The index of any object.
+#[derive(Clone, Copy)]
+pub(super) enum ObjectIdx {
+    Thing(usize),
+    Vertex(usize),
+    LineDef(usize),
+    SideDef(usize),
+    Sector(usize),
+}
+
+impl ObjectIdx {
+    pub(super) fn set(self, selection: &mut Selection) -> (&mut BTreeSet<usize>, usize) {
+        match self {
+            ObjectIdx::Thing(idx) => (&mut selection.things, idx),
+            ObjectIdx::Vertex(idx) => (&mut selection.vertices, idx),
+            ObjectIdx::LineDef(idx) => (&mut selection.linedefs, idx),
+            ObjectIdx::SideDef(idx) => (&mut selection.sidedefs, idx),
+            ObjectIdx::Sector(idx) => (&mut selection.sectors, idx),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn groups() {
+        let mut map = Map::from_str(
+            r#"
+            namespace = "ringracers";
+            version = 1;
+
+            thing { x = 0.0; y = 0.0; angle = 0; type = 1; }
+            thing { x = 0.0; y = 0.0; angle = 0; type = 1; }
+
+            vertex { x = 0.0; y = 0.0; }
+            vertex { x = 0.0; y = 64.0; }
+            "#,
+        )
+        .unwrap();
+
+        map.set_group(
+            &Selection {
+                things: [1].into(),
+                vertices: [0, 1].into(),
+                ..Default::default()
+            },
+            Some("Section A"),
+        );
+        assert_eq!(map.groups(), ["Section A".to_string()].into());
+
+        let in_a = map.in_groups(&["Section A".to_string()].into());
+        assert_eq!(in_a.things, [1].into());
+        assert_eq!(in_a.vertices, [0, 1].into());
+
+        // survives saving
+        let mut map = Map::from_str(&map.to_string()).unwrap();
+        assert_eq!(group_of(&map.things[1].extras), Some("Section A"));
+
+        map.set_group(&in_a, None);
+        assert!(map.groups().is_empty());
+    }
+}
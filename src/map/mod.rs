@@ -0,0 +1,464 @@
+//! Map/course format readers.
+
+mod align;
+mod extras;
+mod fragment;
+pub mod generate;
+pub mod group;
+pub mod namespace;
+mod preserve;
+pub mod query;
+mod recover;
+pub mod remove;
+pub mod replace;
+pub mod stats;
+pub mod things;
+pub mod validate;
+
+pub use extras::Extras;
+pub use fragment::Selection;
+pub use recover::Diagnostic;
+
+use preserve::Source;
+use recover::{DEFAULT_NAMESPACE, DEFAULT_VERSION};
+
+use std::borrow::Cow;
+use std::fmt::{self, Display, Formatter};
+use std::ops::{Deref, DerefMut};
+use std::sync::Arc;
+
+use serde::de::{self, MapAccess, Visitor};
+use serde::{Deserialize, Deserializer, Serialize};
+
+use crate::format::udmf;
+
+/// A single map.
+///
+/// Stores all information about the map in continguous memory. This does not
+/// include textures or any other fun things!
+#[derive(Clone, Debug, Default)]
+pub struct Map {
+    pub namespace: String,
+    pub version: i32,
+    pub things: Vec<Thing>,
+    pub linedefs: Vec<LineDef>,
+    pub sidedefs: Vec<SideDef>,
+    pub sectors: Vec<Sector>,
+    pub vertices: Vec<Vertex>,
+    pub extras: Extras,
+    /// The text the map was read from, if it is being preserved.
+    source: Option<Arc<Source>>,
+}
+
+impl Map {
+    /// Reads a map from a string.
+    pub fn from_str(str: &str) -> Result<Map, udmf::de::Error> {
+        Map::parse(&preprocess(str), None, None)
+    }
+
+    /// Reads a map from preprocessed input.
+    ///
+    /// If `key_starts` is given, the offset of every top level key is pushed
+    /// to it. If `diagnostics` is given, values that fail to read are skipped
+    /// and pushed to it instead of failing the whole map.
+    fn parse(
+        input: &str,
+        mut key_starts: Option<&mut Vec<usize>>,
+        mut diagnostics: Option<&mut Vec<Diagnostic>>,
+    ) -> Result<Map, udmf::de::Error> {
+        #[derive(Default)]
+        struct PartialMap {
+            namespace: Option<String>,
+            version: Option<i32>,
+            things: Vec<Thing>,
+            linedefs: Vec<LineDef>,
+            sidedefs: Vec<SideDef>,
+            sectors: Vec<Sector>,
+            vertices: Vec<Vertex>,
+            extras: Extras,
+        }
+
+        impl PartialMap {
+            fn read_value(
+                &mut self,
+                ident: &str,
+                parser: &mut udmf::de::Parser,
+            ) -> Result<(), udmf::de::Error> {
+                match ident {
+                    "namespace" => {
+                        self.namespace = Some(parser.next_value()?);
+                    }
+                    "version" => {
+                        self.version = Some(parser.next_value()?);
+                    }
+                    "thing" => {
+                        self.things.push(parser.next_value()?);
+                    }
+                    "vertex" => {
+                        self.vertices.push(parser.next_value()?);
+                    }
+                    "linedef" => {
+                        self.linedefs.push(parser.next_value()?);
+                    }
+                    "sidedef" => {
+                        self.sidedefs.push(parser.next_value()?);
+                    }
+                    "sector" => {
+                        self.sectors.push(parser.next_value()?);
+                    }
+                    extra => {
+                        self.extras.insert(extra, parser.next_value()?);
+                    }
+                }
+
+                Ok(())
+            }
+        }
+
+        let mut map = PartialMap::default();
+
+        // parse
+        let mut parser = udmf::de::Parser::new(input);
+        let offset = |parser: &udmf::de::Parser| input.len() - parser.remaining().len();
+
+        loop {
+            let start = offset(&parser);
+
+            let read = match parser.next_key() {
+                Ok(Some(ident)) => {
+                    if let Some(key_starts) = key_starts.as_mut() {
+                        key_starts.push(offset(&parser) - ident.len());
+                    }
+
+                    map.read_value(ident, &mut parser)
+                }
+                Ok(None) => break,
+                Err(error) => Err(error),
+            };
+
+            if let Err(error) = read {
+                let Some(diagnostics) = diagnostics.as_mut() else {
+                    return Err(error);
+                };
+
+                // start over at the key, and skip past the whole value
+                parser = udmf::de::Parser::new(&input[start..]);
+                parser.skip_value();
+
+                let span = start + (input[start..].len() - input[start..].trim_start().len())
+                    ..offset(&parser);
+                diagnostics.push(Diagnostic::new(span, error));
+            }
+        }
+
+        let mut missing = |field: &'static str| {
+            let error = udmf::de::Error::missing_field(field);
+            match diagnostics.as_mut() {
+                Some(diagnostics) => {
+                    diagnostics.push(Diagnostic::new(0..0, error));
+                    Ok(())
+                }
+                None => Err(error),
+            }
+        };
+
+        if map.namespace.is_none() {
+            missing("namespace")?;
+        }
+        if map.version.is_none() {
+            missing("version")?;
+        }
+
+        // create from partial
+        Ok(Map {
+            namespace: map
+                .namespace
+                .unwrap_or_else(|| DEFAULT_NAMESPACE.to_string()),
+            version: map.version.unwrap_or(DEFAULT_VERSION),
+            linedefs: map.linedefs,
+            sidedefs: map.sidedefs,
+            vertices: map.vertices,
+            things: map.things,
+            sectors: map.sectors,
+            extras: map.extras,
+            source: None,
+        })
+    }
+
+    /// Writes the map as `udmf`.
+    ///
+    /// If the map was read with [`Map::from_str_preserving`], unchanged
+    /// objects are written exactly as they were read.
+    pub fn write(&self, out: &mut impl fmt::Write) -> Result<(), udmf::ser::Error> {
+        if let Some(source) = &self.source {
+            return self.write_preserving(source, out);
+        }
+
+        let mut writer = udmf::ser::Writer::new(out);
+
+        writer.write_value("namespace", &self.namespace)?;
+        writer.write_value("version", &self.version)?;
+
+        for (key, value) in self.extras.iter() {
+            writer.write_value(key, value)?;
+        }
+
+        for thing in self.things.iter() {
+            writer.write_value("thing", thing)?;
+        }
+        for vertex in self.vertices.iter() {
+            writer.write_value("vertex", vertex)?;
+        }
+        for linedef in self.linedefs.iter() {
+            writer.write_value("linedef", linedef)?;
+        }
+        for sidedef in self.sidedefs.iter() {
+            writer.write_value("sidedef", sidedef)?;
+        }
+        for sector in self.sectors.iter() {
+            writer.write_value("sector", sector)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Display for Map {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.write(f).map_err(|_| fmt::Error)
+    }
+}
+
+fn preprocess(input: &str) -> Cow<'_, str> {
+    // blank out comments, so everything else stays where it was
+    // TODO: Multilines
+    let Some(mut start) = find_comment(input) else {
+        return Cow::Borrowed(input);
+    };
+    let mut output = String::with_capacity(input.len());
+    let mut copied = 0;
+
+    loop {
+        let end = input[start..]
+            .find('\n')
+            .map_or(input.len(), |idx| start + idx);
+        output.push_str(&input[copied..start]);
+        output.extend(std::iter::repeat_n(' ', end - start));
+        copied = end;
+
+        match find_comment(&input[end..]) {
+            Some(idx) => start = end + idx,
+            None => break,
+        }
+    }
+
+    output.push_str(&input[copied..]);
+    Cow::Owned(output)
+}
+
+/// Finds the start of the first comment in `input`.
+///
+/// Slashes are rare outside of comments, so this looks for them one at a
+/// time, which is much faster than searching for both.
+fn find_comment(input: &str) -> Option<usize> {
+    let mut from = 0;
+
+    while let Some(idx) = input[from..].find('/') {
+        let idx = from + idx;
+        if input.as_bytes().get(idx + 1) == Some(&b'/') {
+            return Some(idx);
+        }
+        from = idx + 1;
+    }
+
+    None
+}
+
+/// A thing.
+///
+/// I didn't name this.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Thing {
+    pub x: f32,
+    pub y: f32,
+    pub height: Option<f32>,
+    /// Not every namespace requires an angle.
+    pub angle: i32,
+    #[serde(rename = "type")]
+    pub kind: i32,
+    #[serde(flatten)]
+    pub extras: Extras,
+}
+
+/// A single vertex on the map.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Vertex {
+    pub x: f32,
+    pub y: f32,
+    #[serde(flatten)]
+    pub extras: Extras,
+}
+
+/// A line definition.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct LineDef {
+    pub v1: i32,
+    pub v2: i32,
+    #[serde(rename = "sidefront")]
+    pub side_front: i32,
+    #[serde(rename = "sideback")]
+    pub side_back: Option<i32>,
+    #[serde(rename = "twosided")]
+    pub two_sided: bool,
+    #[serde(flatten)]
+    pub extras: Extras,
+}
+
+/// A side definition.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct SideDef {
+    #[serde(rename = "offsetx")]
+    pub offset_x: i32,
+    #[serde(rename = "offsety")]
+    pub offset_y: i32,
+    pub sector: i32,
+    #[serde(flatten)]
+    pub extras: Extras,
+}
+
+/// The fields of a [`SideDef`] that hold textures.
+///
+/// These are kept in [`SideDef::extras`].
+pub const SIDEDEF_TEXTURES: [&str; 3] = ["texturetop", "texturemiddle", "texturebottom"];
+
+/// A sector.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Sector {
+    #[serde(rename = "heightfloor")]
+    pub height_floor: i32,
+    #[serde(rename = "heightceiling")]
+    pub height_ceiling: i32,
+    #[serde(rename = "texturefloor")]
+    pub texture_floor: String,
+    #[serde(rename = "textureceiling")]
+    pub texture_ceiling: String,
+    #[serde(flatten)]
+    pub extras: Extras,
+}
+
+/// Implements [`Deserialize`] for a map object.
+///
+/// Fields are given as `field: "name"`, required ones first, then the ones
+/// that default when missing. Every other field is read into the extras.
+///
+/// `#[serde(flatten)]` would read every field into a buffer first, which
+/// made reading big maps several times slower.
+macro_rules! impl_deserialize {
+    (
+        $ty:ident,
+        [$($field:ident: $name:literal),* $(,)?],
+        [$($default_field:ident: $default_name:literal),* $(,)?] $(,)?
+    ) => {
+        impl<'de> Deserialize<'de> for $ty {
+            fn deserialize<D>(deserializer: D) -> Result<$ty, D::Error>
+            where
+                D: Deserializer<'de>,
+            {
+                struct ObjectVisitor;
+
+                impl<'de> Visitor<'de> for ObjectVisitor {
+                    type Value = $ty;
+
+                    fn expecting(&self, f: &mut Formatter) -> fmt::Result {
+                        f.write_str(concat!("a block of ", stringify!($ty)))
+                    }
+
+                    fn visit_map<A>(self, mut access: A) -> Result<$ty, A::Error>
+                    where
+                        A: MapAccess<'de>,
+                    {
+                        $(let mut $field = None;)*
+                        $(let mut $default_field = None;)*
+                        let mut extras = Extras::new();
+
+                        while let Some(Key(key)) = access.next_key()? {
+                            match &*key {
+                                $($name => {
+                                    if $field.is_some() {
+                                        return Err(de::Error::duplicate_field($name));
+                                    }
+                                    $field = Some(access.next_value()?);
+                                })*
+                                $($default_name => {
+                                    if $default_field.is_some() {
+                                        return Err(de::Error::duplicate_field($default_name));
+                                    }
+                                    $default_field = Some(access.next_value()?);
+                                })*
+                                _ => {
+                                    extras.insert(&key, access.next_value()?);
+                                }
+                            }
+                        }
+
+                        Ok($ty {
+                            $($field: $field.ok_or_else(|| de::Error::missing_field($name))?,)*
+                            $($default_field: $default_field.unwrap_or_default(),)*
+                            extras,
+                        })
+                    }
+                }
+
+                deserializer.deserialize_map(ObjectVisitor)
+            }
+        }
+    };
+}
+
+impl_deserialize!(Thing, [x: "x", y: "y", kind: "type"], [height: "height", angle: "angle"]);
+impl_deserialize!(Vertex, [x: "x", y: "y"], []);
+impl_deserialize!(
+    LineDef,
+    [v1: "v1", v2: "v2", side_front: "sidefront"],
+    [side_back: "sideback", two_sided: "twosided"],
+);
+impl_deserialize!(SideDef, [sector: "sector"], [offset_x: "offsetx", offset_y: "offsety"]);
+impl_deserialize!(
+    Sector,
+    [texture_floor: "texturefloor", texture_ceiling: "textureceiling"],
+    [height_floor: "heightfloor", height_ceiling: "heightceiling"],
+);
+
+/// The key of a field of a map object, borrowed from the input if it can be.
+struct Key<'de>(Cow<'de, str>);
+
+impl<'de> Deserialize<'de> for Key<'de> {
+    fn deserialize<D>(deserializer: D) -> Result<Key<'de>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct KeyVisitor;
+
+        impl<'de> Visitor<'de> for KeyVisitor {
+            type Value = Key<'de>;
+
+            fn expecting(&self, f: &mut Formatter) -> fmt::Result {
+                f.write_str("a field name")
+            }
+
+            fn visit_borrowed_str<E>(self, v: &'de str) -> Result<Key<'de>, E> {
+                Ok(Key(Cow::Borrowed(v)))
+            }
+
+            fn visit_str<E>(self, v: &str) -> Result<Key<'de>, E> {
+                Ok(Key(Cow::Owned(v.to_owned())))
+            }
+
+            fn visit_string<E>(self, v: String) -> Result<Key<'de>, E> {
+                Ok(Key(Cow::Owned(v)))
+            }
+        }
+
+        deserializer.deserialize_str(KeyVisitor)
+    }
+}
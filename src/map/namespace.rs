@@ -0,0 +1,722 @@
+//! UDMF namespaces, and the fields objects can have in each.
+//!
+//! Every field of an object is looked up by name in the tables here, with
+//! [`Namespace::field`]. The fields every namespace shares are kept in the
+//! map structs, like [`Thing::x`](super::Thing::x), and everything else in the
+//! extras, but [`Map::field`] and [`Map::set_field`] read and write both the
+//! same way. The tables cover the fields the source ports document, so
+//! [`Map::set_field`] refuses fields missing from them, and converts values
+//! to the type the table gives. Maps are still read with whatever fields
+//! they have, which are only warned about.
+
+use std::collections::BTreeMap;
+use std::fmt::{self, Display, Formatter};
+
+use super::group::{ObjectIdx, EDITOR_PREFIX};
+use super::{Extras, Map, Selection};
+use crate::format::udmf::{Type, Value};
+
+/// A namespace a map can be written in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Namespace {
+    /// The base fields of the UDMF spec.
+    ///
+    /// `heretic`, `hexen` and `strife` maps use these too.
+    Doom,
+    ZDoom,
+    Srb2,
+    RingRacers,
+}
+
+/// A kind of object in a map.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Object {
+    Thing,
+    Vertex,
+    LineDef,
+    SideDef,
+    Sector,
+}
+
+impl Object {
+    /// The name of the object, as shown in the UI.
+    pub fn name(self) -> &'static str {
+        match self {
+            Object::Thing => "Thing",
+            Object::Vertex => "Vertex",
+            Object::LineDef => "Linedef",
+            Object::SideDef => "Sidedef",
+            Object::Sector => "Sector",
+        }
+    }
+
+    /// The fields every namespace has, which are kept in the map structs.
+    fn core_fields(self) -> &'static [Field] {
+        match self {
+            Object::Thing => CORE_THING,
+            Object::Vertex => CORE_VERTEX,
+            Object::LineDef => CORE_LINEDEF,
+            Object::SideDef => CORE_SIDEDEF,
+            Object::Sector => CORE_SECTOR,
+        }
+    }
+}
+
+/// A field an object can have.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Field {
+    /// The name of the field, as written in maps.
+    pub name: &'static str,
+    /// The type of the field's values.
+    pub ty: Type,
+}
+
+impl Field {
+    /// Whether `value` can be stored in the field as it is.
+    ///
+    /// Integers are read as floats, so they fit float fields too.
+    pub fn fits(&self, value: &Value) -> bool {
+        let ty = value.value_type();
+        ty == self.ty || (ty == Type::Integer && self.ty == Type::Float)
+    }
+}
+
+/// Writes a table of [`Field`]s, grouped by type.
+macro_rules! fields {
+    ($($ty:ident: [$($name:literal),* $(,)?]),* $(,)?) => {
+        &[$($(Field { name: $name, ty: Type::$ty },)*)*]
+    };
+}
+
+impl Namespace {
+    /// Every namespace.
+    pub const ALL: [Namespace; 4] = [
+        Namespace::Doom,
+        Namespace::ZDoom,
+        Namespace::Srb2,
+        Namespace::RingRacers,
+    ];
+
+    /// Finds the namespace called `name`, ignoring case.
+    pub fn from_name(name: &str) -> Option<Namespace> {
+        match name.to_ascii_lowercase().as_str() {
+            "doom" | "heretic" | "hexen" | "strife" => Some(Namespace::Doom),
+            "zdoom" | "zdoomtranslated" => Some(Namespace::ZDoom),
+            "srb2" => Some(Namespace::Srb2),
+            "ringracers" => Some(Namespace::RingRacers),
+            _ => None,
+        }
+    }
+
+    /// The name of the namespace, as written in maps.
+    pub fn name(self) -> &'static str {
+        match self {
+            Namespace::Doom => "doom",
+            Namespace::ZDoom => "zdoom",
+            Namespace::Srb2 => "srb2",
+            Namespace::RingRacers => "ringracers",
+        }
+    }
+
+    /// Looks up `field` of `object` in this namespace.
+    pub fn field(self, object: Object, field: &str) -> Option<Field> {
+        std::iter::once(object.core_fields())
+            .chain(self.tables(object).iter().copied())
+            .flatten()
+            .find(|f| f.name == field)
+            .copied()
+    }
+
+    /// Whether `object` can have `field` in this namespace.
+    ///
+    /// Fields starting with `user_` are custom fields, and always allowed.
+    pub fn allows(self, object: Object, field: &str) -> bool {
+        field.starts_with("user_")
+            || field.starts_with(EDITOR_PREFIX)
+            || self.field(object, field).is_some()
+    }
+
+    /// The tables of the extra fields of `object`.
+    fn tables(self, object: Object) -> &'static [&'static [Field]] {
+        use Object::*;
+
+        match (self, object) {
+            (Namespace::Doom, Thing) => &[BASE_THING],
+            (Namespace::Doom, Vertex) => &[],
+            (Namespace::Doom, LineDef) => &[BASE_LINEDEF],
+            (Namespace::Doom, SideDef) => &[BASE_SIDEDEF],
+            (Namespace::Doom, Sector) => &[BASE_SECTOR],
+            (Namespace::ZDoom, Thing) => &[BASE_THING, ZDOOM_THING],
+            (Namespace::ZDoom, Vertex) => &[VERTEX_HEIGHTS],
+            (Namespace::ZDoom, LineDef) => &[BASE_LINEDEF, ZDOOM_LINEDEF],
+            (Namespace::ZDoom, SideDef) => &[BASE_SIDEDEF, PART_SIDEDEF, ZDOOM_SIDEDEF],
+            (Namespace::ZDoom, Sector) => &[BASE_SECTOR, PLANE_SECTOR, ZDOOM_SECTOR],
+            (Namespace::Srb2, Thing) => &[SRB2_THING],
+            (Namespace::Srb2, Vertex) => &[VERTEX_HEIGHTS],
+            (Namespace::Srb2, LineDef) => &[SRB2_LINEDEF],
+            (Namespace::Srb2, SideDef) => &[BASE_SIDEDEF, PART_SIDEDEF, SRB2_SIDEDEF],
+            (Namespace::Srb2, Sector) => &[BASE_SECTOR, PLANE_SECTOR, SRB2_SECTOR],
+            (Namespace::RingRacers, Thing) => &[SRB2_THING],
+            (Namespace::RingRacers, Vertex) => &[VERTEX_HEIGHTS],
+            (Namespace::RingRacers, LineDef) => &[SRB2_LINEDEF, RINGRACERS_LINEDEF],
+            (Namespace::RingRacers, SideDef) => &[BASE_SIDEDEF, PART_SIDEDEF, SRB2_SIDEDEF],
+            (Namespace::RingRacers, Sector) => {
+                &[BASE_SECTOR, PLANE_SECTOR, SRB2_SECTOR, RINGRACERS_SECTOR]
+            }
+        }
+    }
+}
+
+/// An error setting a field with [`Map::set_field`].
+#[derive(Clone, Debug, PartialEq)]
+pub enum FieldError {
+    /// There is no object at the index.
+    NoObject(Object, usize),
+    /// The field isn't in the namespace of the map.
+    Unknown(Namespace, Object, String),
+    /// The field can't hold a value of the type.
+    WrongType(Field, Type),
+    /// The field can't be unset.
+    Required(&'static str),
+}
+
+impl Display for FieldError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            FieldError::NoObject(object, idx) => {
+                write!(f, "{} {} does not exist", object.name(), idx)
+            }
+            FieldError::Unknown(namespace, object, field) => write!(
+                f,
+                "{} isn't a {} field in the {} namespace",
+                field,
+                object.name().to_lowercase(),
+                namespace
+            ),
+            FieldError::WrongType(field, ty) => write!(
+                f,
+                "{} is a {}, and can't be set to a {}",
+                field.name,
+                field.ty.name(),
+                ty.name()
+            ),
+            FieldError::Required(field) => write!(f, "{} can't be unset", field),
+        }
+    }
+}
+
+impl std::error::Error for FieldError {}
+
+impl Display for Namespace {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl Map {
+    /// The extra fields of the `object` at `idx`.
+    pub fn extras(&self, object: Object, idx: usize) -> Option<&Extras> {
+        match object {
+            Object::Thing => self.things.get(idx).map(|thing| &thing.extras),
+            Object::Vertex => self.vertices.get(idx).map(|vertex| &vertex.extras),
+            Object::LineDef => self.linedefs.get(idx).map(|linedef| &linedef.extras),
+            Object::SideDef => self.sidedefs.get(idx).map(|sidedef| &sidedef.extras),
+            Object::Sector => self.sectors.get(idx).map(|sector| &sector.extras),
+        }
+    }
+
+    /// The extra fields of the `object` at `idx`, mutably.
+    pub fn extras_mut(&mut self, object: Object, idx: usize) -> Option<&mut Extras> {
+        match object {
+            Object::Thing => self.things.get_mut(idx).map(|thing| &mut thing.extras),
+            Object::Vertex => self.vertices.get_mut(idx).map(|vertex| &mut vertex.extras),
+            Object::LineDef => self
+                .linedefs
+                .get_mut(idx)
+                .map(|linedef| &mut linedef.extras),
+            Object::SideDef => self
+                .sidedefs
+                .get_mut(idx)
+                .map(|sidedef| &mut sidedef.extras),
+            Object::Sector => self.sectors.get_mut(idx).map(|sector| &mut sector.extras),
+        }
+    }
+
+    /// The value of `field` of the `object` at `idx`.
+    ///
+    /// Fields of the map structs are read the same way as extras. Returns
+    /// `None` if the field isn't set, like a thing without a height.
+    pub fn field(&self, object: Object, idx: usize, field: &str) -> Option<Value> {
+        let value = match object {
+            Object::Thing => {
+                let thing = self.things.get(idx)?;
+                match field {
+                    "x" => thing.x.into(),
+                    "y" => thing.y.into(),
+                    "height" => thing.height?.into(),
+                    "angle" => thing.angle.into(),
+                    "type" => thing.kind.into(),
+                    _ => thing.extras.get(field)?.clone(),
+                }
+            }
+            Object::Vertex => {
+                let vertex = self.vertices.get(idx)?;
+                match field {
+                    "x" => vertex.x.into(),
+                    "y" => vertex.y.into(),
+                    _ => vertex.extras.get(field)?.clone(),
+                }
+            }
+            Object::LineDef => {
+                let linedef = self.linedefs.get(idx)?;
+                match field {
+                    "v1" => linedef.v1.into(),
+                    "v2" => linedef.v2.into(),
+                    "sidefront" => linedef.side_front.into(),
+                    "sideback" => linedef.side_back?.into(),
+                    "twosided" => linedef.two_sided.into(),
+                    _ => linedef.extras.get(field)?.clone(),
+                }
+            }
+            Object::SideDef => {
+                let sidedef = self.sidedefs.get(idx)?;
+                match field {
+                    "offsetx" => sidedef.offset_x.into(),
+                    "offsety" => sidedef.offset_y.into(),
+                    "sector" => sidedef.sector.into(),
+                    _ => sidedef.extras.get(field)?.clone(),
+                }
+            }
+            Object::Sector => {
+                let sector = self.sectors.get(idx)?;
+                match field {
+                    "heightfloor" => sector.height_floor.into(),
+                    "heightceiling" => sector.height_ceiling.into(),
+                    "texturefloor" => sector.texture_floor.as_str().into(),
+                    "textureceiling" => sector.texture_ceiling.as_str().into(),
+                    _ => sector.extras.get(field)?.clone(),
+                }
+            }
+        };
+
+        Some(value)
+    }
+
+    /// Sets `field` of the `object` at `idx` to `value`.
+    ///
+    /// Values are converted to the type of the field, and nil unsets the
+    /// ones that are optional. Fields not in the map structs are kept in the
+    /// extras, and nil removes them.
+    ///
+    /// If the namespace of the map is known, fields it doesn't have can't be
+    /// set, only removed. Custom fields starting with `user_` can be set to
+    /// anything. Maps in unknown namespaces take any field as it is.
+    pub fn set_field(
+        &mut self,
+        object: Object,
+        idx: usize,
+        field: &str,
+        value: Value,
+    ) -> Result<(), FieldError> {
+        let missing = FieldError::NoObject(object, idx);
+
+        let Some(core) = object.core_fields().iter().find(|f| f.name == field) else {
+            let value = match Namespace::from_name(&self.namespace) {
+                Some(namespace) if value != Value::Nil => {
+                    if !namespace.allows(object, field) {
+                        return Err(FieldError::Unknown(namespace, object, field.to_owned()));
+                    }
+
+                    match namespace.field(object, field) {
+                        Some(expected) => value
+                            .coerce(expected.ty)
+                            .ok_or(FieldError::WrongType(expected, value.value_type()))?,
+                        // custom fields
+                        None => value,
+                    }
+                }
+                _ => value,
+            };
+
+            let extras = self.extras_mut(object, idx).ok_or(missing)?;
+            match value {
+                Value::Nil => {
+                    extras.remove(field);
+                }
+                value => {
+                    extras.insert(field, value);
+                }
+            }
+            return Ok(());
+        };
+
+        let value = match value {
+            Value::Nil => Value::Nil,
+            value => value
+                .coerce(core.ty)
+                .ok_or(FieldError::WrongType(*core, value.value_type()))?,
+        };
+
+        // the value has the type of the field now, so anything that doesn't
+        // match is nil for a field that can't be unset
+        match object {
+            Object::Thing => {
+                let thing = self.things.get_mut(idx).ok_or(missing)?;
+                match (field, value) {
+                    ("x", Value::Float(v)) => thing.x = v,
+                    ("y", Value::Float(v)) => thing.y = v,
+                    ("height", Value::Float(v)) => thing.height = Some(v),
+                    ("height", Value::Nil) => thing.height = None,
+                    ("angle", Value::Integer(v)) => thing.angle = v,
+                    ("type", Value::Integer(v)) => thing.kind = v,
+                    _ => return Err(FieldError::Required(core.name)),
+                }
+            }
+            Object::Vertex => {
+                let vertex = self.vertices.get_mut(idx).ok_or(missing)?;
+                match (field, value) {
+                    ("x", Value::Float(v)) => vertex.x = v,
+                    ("y", Value::Float(v)) => vertex.y = v,
+                    _ => return Err(FieldError::Required(core.name)),
+                }
+            }
+            Object::LineDef => {
+                let linedef = self.linedefs.get_mut(idx).ok_or(missing)?;
+                match (field, value) {
+                    ("v1", Value::Integer(v)) => linedef.v1 = v,
+                    ("v2", Value::Integer(v)) => linedef.v2 = v,
+                    ("sidefront", Value::Integer(v)) => linedef.side_front = v,
+                    ("sideback", Value::Integer(v)) => linedef.side_back = Some(v),
+                    ("sideback", Value::Nil) => linedef.side_back = None,
+                    ("twosided", Value::Boolean(v)) => linedef.two_sided = v,
+                    _ => return Err(FieldError::Required(core.name)),
+                }
+            }
+            Object::SideDef => {
+                let sidedef = self.sidedefs.get_mut(idx).ok_or(missing)?;
+                match (field, value) {
+                    ("offsetx", Value::Integer(v)) => sidedef.offset_x = v,
+                    ("offsety", Value::Integer(v)) => sidedef.offset_y = v,
+                    ("sector", Value::Integer(v)) => sidedef.sector = v,
+                    _ => return Err(FieldError::Required(core.name)),
+                }
+            }
+            Object::Sector => {
+                let sector = self.sectors.get_mut(idx).ok_or(missing)?;
+                match (field, value) {
+                    ("heightfloor", Value::Integer(v)) => sector.height_floor = v,
+                    ("heightceiling", Value::Integer(v)) => sector.height_ceiling = v,
+                    ("texturefloor", Value::String(v)) => sector.texture_floor = v,
+                    ("textureceiling", Value::String(v)) => sector.texture_ceiling = v,
+                    _ => return Err(FieldError::Required(core.name)),
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Finds the extra fields that `namespace` doesn't have.
+    ///
+    /// Returns the objects with each field.
+    pub fn invalid_fields(&self, namespace: Namespace) -> BTreeMap<String, Selection> {
+        let mut invalid = BTreeMap::<String, Selection>::new();
+
+        for (idx, extras) in self.all_extras() {
+            let object = match idx {
+                ObjectIdx::Thing(_) => Object::Thing,
+                ObjectIdx::Vertex(_) => Object::Vertex,
+                ObjectIdx::LineDef(_) => Object::LineDef,
+                ObjectIdx::SideDef(_) => Object::SideDef,
+                ObjectIdx::Sector(_) => Object::Sector,
+            };
+
+            for field in extras.keys() {
+                if !namespace.allows(object, field) {
+                    let (set, idx) = idx.set(invalid.entry(field.to_owned()).or_default());
+                    set.insert(idx);
+                }
+            }
+        }
+
+        invalid
+    }
+}
+
+/// The fields of [`Thing`](super::Thing).
+const CORE_THING: &[Field] = fields! {
+    Float: ["x", "y", "height"],
+    Integer: ["angle", "type"],
+};
+
+/// The fields of [`Vertex`](super::Vertex).
+const CORE_VERTEX: &[Field] = fields! {
+    Float: ["x", "y"],
+};
+
+/// The fields of [`LineDef`](super::LineDef).
+const CORE_LINEDEF: &[Field] = fields! {
+    Integer: ["v1", "v2", "sidefront", "sideback"],
+    Boolean: ["twosided"],
+};
+
+/// The fields of [`SideDef`](super::SideDef).
+const CORE_SIDEDEF: &[Field] = fields! {
+    Integer: ["offsetx", "offsety", "sector"],
+};
+
+/// The fields of [`Sector`](super::Sector).
+const CORE_SECTOR: &[Field] = fields! {
+    Integer: ["heightfloor", "heightceiling"],
+    String: ["texturefloor", "textureceiling"],
+};
+
+const BASE_THING: &[Field] = fields! {
+    Integer: ["id", "special", "arg0", "arg1", "arg2", "arg3", "arg4"],
+    Boolean: [
+        "skill1", "skill2", "skill3", "skill4", "skill5", "ambush", "single", "dm", "coop",
+        "friend", "dormant", "class1", "class2", "class3", "standing", "strifeally", "translucent",
+        "invisible",
+    ],
+    String: ["comment"],
+};
+
+const BASE_LINEDEF: &[Field] = fields! {
+    Integer: ["id", "special", "arg0", "arg1", "arg2", "arg3", "arg4"],
+    Boolean: [
+        "blocking", "blockmonsters", "dontpegtop", "dontpegbottom", "secret", "blocksound",
+        "dontdraw", "mapped", "passuse", "translucent", "jumpover", "blockfloaters", "playercross",
+        "playeruse", "monstercross", "monsteruse", "impact", "playerpush", "monsterpush",
+        "missilecross", "repeatspecial",
+    ],
+    String: ["comment"],
+};
+
+const BASE_SIDEDEF: &[Field] = fields! {
+    String: ["texturetop", "texturebottom", "texturemiddle", "comment"],
+};
+
+const BASE_SECTOR: &[Field] = fields! {
+    Integer: ["lightlevel", "special", "id"],
+    String: ["comment"],
+};
+
+/// Vertex heights, for slopes.
+const VERTEX_HEIGHTS: &[Field] = fields! {
+    Float: ["zfloor", "zceiling"],
+};
+
+/// Offsets and scales of each part of a sidedef, and sidedef lighting.
+const PART_SIDEDEF: &[Field] = fields! {
+    Float: [
+        "offsetx_top", "offsety_top", "offsetx_mid", "offsety_mid", "offsetx_bottom",
+        "offsety_bottom", "scalex_top", "scaley_top", "scalex_mid", "scaley_mid", "scalex_bottom",
+        "scaley_bottom",
+    ],
+    Integer: ["light"],
+    Boolean: ["lightabsolute"],
+};
+
+/// Flat panning, scaling and rotation, plane slopes and plane lighting.
+const PLANE_SECTOR: &[Field] = fields! {
+    Float: [
+        "xpanningfloor", "ypanningfloor", "xpanningceiling", "ypanningceiling", "xscalefloor",
+        "yscalefloor", "xscaleceiling", "yscaleceiling", "rotationfloor", "rotationceiling",
+        "floorplane_a", "floorplane_b", "floorplane_c", "floorplane_d", "ceilingplane_a",
+        "ceilingplane_b", "ceilingplane_c", "ceilingplane_d", "gravity",
+    ],
+    Integer: ["lightfloor", "lightceiling", "lightcolor", "fadecolor"],
+    Boolean: ["lightfloorabsolute", "lightceilingabsolute"],
+    String: ["damagetype", "moreids"],
+};
+
+const ZDOOM_THING: &[Field] = fields! {
+    Boolean: [
+        "skill6", "skill7", "skill8", "skill9", "skill10", "skill11", "skill12", "skill13",
+        "skill14", "skill15", "skill16", "class4", "class5", "class6", "class7", "class8",
+        "class9", "class10", "class11", "class12", "class13", "class14", "class15", "class16",
+        "countsecret",
+    ],
+    Float: ["alpha", "scalex", "scaley", "scale", "health", "gravity"],
+    String: ["renderstyle", "arg0str"],
+    Integer: ["fillcolor", "pitch", "roll", "score", "conversation", "floatbobphase"],
+};
+
+const ZDOOM_LINEDEF: &[Field] = fields! {
+    Float: ["alpha"],
+    String: ["renderstyle", "arg0str", "moreids"],
+    Boolean: [
+        "anycross", "monsteractivate", "blockplayers", "blockeverything", "firstsideonly",
+        "zoneboundary", "clipmidtex", "wrapmidtex", "midtex3d", "midtex3dimpassible",
+        "checkswitchrange", "blockprojectiles", "blockuse", "blocksight", "blockhitscan",
+        "transparent", "revealed", "noskywalls", "drawfullheight",
+    ],
+    Integer: ["locknumber", "automapstyle", "health", "healthgroup"],
+};
+
+const ZDOOM_SIDEDEF: &[Field] = fields! {
+    Boolean: [
+        "lightfog", "nofakecontrast", "smoothlighting", "clipmidtex", "wrapmidtex", "nodecals",
+    ],
+};
+
+const ZDOOM_SECTOR: &[Field] = fields! {
+    Float: ["alphafloor", "alphaceiling", "desaturation"],
+    String: [
+        "renderstylefloor", "renderstyleceiling", "floorterrain", "ceilingterrain",
+        "soundsequence",
+    ],
+    Boolean: [
+        "silent", "nofallingdamage", "noattack", "dropactors", "norespawn", "hidden", "waterzone",
+        "damageterraineffect", "damagehazard",
+    ],
+    Integer: ["damageamount", "damageinterval", "leakiness"],
+};
+
+const SRB2_THING: &[Field] = fields! {
+    Integer: [
+        "id", "pitch", "roll", "arg0", "arg1", "arg2", "arg3", "arg4", "arg5", "arg6", "arg7",
+        "arg8", "arg9",
+    ],
+    Float: ["scalex", "scaley", "scale", "mobjscale"],
+    Boolean: ["flip"],
+    String: ["stringarg0", "stringarg1", "comment"],
+};
+
+const SRB2_LINEDEF: &[Field] = fields! {
+    Integer: [
+        "id", "special", "arg0", "arg1", "arg2", "arg3", "arg4", "arg5", "arg6", "arg7", "arg8",
+        "arg9", "executordelay",
+    ],
+    String: ["moreids", "stringarg0", "stringarg1", "renderstyle", "comment"],
+    Boolean: [
+        "blocking", "blockmonsters", "dontpegtop", "dontpegbottom", "skewtd", "noclimb", "noskew",
+        "midpeg", "midsolid", "wrapmidtex", "nonet", "netonly", "notbouncy", "transfer",
+    ],
+    Float: ["alpha"],
+};
+
+const SRB2_SIDEDEF: &[Field] = fields! {
+    Integer: ["repeatcnt"],
+};
+
+const SRB2_SECTOR: &[Field] = fields! {
+    Integer: [
+        "lightalpha", "fadealpha", "fadestart", "fadeend", "triggertag", "triggerer", "action",
+        "arg0", "arg1", "arg2", "arg3", "arg4", "arg5", "arg6", "arg7", "arg8", "arg9",
+    ],
+    Boolean: [
+        "colormapfog", "colormapfadesprites", "colormapprotected", "flipspecial_nofloor",
+        "flipspecial_ceiling", "triggerspecial_touch", "triggerspecial_headbump",
+        "triggerline_plane", "triggerline_mobj", "invertprecip", "gravityflip", "heatwave",
+        "noclipcamera", "outerspace", "doublestepup", "nostepdown", "speedpad",
+        "starpostactivator", "exit", "specialstagepit", "returnflag", "redteambase",
+        "blueteambase", "fan", "supertransform", "forcespin", "zoomtubestart", "zoomtubeend",
+        "finishline", "ropehang", "jumpflip", "gravityoverride",
+    ],
+    Float: ["friction"],
+    String: ["stringarg0", "stringarg1"],
+};
+
+const RINGRACERS_LINEDEF: &[Field] = fields! {
+    Boolean: ["tripwire", "blockplayers"],
+};
+
+const RINGRACERS_SECTOR: &[Field] = fields! {
+    Boolean: [
+        "invertencore", "flatlighting", "forcedirectionallighting", "nostepup", "sneakerpanel",
+        "destroyobjects", "stairjank",
+    ],
+};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn invalid_fields() {
+        // zdoom maps don't need angles
+        let map = Map::from_str(
+            r#"
+            namespace = "ZDoom";
+            version = 1;
+
+            thing { x = 0.0; y = 0.0; type = 1; alpha = 0.5; skill6 = true; }
+            thing { x = 0.0; y = 0.0; type = 1; alpha = 0.5; user_color = 3; }
+            vertex { x = 0.0; y = 0.0; zfloor = 16.0; }
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(Namespace::from_name(&map.namespace), Some(Namespace::ZDoom));
+        assert!(map.invalid_fields(Namespace::ZDoom).is_empty());
+
+        let invalid = map.invalid_fields(Namespace::Srb2);
+        assert_eq!(invalid.keys().collect::<Vec<_>>(), ["alpha", "skill6"]);
+        assert_eq!(invalid["alpha"].things, [0, 1].into());
+
+        let invalid = map.invalid_fields(Namespace::Doom);
+        assert!(invalid.contains_key("zfloor"));
+    }
+
+    #[test]
+    fn fields() {
+        let mut map = Map::from_str(
+            r#"
+            namespace = "srb2";
+            version = 1;
+
+            thing { x = 0.0; y = 0.0; type = 1; flip = true; }
+            "#,
+        )
+        .unwrap();
+
+        let field = Namespace::Srb2.field(Object::Thing, "x").unwrap();
+        assert_eq!(field.ty, Type::Float);
+        assert!(field.fits(&Value::Integer(3)));
+        assert_eq!(
+            Namespace::Srb2.field(Object::Thing, "flip").map(|f| f.ty),
+            Some(Type::Boolean)
+        );
+        assert_eq!(Namespace::Doom.field(Object::Thing, "flip"), None);
+
+        assert_eq!(
+            map.field(Object::Thing, 0, "flip"),
+            Some(Value::Boolean(true))
+        );
+        assert_eq!(map.field(Object::Thing, 0, "height"), None);
+
+        map.set_field(Object::Thing, 0, "x", Value::Integer(32))
+            .unwrap();
+        map.set_field(Object::Thing, 0, "flip", Value::Nil).unwrap();
+        assert_eq!(map.things[0].x, 32.0);
+        assert!(map.things[0].extras.is_empty());
+
+        assert!(map
+            .set_field(Object::Thing, 0, "type", "spring".into())
+            .is_err());
+        assert_eq!(
+            map.set_field(Object::Thing, 0, "type", Value::Nil),
+            Err(FieldError::Required("type"))
+        );
+
+        // extras are checked against the namespace too
+        assert_eq!(
+            map.set_field(Object::Thing, 0, "alpha", Value::Float(0.5)),
+            Err(FieldError::Unknown(
+                Namespace::Srb2,
+                Object::Thing,
+                "alpha".to_owned()
+            ))
+        );
+        assert!(map
+            .set_field(Object::Thing, 0, "flip", "upside down".into())
+            .is_err());
+        map.set_field(Object::Thing, 0, "flip", Value::Integer(1))
+            .unwrap();
+        map.set_field(Object::Thing, 0, "user_note", "hi".into())
+            .unwrap();
+        assert_eq!(map.things[0].extras["flip"], Value::Boolean(true));
+        assert_eq!(map.things[0].extras["user_note"], "hi".into());
+    }
+}
@@ -0,0 +1,192 @@
+//! Round trips that keep the original text of unchanged objects.
+//!
+//! Big maps are usually kept in version control, and rewriting every object
+//! on save makes for unreadable diffs. Reading a map with
+//! [`Map::from_str_preserving`] remembers the text of every top level value,
+//! so writing it back only reformats what actually changed.
+
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::sync::Arc;
+
+use serde::de::Error as _;
+use serde::Serialize;
+
+use super::{preprocess, Map};
+use crate::format::udmf;
+
+/// The original text of a map.
+#[derive(Debug)]
+pub struct Source {
+    /// Anything before the first key, usually comments.
+    preamble: String,
+    /// The top level keys, in the order they first appeared.
+    order: Vec<String>,
+    /// The original text of each value, by how the value is written now.
+    raw: HashMap<String, VecDeque<String>>,
+}
+
+impl Map {
+    /// Reads a map from a string, remembering the original text.
+    ///
+    /// When the map is written, values that weren't changed are written back
+    /// exactly as they were read, comments and all.
+    pub fn from_str_preserving(str: &str) -> Result<Map, udmf::de::Error> {
+        let input = preprocess(str);
+        // preprocessing keeps everything where it was, so offsets into the
+        // input are offsets into the original
+        let mut starts = Vec::new();
+        let mut map = Map::parse(&input, Some(&mut starts), None)?;
+
+        let mut values = map.values().map_err(udmf::de::Error::custom)?;
+        let mut seen = HashMap::<&str, usize>::new();
+        let mut source = Source {
+            preamble: str[..starts.first().copied().unwrap_or(str.len())].to_string(),
+            order: Vec::new(),
+            raw: HashMap::new(),
+        };
+
+        for (idx, &start) in starts.iter().enumerate() {
+            // each value takes everything up to the next key
+            let end = starts.get(idx + 1).copied().unwrap_or(str.len());
+            let key = str[start..]
+                .split(|ch: char| !(ch.is_ascii_alphanumeric() || ch == '_'))
+                .next()
+                .unwrap_or_default();
+
+            let nth = seen.entry(key).or_default();
+            if *nth == 0 {
+                source.order.push(key.to_string());
+            }
+
+            // each written value is only looked up once, so it can be taken
+            let written = values
+                .iter_mut()
+                .find(|(k, _)| *k == key)
+                .and_then(|(_, written)| written.get_mut(*nth))
+                .map(std::mem::take);
+            *nth += 1;
+
+            if let Some(written) = written {
+                source
+                    .raw
+                    .entry(written)
+                    .or_default()
+                    .push_back(str[start..end].to_string());
+            }
+        }
+
+        map.source = Some(Arc::new(source));
+        Ok(map)
+    }
+
+    /// Writes the map, using the original text for unchanged values.
+    pub(super) fn write_preserving(
+        &self,
+        source: &Source,
+        out: &mut impl fmt::Write,
+    ) -> Result<(), udmf::ser::Error> {
+        let mut raw = source.raw.clone();
+        let mut values = self.values()?;
+
+        // keep keys in the order they were read, new keys go last
+        values.sort_by_key(|(key, _)| {
+            source
+                .order
+                .iter()
+                .position(|k| k == key)
+                .unwrap_or(usize::MAX)
+        });
+
+        out.write_str(&source.preamble)?;
+
+        for written in values.into_iter().flat_map(|(_, written)| written) {
+            match raw.get_mut(&written).and_then(VecDeque::pop_front) {
+                Some(original) => out.write_str(&original)?,
+                None => out.write_str(&written)?,
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Every top level value as it would be written, grouped by key.
+    fn values(&self) -> Result<Vec<(&str, Vec<String>)>, udmf::ser::Error> {
+        let mut values = vec![
+            (
+                "namespace",
+                vec![write_value("namespace", &self.namespace)?],
+            ),
+            ("version", vec![write_value("version", &self.version)?]),
+        ];
+
+        for (key, value) in self.extras.iter() {
+            values.push((key, vec![write_value(key, value)?]));
+        }
+
+        values.push(("thing", write_values("thing", &self.things)?));
+        values.push(("vertex", write_values("vertex", &self.vertices)?));
+        values.push(("linedef", write_values("linedef", &self.linedefs)?));
+        values.push(("sidedef", write_values("sidedef", &self.sidedefs)?));
+        values.push(("sector", write_values("sector", &self.sectors)?));
+
+        Ok(values)
+    }
+}
+
+fn write_value<T>(key: &str, value: &T) -> Result<String, udmf::ser::Error>
+where
+    T: Serialize + ?Sized,
+{
+    let mut writer = udmf::ser::Writer::new(String::new());
+    writer.write_value(key, value)?;
+    Ok(writer.into_inner())
+}
+
+fn write_values<T>(key: &str, values: &[T]) -> Result<Vec<String>, udmf::ser::Error>
+where
+    T: Serialize,
+{
+    values.iter().map(|value| write_value(key, value)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_unchanged_objects() {
+        let input = r#"// made by hand
+namespace = "ringracers";
+version = 1;
+
+thing // 0
+{
+  type = 1; x = 0.0; y = 0.0; angle = 0;
+}
+
+thing // 1
+{
+  type = 1; x = 64.0; y = 0.0; angle = 90;
+}
+
+vertex { y = 0.0; x = 0.0; }
+"#;
+        let mut map = Map::from_str_preserving(input).unwrap();
+
+        // nothing changed
+        assert_eq!(map.to_string(), input);
+
+        map.things[0].angle = 180;
+        let output = map.to_string();
+
+        assert!(output.starts_with("// made by hand\nnamespace"));
+        assert!(output.contains("angle = 180;"));
+        assert!(output.contains("thing // 1\n{\n  type = 1; x = 64.0; y = 0.0; angle = 90;\n}"));
+        assert!(output.ends_with("vertex { y = 0.0; x = 0.0; }\n"));
+
+        let reread = Map::from_str(&output).unwrap();
+        assert_eq!(reread.things[0].angle, 180);
+        assert_eq!(reread.things[1].angle, 90);
+    }
+}
@@ -0,0 +1,493 @@
+//! Geometry queries.
+//!
+//! The queries on [`Map`] look through every linedef. The editor asks them
+//! of a [`LineIndex`] instead, which only looks at the linedefs near the
+//! point.
+
+use std::collections::{BTreeSet, HashSet};
+use std::ops::Range;
+
+use super::{LineDef, Map};
+
+/// A point, in map units.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Point {
+    pub x: f32,
+    pub y: f32,
+}
+
+impl Point {
+    pub fn new(x: f32, y: f32) -> Point {
+        Point { x, y }
+    }
+
+    /// The distance between two points.
+    pub fn distance(self, other: Point) -> f32 {
+        (self.x - other.x).hypot(self.y - other.y)
+    }
+}
+
+/// A line segment, in map units.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Segment {
+    pub a: Point,
+    pub b: Point,
+}
+
+impl Segment {
+    pub fn new(a: Point, b: Point) -> Segment {
+        Segment { a, b }
+    }
+
+    /// The distance from `point` to the closest point on the segment.
+    pub fn distance_to(&self, point: Point) -> f32 {
+        let (dx, dy) = (self.b.x - self.a.x, self.b.y - self.a.y);
+        let length_squared = dx * dx + dy * dy;
+
+        let t = if length_squared > 0.0 {
+            (((point.x - self.a.x) * dx + (point.y - self.a.y) * dy) / length_squared)
+                .clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+
+        point.distance(Point::new(self.a.x + dx * t, self.a.y + dy * t))
+    }
+
+    /// Where two segments cross, as a fraction of the way along `self`.
+    pub fn intersect(&self, other: &Segment) -> Option<f32> {
+        let (rx, ry) = (self.b.x - self.a.x, self.b.y - self.a.y);
+        let (sx, sy) = (other.b.x - other.a.x, other.b.y - other.a.y);
+
+        let denom = rx * sy - ry * sx;
+        if denom == 0.0 {
+            // parallel
+            return None;
+        }
+
+        let (qx, qy) = (other.a.x - self.a.x, other.a.y - self.a.y);
+        let t = (qx * sy - qy * sx) / denom;
+        let u = (qx * ry - qy * rx) / denom;
+
+        ((0.0..=1.0).contains(&t) && (0.0..=1.0).contains(&u)).then_some(t)
+    }
+}
+
+impl Map {
+    /// The segment a linedef runs along.
+    ///
+    /// Returns `None` if the linedef has invalid vertices.
+    pub fn linedef_segment(&self, linedef: &LineDef) -> Option<Segment> {
+        let v1 = self.vertices.get(linedef.v1 as usize)?;
+        let v2 = self.vertices.get(linedef.v2 as usize)?;
+
+        Some(Segment::new(Point::new(v1.x, v1.y), Point::new(v2.x, v2.y)))
+    }
+
+    /// Finds the sector `(x, y)` is in.
+    ///
+    /// Returns `None` if the point is in the void.
+    pub fn sector_at(&self, x: f32, y: f32) -> Option<usize> {
+        // cast a ray to the right, and see which side of the first line it
+        // hits the point is on
+        let closest = (0..self.linedefs.len())
+            .filter_map(|idx| Some((self.ray_hit(idx, x, y)?, idx)))
+            .min_by(|a, b| a.0.total_cmp(&b.0).then(a.1.cmp(&b.1)))?;
+
+        self.sector_facing(closest.1, Point::new(x, y))
+    }
+
+    /// How far to the right of `(x, y)` a ray hits `linedef`.
+    fn ray_hit(&self, linedef: usize, x: f32, y: f32) -> Option<f32> {
+        let Segment { a, b } = self.linedef_segment(self.linedefs.get(linedef)?)?;
+
+        // half open, so rays through vertices only hit one line
+        if (a.y <= y) == (b.y <= y) {
+            return None;
+        }
+
+        let hit_x = a.x + (y - a.y) / (b.y - a.y) * (b.x - a.x);
+        let distance = hit_x - x;
+        (distance >= 0.0).then_some(distance)
+    }
+
+    /// The sector on the side of `linedef` facing `point`.
+    fn sector_facing(&self, linedef: usize, point: Point) -> Option<usize> {
+        let side = self.side_facing(&self.linedefs[linedef], point)?;
+
+        self.sidedefs
+            .get(side)
+            .map(|sidedef| sidedef.sector as usize)
+    }
+
+    /// The sidedef of `linedef` on the same side as `point`.
+    ///
+    /// Returns `None` if there is no sidedef on that side.
+    pub fn side_facing(&self, linedef: &LineDef, point: Point) -> Option<usize> {
+        let Segment { a, b } = self.linedef_segment(linedef)?;
+
+        // the front side is on the right
+        let cross = (b.x - a.x) * (point.y - a.y) - (b.y - a.y) * (point.x - a.x);
+        if cross <= 0.0 {
+            Some(linedef.side_front as usize)
+        } else {
+            linedef.side_back.map(|side| side as usize)
+        }
+    }
+
+    /// Finds every linedef crossing `segment`.
+    ///
+    /// The linedefs are ordered by how far along `segment` they cross it.
+    pub fn linedefs_crossing(&self, segment: Segment) -> Vec<usize> {
+        let mut crossing = self
+            .linedefs
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, linedef)| {
+                let t = segment.intersect(&self.linedef_segment(linedef)?)?;
+                Some((t, idx))
+            })
+            .collect::<Vec<_>>();
+
+        crossing.sort_by(|(a, _), (b, _)| a.total_cmp(b));
+        crossing.into_iter().map(|(_, idx)| idx).collect()
+    }
+
+    /// Finds the linedef closest to `point`.
+    ///
+    /// Returns the index of the linedef and how far away it is.
+    pub fn nearest_linedef(&self, point: Point) -> Option<(usize, f32)> {
+        self.nearest_of(0..self.linedefs.len(), point)
+    }
+
+    /// Finds the linedef of `linedefs` closest to `point`.
+    ///
+    /// Ties go to the lowest index, so the order of `linedefs` doesn't matter.
+    fn nearest_of(
+        &self,
+        linedefs: impl Iterator<Item = usize>,
+        point: Point,
+    ) -> Option<(usize, f32)> {
+        linedefs
+            .filter_map(|idx| {
+                let segment = self.linedef_segment(self.linedefs.get(idx)?)?;
+                Some((idx, segment.distance_to(point)))
+            })
+            .min_by(|a, b| a.1.total_cmp(&b.1).then(a.0.cmp(&b.0)))
+    }
+
+    /// The linedefs of the map, snapped to a grid.
+    ///
+    /// The grid has `cells` cells along the longest side of the map. Lines
+    /// that collapse into the same cells are only kept once, so drawing them
+    /// stays cheap on big maps. Linedefs in `skip` are left out.
+    pub fn simplified_linedefs(&self, cells: f32, skip: &BTreeSet<usize>) -> Vec<Segment> {
+        let mut vertices = self.vertices.iter();
+        let Some(first) = vertices.next() else {
+            return Vec::new();
+        };
+        let (min_x, min_y, max_x, max_y) = vertices.fold(
+            (first.x, first.y, first.x, first.y),
+            |(min_x, min_y, max_x, max_y), v| {
+                (
+                    min_x.min(v.x),
+                    min_y.min(v.y),
+                    max_x.max(v.x),
+                    max_y.max(v.y),
+                )
+            },
+        );
+
+        // avoid dividing by zero on degenerate maps
+        let cell = ((max_x - min_x).max(max_y - min_y) + 2.0) / cells;
+        let snap = |p: Point| {
+            (
+                ((p.x - min_x) / cell).round() as i32,
+                ((p.y - min_y) / cell).round() as i32,
+            )
+        };
+        let unsnap =
+            |(x, y): (i32, i32)| Point::new(min_x + x as f32 * cell, min_y + y as f32 * cell);
+
+        let mut seen = HashSet::new();
+
+        self.linedefs
+            .iter()
+            .enumerate()
+            .filter(|(idx, _)| !skip.contains(idx))
+            .filter_map(|(_, linedef)| self.linedef_segment(linedef))
+            .filter_map(|segment| {
+                let (a, b) = (snap(segment.a), snap(segment.b));
+
+                if a == b || !seen.insert((a.min(b), a.max(b))) {
+                    None
+                } else {
+                    Some(Segment::new(unsnap(a), unsnap(b)))
+                }
+            })
+            .collect()
+    }
+}
+
+/// The most cells along either side of a [`LineIndex`].
+const MAX_CELLS: f32 = 256.0;
+
+/// The smallest [`LineIndex`] cells, in map units.
+const MIN_CELL: f32 = 128.0;
+
+/// A grid of the linedefs of a map.
+///
+/// Each cell lists the linedefs whose bounds touch it, so queries only look
+/// at the linedefs near the point. The index has to be rebuilt with
+/// [`LineIndex::new`] when the map changes.
+#[derive(Clone, Debug, Default)]
+pub struct LineIndex {
+    /// The corner of the first cell.
+    origin: Point,
+    /// The size of the cells.
+    cell: f32,
+    width: usize,
+    height: usize,
+    /// The linedefs touching each cell, row by row.
+    cells: Vec<Vec<usize>>,
+}
+
+impl LineIndex {
+    /// Indexes the linedefs of `map`.
+    pub fn new(map: &Map) -> LineIndex {
+        let segments = map
+            .linedefs
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, linedef)| Some((idx, map.linedef_segment(linedef)?)))
+            .collect::<Vec<_>>();
+
+        let Some((min, max)) = segments
+            .iter()
+            .flat_map(|(_, segment)| [segment.a, segment.b])
+            .map(|p| (p, p))
+            .reduce(|(min, max), (p, _)| {
+                (
+                    Point::new(min.x.min(p.x), min.y.min(p.y)),
+                    Point::new(max.x.max(p.x), max.y.max(p.y)),
+                )
+            })
+        else {
+            return LineIndex::default();
+        };
+
+        let cell = ((max.x - min.x).max(max.y - min.y) / MAX_CELLS).max(MIN_CELL);
+        let mut index = LineIndex {
+            origin: min,
+            cell,
+            width: ((max.x - min.x) / cell) as usize + 1,
+            height: ((max.y - min.y) / cell) as usize + 1,
+            cells: Vec::new(),
+        };
+        index.cells = vec![Vec::new(); index.width * index.height];
+
+        for (idx, segment) in segments {
+            let (xs, ys) = index.span(segment.a, segment.b);
+            for y in ys {
+                for x in xs.clone() {
+                    index.cells[y * index.width + x].push(idx);
+                }
+            }
+        }
+
+        index
+    }
+
+    /// The linedefs in the cells within `distance` of `point`.
+    ///
+    /// This can include linedefs further away, and the same linedef more than
+    /// once.
+    pub fn near(&self, point: Point, distance: f32) -> impl Iterator<Item = usize> + '_ {
+        let (xs, ys) = self.span(
+            Point::new(point.x - distance, point.y - distance),
+            Point::new(point.x + distance, point.y + distance),
+        );
+
+        ys.flat_map(move |y| {
+            self.cells[y * self.width..][xs.clone()]
+                .iter()
+                .flatten()
+                .copied()
+        })
+    }
+
+    /// Finds the linedef of `map` closest to `point`, like
+    /// [`Map::nearest_linedef`].
+    pub fn nearest_linedef(&self, map: &Map, point: Point) -> Option<(usize, f32)> {
+        if self.cells.is_empty() {
+            return None;
+        }
+
+        // widen the search until it finds a line within it, or covers
+        // everything
+        let mut distance = self.cell;
+        loop {
+            let nearest = map.nearest_of(self.near(point, distance), point);
+            let covered = point.x - distance <= self.origin.x
+                && point.y - distance <= self.origin.y
+                && point.x + distance >= self.origin.x + self.width as f32 * self.cell
+                && point.y + distance >= self.origin.y + self.height as f32 * self.cell;
+
+            match nearest {
+                Some((_, d)) if d <= distance => return nearest,
+                _ if covered => return nearest,
+                _ => distance *= 2.0,
+            }
+        }
+    }
+
+    /// Finds the sector of `map` that `(x, y)` is in, like
+    /// [`Map::sector_at`].
+    pub fn sector_at(&self, map: &Map, x: f32, y: f32) -> Option<usize> {
+        let (xs, ys) = self.span(Point::new(x, y), Point::new(f32::INFINITY, y));
+        let row = ys.into_iter().next()?;
+
+        // walk the cells along the ray, until one has a hit inside of it
+        let mut closest: Option<(f32, usize)> = None;
+        for column in xs {
+            for &idx in self.cells[row * self.width + column].iter() {
+                let Some(distance) = map.ray_hit(idx, x, y) else {
+                    continue;
+                };
+
+                if closest.is_none_or(|c| (distance, idx) < c) {
+                    closest = Some((distance, idx));
+                }
+            }
+
+            let edge = self.origin.x + (column + 1) as f32 * self.cell;
+            if closest.is_some_and(|(distance, _)| x + distance <= edge) {
+                break;
+            }
+        }
+
+        map.sector_facing(closest?.1, Point::new(x, y))
+    }
+
+    /// The columns and rows of the cells between `min` and `max`.
+    fn span(&self, min: Point, max: Point) -> (Range<usize>, Range<usize>) {
+        let axis = |min: f32, max: f32, origin: f32, len: usize| {
+            let first = ((min - origin) / self.cell).floor().max(0.0);
+            let last = ((max - origin) / self.cell).floor().min(len as f32 - 1.0);
+
+            if first <= last {
+                first as usize..last as usize + 1
+            } else {
+                // outside of the grid
+                0..0
+            }
+        };
+
+        (
+            axis(
+                min.x.min(max.x),
+                min.x.max(max.x),
+                self.origin.x,
+                self.width,
+            ),
+            axis(
+                min.y.min(max.y),
+                min.y.max(max.y),
+                self.origin.y,
+                self.height,
+            ),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // a square room with a smaller square inside of it
+    const ROOMS: &str = r#"
+    namespace = "ringracers";
+    version = 1;
+
+    vertex { x = 0.0; y = 0.0; }
+    vertex { x = 0.0; y = 256.0; }
+    vertex { x = 256.0; y = 256.0; }
+    vertex { x = 256.0; y = 0.0; }
+    vertex { x = 64.0; y = 64.0; }
+    vertex { x = 64.0; y = 128.0; }
+    vertex { x = 128.0; y = 128.0; }
+    vertex { x = 128.0; y = 64.0; }
+
+    linedef { v1 = 0; v2 = 1; sidefront = 0; }
+    linedef { v1 = 1; v2 = 2; sidefront = 0; }
+    linedef { v1 = 2; v2 = 3; sidefront = 0; }
+    linedef { v1 = 3; v2 = 0; sidefront = 0; }
+    linedef { v1 = 4; v2 = 5; sidefront = 1; sideback = 2; twosided = true; }
+    linedef { v1 = 5; v2 = 6; sidefront = 1; sideback = 2; twosided = true; }
+    linedef { v1 = 6; v2 = 7; sidefront = 1; sideback = 2; twosided = true; }
+    linedef { v1 = 7; v2 = 4; sidefront = 1; sideback = 2; twosided = true; }
+
+    sidedef { sector = 0; }
+    sidedef { sector = 1; }
+    sidedef { sector = 0; }
+
+    sector { texturefloor = "FLOOR"; textureceiling = "CEIL"; }
+    sector { texturefloor = "FLOOR"; textureceiling = "CEIL"; }
+    "#;
+
+    #[test]
+    fn sector_at() {
+        let map = Map::from_str(ROOMS).unwrap();
+
+        assert_eq!(map.sector_at(32.0, 32.0), Some(0));
+        assert_eq!(map.sector_at(96.0, 96.0), Some(1));
+        // in line with vertices
+        assert_eq!(map.sector_at(32.0, 128.0), Some(0));
+        assert_eq!(map.sector_at(-32.0, 32.0), None);
+        assert_eq!(map.sector_at(512.0, 32.0), None);
+    }
+
+    #[test]
+    fn linedefs_crossing() {
+        let map = Map::from_str(ROOMS).unwrap();
+
+        let segment = Segment::new(Point::new(-32.0, 96.0), Point::new(96.0, 96.0));
+        assert_eq!(map.linedefs_crossing(segment), vec![0, 4]);
+
+        let reversed = Segment::new(segment.b, segment.a);
+        assert_eq!(map.linedefs_crossing(reversed), vec![4, 0]);
+    }
+
+    #[test]
+    fn nearest_linedef() {
+        let map = Map::from_str(ROOMS).unwrap();
+
+        assert_eq!(
+            map.nearest_linedef(Point::new(96.0, 140.0)),
+            Some((5, 12.0))
+        );
+    }
+
+    #[test]
+    fn line_index() {
+        let map = Map::from_str(ROOMS).unwrap();
+        let index = LineIndex::new(&map);
+
+        for x in (-64..=320).step_by(16) {
+            for y in (-64..=320).step_by(16) {
+                let (x, y) = (x as f32 + 0.5, y as f32);
+                let point = Point::new(x, y);
+
+                assert_eq!(index.sector_at(&map, x, y), map.sector_at(x, y));
+                assert_eq!(
+                    index.nearest_linedef(&map, point),
+                    map.nearest_linedef(point)
+                );
+            }
+        }
+
+        let far = Point::new(-5000.0, 12000.0);
+        assert_eq!(index.nearest_linedef(&map, far), map.nearest_linedef(far));
+        assert_eq!(LineIndex::default().sector_at(&map, 32.0, 32.0), None);
+    }
+}
@@ -0,0 +1,125 @@
+//! Reading damaged maps.
+//!
+//! Community maps are sometimes edited by hand or by broken tools, and one
+//! bad block shouldn't keep the rest of the map from opening.
+//! [`Map::from_str_lenient`] skips values it can't read and reports them, so
+//! the map can be repaired in the editor.
+
+use std::fmt::{self, Display, Formatter};
+use std::ops::Range;
+
+use super::{preprocess, Map};
+use crate::format::udmf;
+
+/// The namespace of leniently read maps that are missing one.
+pub(super) const DEFAULT_NAMESPACE: &str = "ringracers";
+
+/// The version of leniently read maps that are missing one.
+pub(super) const DEFAULT_VERSION: i32 = 1;
+
+/// A value that failed to read.
+#[derive(Debug)]
+pub struct Diagnostic {
+    /// The text that was skipped, as byte offsets of the input.
+    ///
+    /// This is empty for missing fields.
+    pub span: Range<usize>,
+    /// The line the text starts on, from one, or zero for missing fields.
+    pub line: usize,
+    pub error: udmf::de::Error,
+}
+
+impl Diagnostic {
+    pub(super) fn new(span: Range<usize>, error: udmf::de::Error) -> Diagnostic {
+        Diagnostic {
+            span,
+            line: 0,
+            error,
+        }
+    }
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if self.span.is_empty() {
+            Display::fmt(&self.error, f)
+        } else {
+            write!(f, "line {}: {}", self.line, self.error)
+        }
+    }
+}
+
+impl Map {
+    /// Reads a map from a string, skipping anything that can't be read.
+    ///
+    /// Values that fail to read are left out of the map, and the reading
+    /// picks up again at the next top level key. A missing namespace or
+    /// version is filled in. Everything that went wrong is returned with the
+    /// map, in the order it was found.
+    pub fn from_str_lenient(str: &str) -> (Map, Vec<Diagnostic>) {
+        let input = preprocess(str);
+        let mut diagnostics = Vec::new();
+        let map = Map::parse(&input, None, Some(&mut diagnostics))
+            .expect("lenient parsing does not fail");
+
+        // preprocessing keeps everything where it was, so spans only need
+        // their line found
+        let lines = line_starts(str);
+        let locate = |offset: usize| {
+            let line = lines.partition_point(|&start| start <= offset) - 1;
+            (line, offset.min(str.len()))
+        };
+
+        for diagnostic in diagnostics.iter_mut().filter(|d| !d.span.is_empty()) {
+            let (line, start) = locate(diagnostic.span.start);
+            let (_, end) = locate(diagnostic.span.end);
+            diagnostic.span = start..end;
+            diagnostic.line = line + 1;
+        }
+
+        (map, diagnostics)
+    }
+}
+
+fn line_starts(s: &str) -> Vec<usize> {
+    std::iter::once(0)
+        .chain(s.match_indices('\n').map(|(idx, _)| idx + 1))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn skips_bad_blocks() {
+        let input = r#"version = 1;
+
+thing { x = 0.0; y = 0.0; angle = 0; type = 1; }
+thing { x = 0.0; y = ; angle = 0; type = 1; } // broken
+vertex { x = 0.0; y = 0.0;
+vertex { x = 64.0; y = 0.0; }
+foo = "bar"
+sector { heightfloor = 0; heightceiling = 128; texturefloor = "A"; textureceiling = "B"; }
+"#;
+        assert!(Map::from_str(input).is_err());
+
+        let (map, diagnostics) = Map::from_str_lenient(input);
+
+        assert_eq!(map.namespace, DEFAULT_NAMESPACE);
+        assert_eq!(map.version, 1);
+        assert_eq!(map.things.len(), 1);
+        assert_eq!(map.vertices.len(), 1);
+        assert_eq!(map.vertices[0].x, 64.0);
+        assert_eq!(map.sectors.len(), 1);
+
+        let lines = diagnostics.iter().map(|d| d.line).collect::<Vec<_>>();
+        assert_eq!(lines, [4, 5, 7, 0]);
+        assert_eq!(
+            &input[diagnostics[0].span.clone()],
+            "thing { x = 0.0; y = ; angle = 0; type = 1; }"
+        );
+        assert_eq!(&input[diagnostics[2].span.clone()], "foo = \"bar\"\n");
+        assert!(diagnostics[3].span.is_empty());
+    }
+}
@@ -0,0 +1,546 @@
+//! Operations that take objects out of the map.
+//!
+//! Deleting linedefs, merging sectors and welding vertices all remove
+//! objects, shifting the indices of everything after them. These are easy to
+//! get wrong by accident, so each can be previewed with
+//! [`Destructive::preview`] before it is applied.
+
+use std::collections::BTreeSet;
+use std::fmt::{self, Display, Formatter};
+
+use super::{Map, Selection};
+
+/// An operation that removes objects.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Destructive {
+    /// Deletes the selected linedefs, and anything only they used.
+    DeleteLineDefs,
+    /// Merges the selected sectors into the first one, removing the linedefs
+    /// between them.
+    MergeSectors,
+    /// Welds the selected vertices onto the one with the lowest index,
+    /// removing linedefs that shrink to a point.
+    WeldVertices,
+}
+
+impl Destructive {
+    /// Every operation.
+    pub const ALL: [Destructive; 3] = [
+        Destructive::DeleteLineDefs,
+        Destructive::MergeSectors,
+        Destructive::WeldVertices,
+    ];
+
+    /// The name of the operation, as shown in the UI.
+    pub fn name(self) -> &'static str {
+        match self {
+            Destructive::DeleteLineDefs => "Delete linedefs",
+            Destructive::MergeSectors => "Merge sectors",
+            Destructive::WeldVertices => "Weld vertices",
+        }
+    }
+
+    /// Applies the operation to the selection.
+    ///
+    /// Removed objects are deselected, and the rest of the selection is
+    /// moved to the new indices.
+    pub fn apply(self, map: &mut Map, selection: &mut Selection) -> Impact {
+        let impact = self.preview(map, selection);
+
+        if let Some(kept) = impact.merged_into {
+            for &idx in impact.changed.sidedefs.iter() {
+                map.sidedefs[idx].sector = kept as i32;
+            }
+        }
+
+        for &idx in impact.changed.linedefs.iter() {
+            let linedef = &mut map.linedefs[idx];
+            linedef.v1 = impact.vertex(linedef.v1);
+            linedef.v2 = impact.vertex(linedef.v2);
+        }
+
+        map.remove_objects(&impact.removed);
+
+        *selection = shift_selection(selection, &impact.removed);
+        impact
+    }
+
+    /// Shows what the operation would do, without changing or copying `map`.
+    pub fn preview(self, map: &Map, selection: &Selection) -> Impact {
+        let Some(targets) = self.targets(map, selection) else {
+            return Impact::default();
+        };
+
+        match self {
+            Destructive::DeleteLineDefs => map.delete_linedefs(targets),
+            Destructive::MergeSectors => map.merge_sectors(targets),
+            Destructive::WeldVertices => map.weld_vertices(targets),
+        }
+    }
+
+    /// The selected objects the operation works on, if there are enough.
+    fn targets(self, map: &Map, selection: &Selection) -> Option<BTreeSet<usize>> {
+        let (set, len, min) = match self {
+            Destructive::DeleteLineDefs => (&selection.linedefs, map.linedefs.len(), 1),
+            Destructive::MergeSectors => (&selection.sectors, map.sectors.len(), 2),
+            Destructive::WeldVertices => (&selection.vertices, map.vertices.len(), 2),
+        };
+
+        let targets = set.range(..len).copied().collect::<BTreeSet<_>>();
+        (targets.len() >= min).then_some(targets)
+    }
+}
+
+/// What a [`Destructive`] operation does.
+///
+/// Every index is from before the operation.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Impact {
+    /// The removed objects.
+    pub removed: Selection,
+    /// The objects that are kept, but changed.
+    ///
+    /// These are linedefs that are moved by welding, and the sidedefs and
+    /// linedefs of merged sectors.
+    pub changed: Selection,
+    /// The sector the others are merged into.
+    pub merged_into: Option<usize>,
+    /// The vertex the others are welded onto.
+    pub welded_onto: Option<usize>,
+    /// How many sectors became one.
+    pub merged_sectors: usize,
+    /// How many vertices became one.
+    pub welded_vertices: usize,
+}
+
+impl Impact {
+    /// Whether nothing changed.
+    pub fn is_empty(&self) -> bool {
+        self.removed.is_empty()
+    }
+
+    /// The vertex `v` is welded onto, or `v` if it isn't welded.
+    pub fn vertex(&self, v: i32) -> i32 {
+        match self.welded_onto {
+            Some(kept) if self.removed.vertices.contains(&(v as usize)) => kept as i32,
+            _ => v,
+        }
+    }
+}
+
+impl Display for Impact {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return f.write_str("Nothing changes");
+        }
+
+        let mut parts = Vec::new();
+
+        if self.merged_sectors > 0 {
+            parts.push(format!("{} sectors merged", self.merged_sectors));
+        }
+        if self.welded_vertices > 0 {
+            parts.push(format!("{} vertices welded", self.welded_vertices));
+        }
+
+        let removed = &self.removed;
+        // merged sectors were already counted
+        let sectors = match self.merged_sectors {
+            0 => removed.sectors.len(),
+            _ => 0,
+        };
+
+        for (count, one, many) in [
+            (removed.linedefs.len(), "linedef", "linedefs"),
+            (removed.sidedefs.len(), "sidedef", "sidedefs"),
+            (removed.vertices.len(), "vertex", "vertices"),
+            (sectors, "sector", "sectors"),
+        ] {
+            if count == 0 {
+                continue;
+            }
+
+            let name = if count == 1 { one } else { many };
+            parts.push(format!("{} {} removed", count, name));
+        }
+
+        f.write_str(&parts.join(", "))
+    }
+}
+
+impl Map {
+    fn delete_linedefs(&self, linedefs: BTreeSet<usize>) -> Impact {
+        let mut removed = Selection {
+            linedefs,
+            ..Default::default()
+        };
+        self.remove_unused(&mut removed, |v| v, |sector| sector);
+
+        Impact {
+            removed,
+            ..Default::default()
+        }
+    }
+
+    fn merge_sectors(&self, mut sectors: BTreeSet<usize>) -> Impact {
+        let merged_sectors = sectors.len();
+        let kept = sectors.pop_first().expect("merging at least two sectors");
+        let sector_of = |side: i32| {
+            self.sidedefs
+                .get(side as usize)
+                .map(|sidedef| sidedef.sector as usize)
+        };
+
+        // lines between two of the sectors end up inside of the merged one
+        let linedefs = self
+            .linedefs
+            .iter()
+            .enumerate()
+            .filter(|(_, linedef)| {
+                let front = sector_of(linedef.side_front);
+                let back = linedef.side_back.and_then(sector_of);
+
+                match (front, back) {
+                    (Some(front), Some(back)) => {
+                        front != back
+                            && [front, back]
+                                .iter()
+                                .all(|sector| *sector == kept || sectors.contains(sector))
+                    }
+                    _ => false,
+                }
+            })
+            .map(|(idx, _)| idx)
+            .collect();
+
+        let merged = sectors.clone();
+        let merge = |sector: i32| match merged.contains(&(sector as usize)) {
+            true => kept as i32,
+            false => sector,
+        };
+
+        let mut removed = Selection {
+            linedefs,
+            sectors,
+            ..Default::default()
+        };
+        self.remove_unused(&mut removed, |v| v, merge);
+
+        let sidedefs = self
+            .sidedefs
+            .iter()
+            .enumerate()
+            .filter(|(idx, sidedef)| {
+                merged.contains(&(sidedef.sector as usize)) && !removed.sidedefs.contains(idx)
+            })
+            .map(|(idx, _)| idx)
+            .collect::<BTreeSet<_>>();
+        let linedefs = self
+            .linedefs
+            .iter()
+            .enumerate()
+            .filter(|(idx, linedef)| {
+                !removed.linedefs.contains(idx)
+                    && std::iter::once(linedef.side_front)
+                        .chain(linedef.side_back)
+                        .any(|side| sidedefs.contains(&(side as usize)))
+            })
+            .map(|(idx, _)| idx)
+            .collect();
+
+        Impact {
+            removed,
+            changed: Selection {
+                linedefs,
+                sidedefs,
+                ..Default::default()
+            },
+            merged_into: Some(kept),
+            merged_sectors,
+            ..Default::default()
+        }
+    }
+
+    fn weld_vertices(&self, mut vertices: BTreeSet<usize>) -> Impact {
+        let welded_vertices = vertices.len();
+        let kept = vertices.pop_first().expect("welding at least two vertices");
+
+        let welded = vertices.clone();
+        let weld = |v: i32| match welded.contains(&(v as usize)) {
+            true => kept as i32,
+            false => v,
+        };
+
+        let linedefs = self
+            .linedefs
+            .iter()
+            .enumerate()
+            .filter(|(_, linedef)| {
+                weld(linedef.v1) == kept as i32 && weld(linedef.v2) == kept as i32
+            })
+            .map(|(idx, _)| idx)
+            .collect::<BTreeSet<_>>();
+        let changed = self
+            .linedefs
+            .iter()
+            .enumerate()
+            .filter(|(idx, linedef)| {
+                !linedefs.contains(idx)
+                    && (weld(linedef.v1) != linedef.v1 || weld(linedef.v2) != linedef.v2)
+            })
+            .map(|(idx, _)| idx)
+            .collect();
+
+        let mut removed = Selection {
+            linedefs,
+            vertices,
+            ..Default::default()
+        };
+        self.remove_unused(&mut removed, weld, |sector| sector);
+        // the welded vertex stays, even if all its lines are gone
+        removed.vertices.remove(&kept);
+
+        Impact {
+            removed,
+            changed: Selection {
+                linedefs: changed,
+                ..Default::default()
+            },
+            welded_onto: Some(kept),
+            welded_vertices,
+            ..Default::default()
+        }
+    }
+
+    /// Adds what only the removed linedefs use to `removed`.
+    ///
+    /// These are sidedefs and vertices no other linedef has, and sectors
+    /// left without sidedefs.
+    ///
+    /// `vertex` and `sector` give the vertex and sector each index is welded
+    /// or merged into by the operation.
+    fn remove_unused(
+        &self,
+        removed: &mut Selection,
+        vertex: impl Fn(i32) -> i32,
+        sector: impl Fn(i32) -> i32,
+    ) {
+        let mut used_vertices = BTreeSet::new();
+        let mut used_sidedefs = BTreeSet::new();
+
+        for (_, linedef) in self
+            .linedefs
+            .iter()
+            .enumerate()
+            .filter(|(idx, _)| !removed.linedefs.contains(idx))
+        {
+            used_vertices.extend([vertex(linedef.v1) as usize, vertex(linedef.v2) as usize]);
+            used_sidedefs.insert(linedef.side_front as usize);
+            used_sidedefs.extend(linedef.side_back.map(|side| side as usize));
+        }
+
+        for linedef in removed
+            .linedefs
+            .iter()
+            .filter_map(|&idx| self.linedefs.get(idx))
+        {
+            removed.vertices.extend(
+                [vertex(linedef.v1) as usize, vertex(linedef.v2) as usize]
+                    .into_iter()
+                    .filter(|v| *v < self.vertices.len() && !used_vertices.contains(v)),
+            );
+            removed.sidedefs.extend(
+                std::iter::once(linedef.side_front as usize)
+                    .chain(linedef.side_back.map(|side| side as usize))
+                    .filter(|side| *side < self.sidedefs.len() && !used_sidedefs.contains(side)),
+            );
+        }
+
+        let used_sectors = self
+            .sidedefs
+            .iter()
+            .enumerate()
+            .filter(|(idx, _)| !removed.sidedefs.contains(idx))
+            .map(|(_, sidedef)| sector(sidedef.sector) as usize)
+            .collect::<BTreeSet<_>>();
+
+        let emptied = removed
+            .sidedefs
+            .iter()
+            .map(|&idx| sector(self.sidedefs[idx].sector) as usize)
+            .filter(|sector| *sector < self.sectors.len() && !used_sectors.contains(sector))
+            .collect::<Vec<_>>();
+        removed.sectors.extend(emptied);
+    }
+
+    /// Removes the objects in `removed`, and moves the indices of everything
+    /// left to match.
+    ///
+    /// Nothing left in the map should refer to a removed object.
+    fn remove_objects(&mut self, removed: &Selection) {
+        retain_unremoved(&mut self.things, &removed.things);
+        retain_unremoved(&mut self.vertices, &removed.vertices);
+        retain_unremoved(&mut self.linedefs, &removed.linedefs);
+        retain_unremoved(&mut self.sidedefs, &removed.sidedefs);
+        retain_unremoved(&mut self.sectors, &removed.sectors);
+
+        for linedef in self.linedefs.iter_mut() {
+            linedef.v1 = shift(&removed.vertices, linedef.v1);
+            linedef.v2 = shift(&removed.vertices, linedef.v2);
+            linedef.side_front = shift(&removed.sidedefs, linedef.side_front);
+            linedef.side_back = linedef.side_back.map(|side| shift(&removed.sidedefs, side));
+        }
+
+        for sidedef in self.sidedefs.iter_mut() {
+            sidedef.sector = shift(&removed.sectors, sidedef.sector);
+        }
+    }
+}
+
+fn retain_unremoved<T>(objects: &mut Vec<T>, removed: &BTreeSet<usize>) {
+    let mut idx = 0;
+    objects.retain(|_| {
+        idx += 1;
+        !removed.contains(&(idx - 1))
+    });
+}
+
+/// Moves `idx` back past the removed indices before it.
+fn shift(removed: &BTreeSet<usize>, idx: i32) -> i32 {
+    idx - removed.range(..idx.max(0) as usize).count() as i32
+}
+
+/// Moves the objects of `selection` to their indices after `removed` are
+/// removed, leaving the removed objects out.
+fn shift_selection(selection: &Selection, removed: &Selection) -> Selection {
+    let shift_set = |set: &BTreeSet<usize>, removed: &BTreeSet<usize>| {
+        set.difference(removed)
+            .map(|&idx| shift(removed, idx as i32) as usize)
+            .collect()
+    };
+
+    Selection {
+        things: shift_set(&selection.things, &removed.things),
+        vertices: shift_set(&selection.vertices, &removed.vertices),
+        linedefs: shift_set(&selection.linedefs, &removed.linedefs),
+        sidedefs: shift_set(&selection.sidedefs, &removed.sidedefs),
+        sectors: shift_set(&selection.sectors, &removed.sectors),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // two squares side by side, sharing the line from vertex 1 to 2
+    const SQUARES: &str = r#"
+    namespace = "ringracers";
+    version = 1;
+
+    vertex { x = 0.0; y = 0.0; }
+    vertex { x = 64.0; y = 0.0; }
+    vertex { x = 64.0; y = 64.0; }
+    vertex { x = 0.0; y = 64.0; }
+    vertex { x = 128.0; y = 0.0; }
+    vertex { x = 128.0; y = 64.0; }
+
+    linedef { v1 = 0; v2 = 1; sidefront = 0; }
+    linedef { v1 = 1; v2 = 2; sidefront = 1; sideback = 4; twosided = true; }
+    linedef { v1 = 2; v2 = 3; sidefront = 2; }
+    linedef { v1 = 3; v2 = 0; sidefront = 3; }
+    linedef { v1 = 1; v2 = 4; sidefront = 5; }
+    linedef { v1 = 4; v2 = 5; sidefront = 6; }
+    linedef { v1 = 5; v2 = 2; sidefront = 7; }
+
+    sidedef { sector = 0; }
+    sidedef { sector = 0; }
+    sidedef { sector = 0; }
+    sidedef { sector = 0; }
+    sidedef { sector = 1; }
+    sidedef { sector = 1; }
+    sidedef { sector = 1; }
+    sidedef { sector = 1; }
+
+    sector { texturefloor = "FLOOR"; textureceiling = "CEIL"; }
+    sector { texturefloor = "GRASS"; textureceiling = "CEIL"; }
+    "#;
+
+    fn assert_consistent(map: &Map) {
+        for linedef in map.linedefs.iter() {
+            assert!((linedef.v1 as usize) < map.vertices.len());
+            assert!((linedef.v2 as usize) < map.vertices.len());
+            assert!((linedef.side_front as usize) < map.sidedefs.len());
+        }
+        for sidedef in map.sidedefs.iter() {
+            assert!((sidedef.sector as usize) < map.sectors.len());
+        }
+    }
+
+    #[test]
+    fn merge_preview() {
+        let map = Map::from_str(SQUARES).unwrap();
+        let selection = Selection {
+            sectors: [0, 1].into(),
+            ..Default::default()
+        };
+
+        let impact = Destructive::MergeSectors.preview(&map, &selection);
+        assert_eq!(impact.removed.linedefs, [1].into());
+        assert_eq!(impact.removed.sidedefs, [1, 4].into());
+        assert_eq!(impact.merged_into, Some(0));
+        // the outer sides of the right square move into the left one
+        assert_eq!(impact.changed.sidedefs, [5, 6, 7].into());
+        assert_eq!(impact.changed.linedefs, [4, 5, 6].into());
+        assert_eq!(
+            impact.to_string(),
+            "2 sectors merged, 1 linedef removed, 2 sidedefs removed"
+        );
+
+        let mut merged = map.clone();
+        let applied = Destructive::MergeSectors.apply(&mut merged, &mut selection.clone());
+        assert_eq!(applied, impact);
+        assert_eq!(merged.sectors.len(), 1);
+        assert_eq!(merged.linedefs.len(), 6);
+        assert_consistent(&merged);
+
+        // the shared vertices are still used by the outer lines
+        assert_eq!(merged.vertices.len(), 6);
+
+        let nothing = Destructive::WeldVertices.preview(&map, &selection);
+        assert!(nothing.is_empty());
+    }
+
+    #[test]
+    fn delete_and_weld() {
+        let mut map = Map::from_str(SQUARES).unwrap();
+
+        // the right square, without its shared line
+        let mut selection = Selection {
+            linedefs: [4, 5, 6].into(),
+            sectors: [0].into(),
+            ..Default::default()
+        };
+        let impact = Destructive::DeleteLineDefs.apply(&mut map, &mut selection);
+        assert_eq!(impact.removed.vertices, [4, 5].into());
+        assert_eq!(impact.removed.sectors, BTreeSet::new());
+        assert_eq!(selection.sectors, [0].into());
+        assert!(selection.linedefs.is_empty());
+        assert_eq!(map.linedefs.len(), 4);
+        assert_consistent(&map);
+
+        // collapse the bottom line of the left square
+        let mut selection = Selection {
+            vertices: [0, 1].into(),
+            ..Default::default()
+        };
+        let impact = Destructive::WeldVertices.apply(&mut map, &mut selection);
+        assert_eq!(impact.removed.linedefs, [0].into());
+        assert_eq!(impact.removed.vertices, [1].into());
+        assert_eq!(impact.changed.linedefs, [1].into());
+        assert_eq!(impact.vertex(1), 0);
+        assert_eq!(selection.vertices, [0].into());
+        assert_eq!(map.vertices.len(), 3);
+        assert_eq!(map.linedefs[0].v1, 0);
+        assert_consistent(&map);
+    }
+}
@@ -0,0 +1,215 @@
+//! Find and replace.
+
+use std::collections::BTreeSet;
+
+use super::{Map, Selection, SIDEDEF_TEXTURES};
+use crate::format::udmf::Value;
+
+/// Something to replace across a map.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Replace {
+    /// Replaces a texture or flat on sidedefs and sectors.
+    ///
+    /// Texture names are compared without case, like the game does.
+    Texture { from: String, to: String },
+    /// Replaces the type of things.
+    ThingType { from: i32, to: i32 },
+}
+
+impl Map {
+    /// Finds the objects [`Map::replace`] would change.
+    ///
+    /// See [`Map::replace`] for how `within` works.
+    pub fn find(&self, replace: &Replace, within: Option<&Selection>) -> Selection {
+        let scope = within.map(|selection| self.scope(selection));
+        let in_scope = |set: fn(&Selection) -> &BTreeSet<usize>, idx: usize| match &scope {
+            Some(scope) => set(scope).contains(&idx),
+            None => true,
+        };
+
+        let mut found = Selection::default();
+
+        match replace {
+            Replace::Texture { from, .. } => {
+                let matches = |texture: &str| texture.eq_ignore_ascii_case(from);
+
+                found.sidedefs = self
+                    .sidedefs
+                    .iter()
+                    .enumerate()
+                    .filter(|(idx, _)| in_scope(|s| &s.sidedefs, *idx))
+                    .filter(|(_, sidedef)| {
+                        SIDEDEF_TEXTURES.iter().any(|field| {
+                            matches!(
+                                sidedef.extras.get(field),
+                                Some(Value::String(texture)) if matches(texture)
+                            )
+                        })
+                    })
+                    .map(|(idx, _)| idx)
+                    .collect();
+                found.sectors = self
+                    .sectors
+                    .iter()
+                    .enumerate()
+                    .filter(|(idx, _)| in_scope(|s| &s.sectors, *idx))
+                    .filter(|(_, sector)| {
+                        matches(&sector.texture_floor) || matches(&sector.texture_ceiling)
+                    })
+                    .map(|(idx, _)| idx)
+                    .collect();
+            }
+            Replace::ThingType { from, .. } => {
+                found.things = self
+                    .things
+                    .iter()
+                    .enumerate()
+                    .filter(|(idx, _)| in_scope(|s| &s.things, *idx))
+                    .filter(|(_, thing)| thing.kind == *from)
+                    .map(|(idx, _)| idx)
+                    .collect();
+            }
+        }
+
+        found
+    }
+
+    /// Replaces a texture or thing type across the map.
+    ///
+    /// If `within` is given, only the selected objects are changed. The
+    /// sidedefs of selected linedefs count as selected, and so do the sectors
+    /// of selected sidedefs.
+    ///
+    /// Returns the objects that were changed.
+    pub fn replace(&mut self, replace: &Replace, within: Option<&Selection>) -> Selection {
+        let found = self.find(replace, within);
+
+        match replace {
+            Replace::Texture { from, to } => {
+                let replace_texture = |texture: &mut String| {
+                    if texture.eq_ignore_ascii_case(from) {
+                        texture.clone_from(to);
+                    }
+                };
+
+                for &idx in found.sidedefs.iter() {
+                    for field in SIDEDEF_TEXTURES {
+                        if let Some(Value::String(texture)) =
+                            self.sidedefs[idx].extras.get_mut(field)
+                        {
+                            replace_texture(texture);
+                        }
+                    }
+                }
+                for &idx in found.sectors.iter() {
+                    let sector = &mut self.sectors[idx];
+                    replace_texture(&mut sector.texture_floor);
+                    replace_texture(&mut sector.texture_ceiling);
+                }
+            }
+            Replace::ThingType { to, .. } => {
+                for &idx in found.things.iter() {
+                    self.things[idx].kind = *to;
+                }
+            }
+        }
+
+        found
+    }
+
+    /// Adds the sidedefs and sectors a selection implies.
+    fn scope(&self, selection: &Selection) -> Selection {
+        let mut scope = selection.clone();
+
+        for linedef in selection
+            .linedefs
+            .iter()
+            .filter_map(|&idx| self.linedefs.get(idx))
+        {
+            scope.sidedefs.insert(linedef.side_front as usize);
+            scope
+                .sidedefs
+                .extend(linedef.side_back.map(|side| side as usize));
+        }
+
+        let sectors = scope
+            .sidedefs
+            .iter()
+            .filter_map(|&idx| self.sidedefs.get(idx))
+            .map(|sidedef| sidedef.sector as usize)
+            .collect::<Vec<_>>();
+        scope.sectors.extend(sectors);
+
+        scope
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MAP: &str = r#"
+    namespace = "ringracers";
+    version = 1;
+
+    thing { x = 0.0; y = 0.0; angle = 0; type = 1; }
+    thing { x = 0.0; y = 0.0; angle = 0; type = 2; }
+    thing { x = 0.0; y = 0.0; angle = 0; type = 1; }
+
+    vertex { x = 0.0; y = 0.0; }
+    vertex { x = 0.0; y = 64.0; }
+    vertex { x = 64.0; y = 64.0; }
+
+    linedef { v1 = 0; v2 = 1; sidefront = 0; }
+    linedef { v1 = 1; v2 = 2; sidefront = 1; }
+
+    sidedef { sector = 0; texturemiddle = "WALL"; }
+    sidedef { sector = 1; texturemiddle = "wall"; texturetop = "WALL"; }
+
+    sector { texturefloor = "WALL"; textureceiling = "CEIL"; }
+    sector { texturefloor = "FLOOR"; textureceiling = "CEIL"; }
+    "#;
+
+    #[test]
+    fn replace_texture() {
+        let mut map = Map::from_str(MAP).unwrap();
+        let replace = Replace::Texture {
+            from: "WALL".into(),
+            to: "BRICK".into(),
+        };
+
+        let changed = map.replace(&replace, None);
+
+        assert_eq!(changed.sidedefs, [0, 1].into());
+        assert_eq!(changed.sectors, [0].into());
+        assert_eq!(
+            map.sidedefs[1].extras.get("texturemiddle"),
+            Some(&Value::String("BRICK".into()))
+        );
+        assert_eq!(map.sectors[0].texture_floor, "BRICK");
+        assert!(map.find(&replace, None).is_empty());
+    }
+
+    #[test]
+    fn replace_within_selection() {
+        let mut map = Map::from_str(MAP).unwrap();
+
+        let selection = Selection {
+            things: [0, 1].into(),
+            linedefs: [1].into(),
+            ..Default::default()
+        };
+
+        let changed = map.replace(&Replace::ThingType { from: 1, to: 3 }, Some(&selection));
+        assert_eq!(changed.things, [0].into());
+        assert_eq!(map.things[2].kind, 1);
+
+        let replace = Replace::Texture {
+            from: "WALL".into(),
+            to: "BRICK".into(),
+        };
+        let found = map.find(&replace, Some(&selection));
+        assert_eq!(found.sidedefs, [1].into());
+        assert!(found.sectors.is_empty());
+    }
+}
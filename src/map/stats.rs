@@ -0,0 +1,167 @@
+//! Map statistics.
+
+use std::collections::BTreeMap;
+use std::fmt::{self, Display, Formatter};
+
+use super::{Map, SIDEDEF_TEXTURES};
+use crate::format::udmf::Value;
+
+/// Statistics about a map, see [`Map::stats`].
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Stats {
+    pub things: usize,
+    pub vertices: usize,
+    pub linedefs: usize,
+    pub sidedefs: usize,
+    pub sectors: usize,
+    /// The smallest rectangle around all vertices and things, as
+    /// `[min_x, min_y, max_x, max_y]`.
+    ///
+    /// `None` if the map is empty.
+    pub bounds: Option<[f32; 4]>,
+    /// The length of all linedefs added up, in map units.
+    pub linedef_length: f32,
+    /// How many times each texture and flat is used.
+    pub textures: BTreeMap<String, usize>,
+    /// How many things of each type there are.
+    pub thing_types: BTreeMap<i32, usize>,
+}
+
+impl Map {
+    /// Collects statistics about the map.
+    pub fn stats(&self) -> Stats {
+        let points = self
+            .vertices
+            .iter()
+            .map(|v| (v.x, v.y))
+            .chain(self.things.iter().map(|t| (t.x, t.y)));
+        let bounds = points.fold(None, |bounds: Option<[f32; 4]>, (x, y)| {
+            Some(match bounds {
+                Some([min_x, min_y, max_x, max_y]) => {
+                    [min_x.min(x), min_y.min(y), max_x.max(x), max_y.max(y)]
+                }
+                None => [x, y, x, y],
+            })
+        });
+
+        let linedef_length = self
+            .linedefs
+            .iter()
+            .filter_map(|linedef| self.linedef_segment(linedef))
+            .map(|segment| segment.a.distance(segment.b))
+            .sum();
+
+        let mut textures = BTreeMap::new();
+        let mut count_texture = |texture: &str| {
+            // "-" means no texture
+            if !texture.is_empty() && texture != "-" {
+                *textures.entry(texture.to_string()).or_default() += 1;
+            }
+        };
+
+        for sector in self.sectors.iter() {
+            count_texture(&sector.texture_floor);
+            count_texture(&sector.texture_ceiling);
+        }
+        for sidedef in self.sidedefs.iter() {
+            for field in SIDEDEF_TEXTURES {
+                if let Some(Value::String(texture)) = sidedef.extras.get(field) {
+                    count_texture(texture);
+                }
+            }
+        }
+
+        let mut thing_types = BTreeMap::new();
+        for thing in self.things.iter() {
+            *thing_types.entry(thing.kind).or_default() += 1;
+        }
+
+        Stats {
+            things: self.things.len(),
+            vertices: self.vertices.len(),
+            linedefs: self.linedefs.len(),
+            sidedefs: self.sidedefs.len(),
+            sectors: self.sectors.len(),
+            bounds,
+            linedef_length,
+            textures,
+            thing_types,
+        }
+    }
+}
+
+impl Display for Stats {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        writeln!(f, "things:   {}", self.things)?;
+        writeln!(f, "vertices: {}", self.vertices)?;
+        writeln!(f, "linedefs: {}", self.linedefs)?;
+        writeln!(f, "sidedefs: {}", self.sidedefs)?;
+        writeln!(f, "sectors:  {}", self.sectors)?;
+
+        if let Some([min_x, min_y, max_x, max_y]) = self.bounds {
+            writeln!(
+                f,
+                "bounds:   ({}, {}) to ({}, {}), {} x {}",
+                min_x,
+                min_y,
+                max_x,
+                max_y,
+                max_x - min_x,
+                max_y - min_y
+            )?;
+        }
+        writeln!(f, "linedef length: {:.0}", self.linedef_length)?;
+
+        writeln!(f, "textures:")?;
+        for (texture, count) in self.textures.iter() {
+            writeln!(f, "  {:<8} {}", texture, count)?;
+        }
+
+        writeln!(f, "thing types:")?;
+        for (kind, count) in self.thing_types.iter() {
+            writeln!(f, "  {:<8} {}", kind, count)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stats() {
+        let map = Map::from_str(
+            r#"
+            namespace = "ringracers";
+            version = 1;
+
+            thing { x = -64.0; y = 0.0; angle = 0; type = 1; }
+            thing { x = 32.0; y = 32.0; angle = 0; type = 1; }
+
+            vertex { x = 0.0; y = 0.0; }
+            vertex { x = 0.0; y = 128.0; }
+            vertex { x = 96.0; y = 128.0; }
+
+            linedef { v1 = 0; v2 = 1; sidefront = 0; }
+            linedef { v1 = 1; v2 = 2; sidefront = 1; }
+
+            sidedef { sector = 0; texturemiddle = "WALL"; texturetop = "-"; }
+            sidedef { sector = 0; texturemiddle = "WALL"; }
+
+            sector { texturefloor = "FLOOR"; textureceiling = "F_SKY1"; }
+            "#,
+        )
+        .unwrap();
+        let stats = map.stats();
+
+        assert_eq!(stats.things, 2);
+        assert_eq!(stats.bounds, Some([-64.0, 0.0, 96.0, 128.0]));
+        assert_eq!(stats.linedef_length, 224.0);
+        assert_eq!(stats.textures.get("WALL"), Some(&2));
+        assert_eq!(stats.textures.get("-"), None);
+        assert_eq!(stats.textures.len(), 3);
+        assert_eq!(stats.thing_types.get(&1), Some(&2));
+    }
+}
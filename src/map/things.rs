@@ -0,0 +1,192 @@
+//! Thing categories, and which way things face.
+//!
+//! This is a small database of the common Ring Racers thing types, enough to
+//! tell the things that matter for a race apart from scenery.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::{self, Display, Formatter};
+use std::ops::RangeInclusive;
+
+use super::validate::{PLAYER_STARTS, STAR_POST, WAYPOINT};
+use super::Map;
+
+/// The thing type of random item boxes.
+pub const ITEM_BOX: i32 = 2000;
+
+/// What a thing is for.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Category {
+    PlayerStart,
+    /// Waypoints and star posts.
+    Waypoint,
+    /// Rings and item boxes.
+    Item,
+    Hazard,
+    /// Things that control the map, like slope vertices and polyobject
+    /// anchors.
+    Control,
+    Decoration,
+    /// Anything not in the database.
+    Other,
+}
+
+impl Category {
+    /// Every category.
+    pub const ALL: [Category; 7] = [
+        Category::PlayerStart,
+        Category::Waypoint,
+        Category::Item,
+        Category::Hazard,
+        Category::Control,
+        Category::Decoration,
+        Category::Other,
+    ];
+
+    /// The category of a thing type.
+    pub fn of(kind: i32) -> Category {
+        TYPES
+            .iter()
+            .find(|(kinds, _)| kinds.contains(&kind))
+            .map_or(Category::Other, |&(_, category)| category)
+    }
+}
+
+/// The thing types in the database, and their categories.
+const TYPES: &[(RangeInclusive<i32>, Category)] = &[
+    (PLAYER_STARTS, Category::PlayerStart),
+    (WAYPOINT..=WAYPOINT, Category::Waypoint),
+    (STAR_POST..=STAR_POST, Category::Waypoint),
+    // rings
+    (300..=399, Category::Item),
+    (ITEM_BOX..=ITEM_BOX, Category::Item),
+    // spikes, spike balls and mines
+    (520..=529, Category::Hazard),
+    // ambient sounds
+    (700..=749, Category::Control),
+    // slope vertices, teleport destinations and viewpoints
+    (750..=759, Category::Control),
+    // polyobject anchors and spawn points
+    (760..=769, Category::Control),
+    // skybox viewpoints and centers
+    (780..=799, Category::Control),
+    // scenery, like flowers and trees
+    (800..=1999, Category::Decoration),
+];
+
+impl Display for Category {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Category::PlayerStart => "Player starts",
+            Category::Waypoint => "Waypoints",
+            Category::Item => "Items",
+            Category::Hazard => "Hazards",
+            Category::Control => "Control",
+            Category::Decoration => "Decoration",
+            Category::Other => "Other",
+        })
+    }
+}
+
+impl Map {
+    /// Finds all things in any of `categories`.
+    pub fn things_in(&self, categories: &BTreeSet<Category>) -> BTreeSet<usize> {
+        if categories.is_empty() {
+            return BTreeSet::new();
+        }
+
+        self.things
+            .iter()
+            .enumerate()
+            .filter(|(_, thing)| categories.contains(&Category::of(thing.kind)))
+            .map(|(idx, _)| idx)
+            .collect()
+    }
+
+    /// How many things there are in each category.
+    pub fn category_counts(&self) -> BTreeMap<Category, usize> {
+        let mut counts = BTreeMap::new();
+
+        for thing in self.things.iter() {
+            *counts.entry(Category::of(thing.kind)).or_default() += 1;
+        }
+
+        counts
+    }
+
+    /// Turns the things in `things` to face the point `(x, y)`.
+    ///
+    /// Things right on the point are left as they are.
+    pub fn face_point(&mut self, things: &BTreeSet<usize>, x: f32, y: f32) {
+        for &idx in things.iter() {
+            let Some(thing) = self.things.get_mut(idx) else {
+                continue;
+            };
+
+            if (thing.x, thing.y) != (x, y) {
+                thing.angle = angle_toward(x - thing.x, y - thing.y);
+            }
+        }
+    }
+}
+
+/// The angle of the direction `(dx, dy)`, in whole degrees.
+///
+/// Angles go counterclockwise from east, and are always between 0 and 359.
+pub fn angle_toward(dx: f32, dy: f32) -> i32 {
+    (dy.atan2(dx).to_degrees().round() as i32).rem_euclid(360)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn things_in() {
+        let map = Map::from_str(
+            r#"
+            namespace = "ringracers";
+            version = 1;
+
+            thing { x = 0.0; y = 0.0; angle = 0; type = 1; }
+            thing { x = 0.0; y = 0.0; angle = 0; type = 2001; }
+            thing { x = 0.0; y = 0.0; angle = 0; type = 800; }
+            thing { x = 0.0; y = 0.0; angle = 0; type = 801; }
+            thing { x = 0.0; y = 0.0; angle = 0; type = 4242; }
+            thing { x = 0.0; y = 0.0; angle = 0; type = 750; }
+            thing { x = 0.0; y = 0.0; angle = 0; type = 760; }
+            "#,
+        )
+        .unwrap();
+
+        let counts = map.category_counts();
+        assert_eq!(counts.get(&Category::Decoration), Some(&2));
+        assert_eq!(counts.get(&Category::Control), Some(&2));
+        assert_eq!(counts.get(&Category::Item), None);
+
+        let things = map.things_in(&[Category::PlayerStart, Category::Other].into());
+        assert_eq!(things, [0, 4].into());
+    }
+
+    #[test]
+    fn face_point() {
+        let mut map = Map::from_str(
+            r#"
+            namespace = "ringracers";
+            version = 1;
+
+            thing { x = 0.0; y = 0.0; angle = 0; type = 1; }
+            thing { x = 64.0; y = 64.0; angle = 0; type = 1; }
+            thing { x = 0.0; y = 64.0; angle = 90; type = 1; }
+            thing { x = 0.0; y = 0.0; angle = 45; type = 1; }
+            "#,
+        )
+        .unwrap();
+
+        map.face_point(&[0, 1, 2].into(), 0.0, 64.0);
+
+        let angles = map.things.iter().map(|t| t.angle).collect::<Vec<_>>();
+        // the thing on the point and the unselected thing stay put
+        assert_eq!(angles, [90, 180, 90, 45]);
+        assert_eq!(angle_toward(0.0, -1.0), 270);
+    }
+}
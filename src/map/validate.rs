@@ -0,0 +1,448 @@
+//! Checks for common mistakes in race maps.
+//!
+//! These follow Ring Racers conventions: a race needs a finish line, player
+//! starts, star posts numbered in order, and waypoints along the whole course
+//! for bots and position tracking. Maps in other namespaces are only checked
+//! for fields their namespace doesn't have.
+
+use std::cmp::Reverse;
+use std::fmt::{self, Display, Formatter};
+use std::ops::RangeInclusive;
+
+use super::namespace::Namespace;
+use super::{Extras, Map, Selection, Thing};
+use crate::format::udmf::Value;
+
+/// The linedef special of finish lines.
+pub const FINISH_LINE_SPECIAL: i32 = 2001;
+
+/// The thing types of player starts.
+pub const PLAYER_STARTS: RangeInclusive<i32> = 1..=16;
+
+/// The thing type of star posts.
+pub const STAR_POST: i32 = 502;
+
+/// The thing type of waypoints.
+pub const WAYPOINT: i32 = 2001;
+
+/// How close player starts can be before karts overlap, in map units.
+pub const MIN_START_SPACING: f32 = 64.0;
+
+/// How far any point on the course can be from a waypoint, in map units.
+pub const WAYPOINT_RANGE: f32 = 2048.0;
+
+/// How serious a problem is.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    /// The map works, but probably not the way it should.
+    Warning,
+    /// The map is not playable.
+    Error,
+}
+
+/// A problem found in a map.
+#[derive(Clone, Debug)]
+pub struct Problem {
+    pub severity: Severity,
+    pub message: String,
+    /// The objects with the problem.
+    pub objects: Selection,
+    /// How the problem can be fixed automatically, if it can be.
+    pub fix: Option<Fix>,
+}
+
+impl Display for Problem {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+/// An automatic fix for a [`Problem`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Fix {
+    /// Clears the finish line special from every linedef but `keep`.
+    KeepFinishLine { keep: usize },
+    /// Numbers the star posts from 1, in their current order.
+    RenumberStarPosts,
+}
+
+impl Fix {
+    /// Describes what the fix does.
+    pub fn description(&self) -> &'static str {
+        match self {
+            Fix::KeepFinishLine { .. } => "Remove the other finish lines",
+            Fix::RenumberStarPosts => "Renumber star posts",
+        }
+    }
+
+    /// Applies the fix to `map`.
+    pub fn apply(&self, map: &mut Map) {
+        match *self {
+            Fix::KeepFinishLine { keep } => {
+                for (idx, linedef) in map.linedefs.iter_mut().enumerate() {
+                    if idx != keep && int_field(&linedef.extras, "special") == FINISH_LINE_SPECIAL {
+                        linedef.extras.remove("special");
+                    }
+                }
+            }
+            Fix::RenumberStarPosts => {
+                for (n, idx) in star_posts(map).into_iter().map(|(_, idx)| idx).enumerate() {
+                    map.things[idx]
+                        .extras
+                        .insert("arg0", Value::Integer(n as i32 + 1));
+                }
+            }
+        }
+    }
+}
+
+/// A single check, adding the problems it finds.
+pub type Check = fn(&Map, &mut Vec<Problem>);
+
+/// Every check [`check`] runs.
+///
+/// These can also be run one at a time, like the editor does in the
+/// background.
+pub const CHECKS: [Check; 5] = [
+    check_fields,
+    check_finish_line,
+    check_player_starts,
+    check_star_posts,
+    check_waypoints,
+];
+
+/// Checks a map for problems.
+///
+/// Problems are sorted with the most severe first.
+pub fn check(map: &Map) -> Vec<Problem> {
+    let mut problems = Vec::new();
+
+    for check in CHECKS {
+        check(map, &mut problems);
+    }
+
+    sort(&mut problems);
+    problems
+}
+
+/// Sorts problems with the most severe first.
+pub fn sort(problems: &mut [Problem]) {
+    problems.sort_by_key(|problem| Reverse(problem.severity));
+}
+
+fn check_fields(map: &Map, problems: &mut Vec<Problem>) {
+    let Some(namespace) = Namespace::from_name(&map.namespace) else {
+        problems.push(Problem {
+            severity: Severity::Warning,
+            message: format!(
+                "Unknown namespace \"{}\", fields aren't checked",
+                map.namespace
+            ),
+            objects: Selection::default(),
+            fix: None,
+        });
+        return;
+    };
+
+    for (field, objects) in map.invalid_fields(namespace) {
+        problems.push(Problem {
+            severity: Severity::Warning,
+            message: format!("`{}` isn't a field in the {} namespace", field, namespace),
+            objects,
+            fix: None,
+        });
+    }
+}
+
+fn check_finish_line(map: &Map, problems: &mut Vec<Problem>) {
+    if !is_race_map(map) {
+        return;
+    }
+
+    let finish_lines = finish_lines(map);
+
+    match finish_lines.len() {
+        0 => problems.push(Problem {
+            severity: Severity::Error,
+            message: "There is no finish line".into(),
+            objects: Selection::default(),
+            fix: None,
+        }),
+        1 => (),
+        n => problems.push(Problem {
+            severity: Severity::Error,
+            message: format!("There are {} finish lines, there should only be one", n),
+            objects: Selection {
+                linedefs: finish_lines.iter().copied().collect(),
+                ..Default::default()
+            },
+            fix: Some(Fix::KeepFinishLine {
+                keep: finish_lines[0],
+            }),
+        }),
+    }
+}
+
+fn check_player_starts(map: &Map, problems: &mut Vec<Problem>) {
+    if !is_race_map(map) {
+        return;
+    }
+
+    let starts = things_of(map, |kind| PLAYER_STARTS.contains(&kind));
+
+    if starts.len() < PLAYER_STARTS.count() {
+        problems.push(Problem {
+            severity: Severity::Warning,
+            message: format!(
+                "There are only {} of {} player starts",
+                starts.len(),
+                PLAYER_STARTS.count()
+            ),
+            objects: Selection {
+                things: starts.iter().copied().collect(),
+                ..Default::default()
+            },
+            fix: None,
+        });
+    }
+
+    for (i, &a) in starts.iter().enumerate() {
+        for &b in starts[i + 1..].iter() {
+            if distance(&map.things[a], &map.things[b]) < MIN_START_SPACING {
+                problems.push(Problem {
+                    severity: Severity::Warning,
+                    message: format!("Player starts {} and {} are too close", a, b),
+                    objects: Selection {
+                        things: [a, b].into(),
+                        ..Default::default()
+                    },
+                    fix: None,
+                });
+            }
+        }
+    }
+}
+
+fn check_star_posts(map: &Map, problems: &mut Vec<Problem>) {
+    if !is_race_map(map) {
+        return;
+    }
+
+    let posts = star_posts(map);
+
+    // numbers should go 1, 2, 3...
+    let mut expected = 1;
+    for &(number, idx) in posts.iter() {
+        if number != expected {
+            let message = if number == expected - 1 {
+                format!(
+                    "Star post {} is numbered {}, like the one before",
+                    idx, number
+                )
+            } else {
+                format!(
+                    "Star post {} is numbered {}, expected {}",
+                    idx, number, expected
+                )
+            };
+
+            problems.push(Problem {
+                severity: Severity::Warning,
+                message,
+                objects: Selection {
+                    things: [idx].into(),
+                    ..Default::default()
+                },
+                fix: Some(Fix::RenumberStarPosts),
+            });
+        }
+
+        expected = number + 1;
+    }
+}
+
+fn check_waypoints(map: &Map, problems: &mut Vec<Problem>) {
+    if !is_race_map(map) {
+        return;
+    }
+
+    let waypoints = things_of(map, |kind| kind == WAYPOINT)
+        .into_iter()
+        .map(|idx| &map.things[idx])
+        .collect::<Vec<_>>();
+
+    if waypoints.is_empty() {
+        problems.push(Problem {
+            severity: Severity::Error,
+            message: "There are no waypoints".into(),
+            objects: Selection::default(),
+            fix: None,
+        });
+        return;
+    }
+
+    // follow the course from the starts, through the star posts, to the
+    // finish line
+    let mut course = Vec::new();
+
+    let starts = things_of(map, |kind| PLAYER_STARTS.contains(&kind));
+    if !starts.is_empty() {
+        let (x, y) = starts.iter().fold((0.0, 0.0), |(x, y), &idx| {
+            (x + map.things[idx].x, y + map.things[idx].y)
+        });
+        course.push((x / starts.len() as f32, y / starts.len() as f32, None));
+    }
+
+    course.extend(star_posts(map).into_iter().map(|(_, idx)| {
+        let thing = &map.things[idx];
+        (thing.x, thing.y, Some(idx))
+    }));
+
+    if let Some(&idx) = finish_lines(map).first() {
+        let linedef = &map.linedefs[idx];
+        if let (Some(v1), Some(v2)) = (
+            map.vertices.get(linedef.v1 as usize),
+            map.vertices.get(linedef.v2 as usize),
+        ) {
+            course.push(((v1.x + v2.x) / 2.0, (v1.y + v2.y) / 2.0, None));
+        }
+    }
+
+    let covered = |x: f32, y: f32| {
+        waypoints
+            .iter()
+            .any(|w| (w.x - x).hypot(w.y - y) <= WAYPOINT_RANGE)
+    };
+
+    for (i, window) in course.windows(2).enumerate() {
+        let [(x1, y1, _), (x2, y2, post)] = *window else {
+            unreachable!();
+        };
+
+        // sample the leg often enough to not miss any gaps
+        let steps = ((x2 - x1).hypot(y2 - y1) / WAYPOINT_RANGE).ceil().max(1.0) as usize * 2;
+        let uncovered = (0..=steps).any(|step| {
+            let t = step as f32 / steps as f32;
+            !covered(x1 + (x2 - x1) * t, y1 + (y2 - y1) * t)
+        });
+
+        if uncovered {
+            problems.push(Problem {
+                severity: Severity::Warning,
+                message: format!("Part {} of the course has no waypoints nearby", i + 1),
+                objects: Selection {
+                    things: post.into_iter().collect(),
+                    ..Default::default()
+                },
+                fix: None,
+            });
+        }
+    }
+}
+
+/// Whether the race checks apply to `map`.
+fn is_race_map(map: &Map) -> bool {
+    Namespace::from_name(&map.namespace) == Some(Namespace::RingRacers)
+}
+
+/// The linedefs with the finish line special.
+fn finish_lines(map: &Map) -> Vec<usize> {
+    map.linedefs
+        .iter()
+        .enumerate()
+        .filter(|(_, linedef)| int_field(&linedef.extras, "special") == FINISH_LINE_SPECIAL)
+        .map(|(idx, _)| idx)
+        .collect()
+}
+
+/// The star posts, as `(number, index)` pairs, in course order.
+fn star_posts(map: &Map) -> Vec<(i32, usize)> {
+    let mut posts = things_of(map, |kind| kind == STAR_POST)
+        .into_iter()
+        .map(|idx| (int_field(&map.things[idx].extras, "arg0"), idx))
+        .collect::<Vec<_>>();
+    posts.sort();
+    posts
+}
+
+fn things_of(map: &Map, f: impl Fn(i32) -> bool) -> Vec<usize> {
+    map.things
+        .iter()
+        .enumerate()
+        .filter(|(_, thing)| f(thing.kind))
+        .map(|(idx, _)| idx)
+        .collect()
+}
+
+fn int_field(extras: &Extras, key: &str) -> i32 {
+    match extras.get(key) {
+        Some(Value::Integer(int)) => *int,
+        _ => 0,
+    }
+}
+
+fn distance(a: &Thing, b: &Thing) -> f32 {
+    (a.x - b.x).hypot(a.y - b.y)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const COURSE: &str = r#"
+    namespace = "ringracers";
+    version = 1;
+
+    thing { x = 0.0; y = 0.0; angle = 0; type = 1; }
+    thing { x = 32.0; y = 0.0; angle = 0; type = 2; }
+    thing { x = 1024.0; y = 0.0; angle = 0; type = 502; arg0 = 1; }
+    thing { x = 4096.0; y = 0.0; angle = 0; type = 502; arg0 = 3; }
+    thing { x = 1024.0; y = 0.0; angle = 0; type = 2001; }
+
+    vertex { x = 8192.0; y = -256.0; }
+    vertex { x = 8192.0; y = 256.0; }
+    vertex { x = 0.0; y = -256.0; }
+    vertex { x = 0.0; y = 256.0; }
+
+    linedef { v1 = 0; v2 = 1; sidefront = 0; special = 2001; }
+    linedef { v1 = 2; v2 = 3; sidefront = 0; special = 2001; }
+
+    sidedef { sector = 0; }
+
+    sector { texturefloor = "FLOOR"; textureceiling = "CEIL"; }
+    "#;
+
+    #[test]
+    fn finds_problems() {
+        let map = Map::from_str(COURSE).unwrap();
+        let problems = check(&map);
+
+        let fixes = problems
+            .iter()
+            .filter_map(|p| p.fix.clone())
+            .collect::<Vec<_>>();
+        assert!(fixes.contains(&Fix::KeepFinishLine { keep: 0 }));
+        assert!(fixes.contains(&Fix::RenumberStarPosts));
+
+        // starts 1 and 2 overlap
+        assert!(problems
+            .iter()
+            .any(|p| p.objects.things == [0, 1].into() && p.severity == Severity::Warning));
+        // nothing near the finish line
+        assert!(problems.iter().any(|p| p.message.contains("no waypoints")));
+        assert_eq!(problems[0].severity, Severity::Error);
+    }
+
+    #[test]
+    fn fixes() {
+        let mut map = Map::from_str(COURSE).unwrap();
+
+        Fix::KeepFinishLine { keep: 0 }.apply(&mut map);
+        Fix::RenumberStarPosts.apply(&mut map);
+
+        let problems = check(&map);
+        assert!(problems.iter().all(|p| p.fix.is_none()));
+        assert_eq!(finish_lines(&map), vec![0]);
+        assert_eq!(star_posts(&map), vec![(1, 2), (2, 3)]);
+    }
+}
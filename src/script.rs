@@ -0,0 +1,439 @@
+//! Map scripting with [`rhai`].
+//!
+//! Scripts are given a `map` variable that can be used to look at and change
+//! the map. Every object in the map has its common fields as properties, and
+//! any field can be accessed by indexing with its name in the map:
+//!
+//! ```rhai
+//! // raise every sector tagged 5 by 64 units
+//! for sector in map.sectors {
+//!     if sector["id"] == 5 {
+//!         sector.height_floor += 64;
+//!     }
+//! }
+//! ```
+//!
+//! Setting a field the namespace of the map doesn't have is an error, see
+//! [`Map::set_field`](crate::map::Map::set_field).
+
+use std::cell::RefCell;
+use std::marker::PhantomData;
+use std::rc::Rc;
+
+use rhai::{Array, Dynamic, Engine, EvalAltResult, ImmutableString, Scope, FLOAT, INT};
+
+use crate::format::udmf::Value;
+use crate::map::namespace::Object;
+use crate::map::{Extras, LineDef, Map, Sector, SideDef, Thing, Vertex};
+
+pub use rhai::EvalAltResult as Error;
+
+type SharedMap = Rc<RefCell<Map>>;
+
+/// Runs a script on a map.
+///
+/// Anything the script prints is passed to `print`. If the script fails, the
+/// map is left untouched.
+pub fn run(
+    source: &str,
+    map: &mut Map,
+    print: impl Fn(&str) + 'static,
+) -> Result<(), Box<EvalAltResult>> {
+    let shared = Rc::new(RefCell::new(map.clone()));
+
+    let mut engine = engine();
+    engine.on_print(print);
+
+    let mut scope = Scope::new();
+    scope.push("map", ScriptMap(shared.clone()));
+
+    engine.run_with_scope(&mut scope, source)?;
+
+    *map = shared.take();
+    Ok(())
+}
+
+/// Creates an [`Engine`] with the map API registered.
+pub fn engine() -> Engine {
+    let mut engine = Engine::new();
+
+    engine
+        .register_type_with_name::<ScriptMap>("Map")
+        .register_get("namespace", |map: &mut ScriptMap| {
+            ImmutableString::from(map.0.borrow().namespace.as_str())
+        })
+        .register_get("things", ScriptMap::objects::<Thing>)
+        .register_get("vertices", ScriptMap::objects::<Vertex>)
+        .register_get("linedefs", ScriptMap::objects::<LineDef>)
+        .register_get("sidedefs", ScriptMap::objects::<SideDef>)
+        .register_get("sectors", ScriptMap::objects::<Sector>)
+        .register_fn("add_thing", ScriptMap::add_thing)
+        .register_fn(
+            "add_thing",
+            |map: &mut ScriptMap, kind: INT, x: INT, y: INT| {
+                map.add_thing(kind, x as FLOAT, y as FLOAT)
+            },
+        );
+
+    register_object::<Thing>(&mut engine);
+    float_field::<Thing>(&mut engine, "x", |t| t.x, |t, v| t.x = v);
+    float_field::<Thing>(&mut engine, "y", |t| t.y, |t, v| t.y = v);
+    int_field::<Thing>(&mut engine, "angle", |t| t.angle, |t, v| t.angle = v);
+    int_field::<Thing>(&mut engine, "type", |t| t.kind, |t, v| t.kind = v);
+
+    register_object::<Vertex>(&mut engine);
+    float_field::<Vertex>(&mut engine, "x", |v| v.x, |v, x| v.x = x);
+    float_field::<Vertex>(&mut engine, "y", |v| v.y, |v, y| v.y = y);
+
+    register_object::<LineDef>(&mut engine);
+    int_field::<LineDef>(&mut engine, "v1", |l| l.v1, |l, v| l.v1 = v);
+    int_field::<LineDef>(&mut engine, "v2", |l| l.v2, |l, v| l.v2 = v);
+    int_field::<LineDef>(
+        &mut engine,
+        "side_front",
+        |l| l.side_front,
+        |l, v| l.side_front = v,
+    );
+    int_field::<LineDef>(
+        &mut engine,
+        "side_back",
+        |l| l.side_back.unwrap_or(-1),
+        |l, v| l.side_back = (v >= 0).then_some(v),
+    );
+    bool_field::<LineDef>(
+        &mut engine,
+        "two_sided",
+        |l| l.two_sided,
+        |l, v| l.two_sided = v,
+    );
+
+    register_object::<SideDef>(&mut engine);
+    int_field::<SideDef>(
+        &mut engine,
+        "offset_x",
+        |s| s.offset_x,
+        |s, v| s.offset_x = v,
+    );
+    int_field::<SideDef>(
+        &mut engine,
+        "offset_y",
+        |s| s.offset_y,
+        |s, v| s.offset_y = v,
+    );
+    int_field::<SideDef>(&mut engine, "sector", |s| s.sector, |s, v| s.sector = v);
+
+    register_object::<Sector>(&mut engine);
+    int_field::<Sector>(
+        &mut engine,
+        "height_floor",
+        |s| s.height_floor,
+        |s, v| s.height_floor = v,
+    );
+    int_field::<Sector>(
+        &mut engine,
+        "height_ceiling",
+        |s| s.height_ceiling,
+        |s, v| s.height_ceiling = v,
+    );
+    string_field::<Sector>(
+        &mut engine,
+        "texture_floor",
+        |s| &s.texture_floor,
+        |s, v| s.texture_floor = v,
+    );
+    string_field::<Sector>(
+        &mut engine,
+        "texture_ceiling",
+        |s| &s.texture_ceiling,
+        |s, v| s.texture_ceiling = v,
+    );
+
+    engine
+}
+
+/// The map, as seen by scripts.
+#[derive(Clone)]
+struct ScriptMap(SharedMap);
+
+impl ScriptMap {
+    fn objects<T: MapObject>(&mut self) -> Array {
+        (0..T::list(&self.0.borrow()).len())
+            .map(|idx| Dynamic::from(ObjectRef::<T>::new(self.0.clone(), idx)))
+            .collect()
+    }
+
+    fn add_thing(&mut self, kind: INT, x: FLOAT, y: FLOAT) -> ObjectRef<Thing> {
+        let mut map = self.0.borrow_mut();
+
+        map.things.push(Thing {
+            x: x as f32,
+            y: y as f32,
+            height: None,
+            angle: 0,
+            kind: kind as i32,
+            extras: Extras::default(),
+        });
+
+        ObjectRef::new(self.0.clone(), map.things.len() - 1)
+    }
+}
+
+/// An object that lives in a list in the map.
+trait MapObject: Sized + 'static {
+    /// The name of the object, as seen by scripts.
+    const NAME: &'static str;
+
+    /// The kind of object, for looking up its fields.
+    const OBJECT: Object;
+
+    fn list(map: &Map) -> &Vec<Self>;
+
+    fn list_mut(map: &mut Map) -> &mut Vec<Self>;
+}
+
+macro_rules! impl_map_object {
+    ($ty:ident, $list:ident, $name:literal) => {
+        impl MapObject for $ty {
+            const NAME: &'static str = $name;
+
+            const OBJECT: Object = Object::$ty;
+
+            fn list(map: &Map) -> &Vec<Self> {
+                &map.$list
+            }
+
+            fn list_mut(map: &mut Map) -> &mut Vec<Self> {
+                &mut map.$list
+            }
+        }
+    };
+}
+
+impl_map_object!(Thing, things, "Thing");
+impl_map_object!(Vertex, vertices, "Vertex");
+impl_map_object!(LineDef, linedefs, "LineDef");
+impl_map_object!(SideDef, sidedefs, "SideDef");
+impl_map_object!(Sector, sectors, "Sector");
+
+/// A reference to an object in the map.
+struct ObjectRef<T> {
+    map: SharedMap,
+    idx: usize,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<T: MapObject> ObjectRef<T> {
+    fn new(map: SharedMap, idx: usize) -> ObjectRef<T> {
+        ObjectRef {
+            map,
+            idx,
+            _marker: PhantomData,
+        }
+    }
+
+    fn with<R>(&self, f: impl FnOnce(&T) -> R) -> Result<R, Box<EvalAltResult>> {
+        T::list(&self.map.borrow())
+            .get(self.idx)
+            .map(f)
+            .ok_or_else(|| self.missing())
+    }
+
+    fn with_mut<R>(&self, f: impl FnOnce(&mut T) -> R) -> Result<R, Box<EvalAltResult>> {
+        T::list_mut(&mut self.map.borrow_mut())
+            .get_mut(self.idx)
+            .map(f)
+            .ok_or_else(|| self.missing())
+    }
+
+    fn missing(&self) -> Box<EvalAltResult> {
+        format!("{} {} does not exist", T::NAME, self.idx).into()
+    }
+}
+
+impl<T> Clone for ObjectRef<T> {
+    fn clone(&self) -> Self {
+        ObjectRef {
+            map: self.map.clone(),
+            idx: self.idx,
+            _marker: PhantomData,
+        }
+    }
+}
+
+/// Registers the common API of an object.
+fn register_object<T: MapObject>(engine: &mut Engine) {
+    engine
+        .register_type_with_name::<ObjectRef<T>>(T::NAME)
+        .register_get("index", |obj: &mut ObjectRef<T>| obj.idx as INT)
+        .register_fn("to_string", |obj: &mut ObjectRef<T>| {
+            format!("{} {}", T::NAME, obj.idx)
+        })
+        .register_indexer_get_set(
+            |obj: &mut ObjectRef<T>, key: ImmutableString| {
+                obj.with(|_| ())?;
+
+                Ok(obj
+                    .map
+                    .borrow()
+                    .field(T::OBJECT, obj.idx, &key)
+                    .map(|value| value_to_dynamic(&value))
+                    .unwrap_or(Dynamic::UNIT))
+            },
+            |obj: &mut ObjectRef<T>, key: ImmutableString, value: Dynamic| {
+                let value = dynamic_to_value(value)?;
+
+                obj.map
+                    .borrow_mut()
+                    .set_field(T::OBJECT, obj.idx, &key, value)
+                    .map_err(|err| err.to_string().into())
+            },
+        );
+}
+
+fn float_field<T: MapObject>(
+    engine: &mut Engine,
+    name: &str,
+    get: fn(&T) -> f32,
+    set: fn(&mut T, f32),
+) {
+    engine
+        .register_get(name, move |obj: &mut ObjectRef<T>| {
+            obj.with(|t| get(t) as FLOAT)
+        })
+        .register_set(name, move |obj: &mut ObjectRef<T>, value: FLOAT| {
+            obj.with_mut(|t| set(t, value as f32))
+        })
+        .register_set(name, move |obj: &mut ObjectRef<T>, value: INT| {
+            obj.with_mut(|t| set(t, value as f32))
+        });
+}
+
+fn int_field<T: MapObject>(
+    engine: &mut Engine,
+    name: &str,
+    get: fn(&T) -> i32,
+    set: fn(&mut T, i32),
+) {
+    engine.register_get_set(
+        name,
+        move |obj: &mut ObjectRef<T>| obj.with(|t| get(t) as INT),
+        move |obj: &mut ObjectRef<T>, value: INT| {
+            let value = i32::try_from(value)
+                .map_err(|_| Box::<EvalAltResult>::from(format!("{} is out of range", value)))?;
+            obj.with_mut(|t| set(t, value))
+        },
+    );
+}
+
+fn bool_field<T: MapObject>(
+    engine: &mut Engine,
+    name: &str,
+    get: fn(&T) -> bool,
+    set: fn(&mut T, bool),
+) {
+    engine.register_get_set(
+        name,
+        move |obj: &mut ObjectRef<T>| obj.with(get),
+        move |obj: &mut ObjectRef<T>, value: bool| obj.with_mut(|t| set(t, value)),
+    );
+}
+
+fn string_field<T: MapObject>(
+    engine: &mut Engine,
+    name: &str,
+    get: fn(&T) -> &String,
+    set: fn(&mut T, String),
+) {
+    engine.register_get_set(
+        name,
+        move |obj: &mut ObjectRef<T>| obj.with(|t| ImmutableString::from(get(t).as_str())),
+        move |obj: &mut ObjectRef<T>, value: ImmutableString| {
+            obj.with_mut(|t| set(t, value.to_string()))
+        },
+    );
+}
+
+fn value_to_dynamic(value: &Value) -> Dynamic {
+    match value {
+        Value::Boolean(b) => Dynamic::from_bool(*b),
+        Value::Integer(int) => Dynamic::from_int(*int as INT),
+        Value::Float(fl) => Dynamic::from_float(*fl as FLOAT),
+        Value::String(s) => Dynamic::from(ImmutableString::from(s.as_str())),
+        Value::Nil => Dynamic::UNIT,
+    }
+}
+
+fn dynamic_to_value(value: Dynamic) -> Result<Value, Box<EvalAltResult>> {
+    if value.is_unit() {
+        Ok(Value::Nil)
+    } else if let Some(b) = value.clone().try_cast::<bool>() {
+        Ok(Value::Boolean(b))
+    } else if let Some(int) = value.clone().try_cast::<INT>() {
+        i32::try_from(int)
+            .map(Value::Integer)
+            .map_err(|_| format!("{} is out of range", int).into())
+    } else if let Some(fl) = value.clone().try_cast::<FLOAT>() {
+        Ok(Value::Float(fl as f32))
+    } else if let Some(s) = value.clone().try_cast::<ImmutableString>() {
+        Ok(Value::String(s.to_string()))
+    } else {
+        Err(format!("cannot store {} in a map", value.type_name()).into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MAP: &str = r#"
+    namespace = "ringracers";
+    version = 1;
+
+    thing { x = 0.0; y = 0.0; angle = 0; type = 1; }
+
+    sector { texturefloor = "FLOOR"; textureceiling = "CEIL"; id = 5; }
+    sector { texturefloor = "FLOOR"; textureceiling = "CEIL"; id = 2; }
+    "#;
+
+    #[test]
+    fn raise_tagged_sectors() {
+        let mut map = Map::from_str(MAP).unwrap();
+
+        run(
+            r#"
+            for sector in map.sectors {
+                if sector["id"] == 5 {
+                    sector.height_floor += 64;
+                    sector["texturefloor"] = "LAVA";
+                }
+            }
+            "#,
+            &mut map,
+            |_| (),
+        )
+        .unwrap();
+
+        assert_eq!(map.sectors[0].height_floor, 64);
+        assert_eq!(map.sectors[0].texture_floor, "LAVA");
+        assert_eq!(map.sectors[1].height_floor, 0);
+    }
+
+    #[test]
+    fn failed_script_keeps_map() {
+        let mut map = Map::from_str(MAP).unwrap();
+
+        let result = run(
+            r#"
+            map.things[0].x = 128;
+            map.add_thing(1, 0, 0);
+            throw "oops";
+            "#,
+            &mut map,
+            |_| (),
+        );
+
+        assert!(result.is_err());
+        assert_eq!(map.things.len(), 1);
+        assert_eq!(map.things[0].x, 0.0);
+    }
+}
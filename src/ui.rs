@@ -1,131 +0,0 @@
-//! UI details with egui.
-
-use bevy::prelude::*;
-use bevy::render::camera::{CameraProjection, Viewport};
-use bevy::window::PrimaryWindow;
-
-use bevy_egui::{EguiContext, EguiSet};
-
-use egui_dock::{DockArea, DockState, NodeIndex, Style};
-
-use crate::editor::EditorCamera;
-
-/// `egui` UI plugin.
-pub struct UiPlugin;
-
-impl Plugin for UiPlugin {
-    fn build(&self, app: &mut App) {
-        app.insert_resource(UiState::new()).add_systems(
-            PostUpdate,
-            (show_ui_system, update_camera_viewport)
-                .chain()
-                .before(EguiSet::ProcessOutput)
-                .before(bevy::transform::TransformSystem::TransformPropagate),
-        );
-    }
-}
-
-#[derive(Resource)]
-struct UiState {
-    state: DockState<EguiWindow>,
-    viewport_rect: egui::Rect,
-}
-
-impl UiState {
-    pub fn new() -> Self {
-        let mut state = DockState::new(vec![EguiWindow::View]);
-        let tree = state.main_surface_mut();
-        let [_game, _inspector] =
-            tree.split_right(NodeIndex::root(), 0.75, vec![EguiWindow::Inspector]);
-
-        Self {
-            state,
-            viewport_rect: egui::Rect::NOTHING,
-        }
-    }
-
-    fn ui(&mut self, world: &mut World, ctx: &mut egui::Context) {
-        let mut tab_viewer = TabViewer {
-            world,
-            viewport_rect: &mut self.viewport_rect,
-        };
-        DockArea::new(&mut self.state)
-            .style(Style::from_egui(ctx.style().as_ref()))
-            .show(ctx, &mut tab_viewer);
-    }
-}
-
-#[derive(Debug)]
-enum EguiWindow {
-    View,
-    Inspector,
-}
-
-struct TabViewer<'a> {
-    world: &'a mut World,
-    viewport_rect: &'a mut egui::Rect,
-}
-
-impl egui_dock::TabViewer for TabViewer<'_> {
-    type Tab = EguiWindow;
-
-    fn ui(&mut self, ui: &mut egui_dock::egui::Ui, window: &mut Self::Tab) {
-        match window {
-            EguiWindow::View => {
-                *self.viewport_rect = ui.clip_rect();
-            }
-            EguiWindow::Inspector => {
-                // do nothing
-                // TODO: do something
-            }
-        }
-    }
-
-    fn title(&mut self, window: &mut Self::Tab) -> egui::WidgetText {
-        format!("{window:?}").into()
-    }
-
-    fn clear_background(&self, window: &Self::Tab) -> bool {
-        !matches!(window, EguiWindow::View)
-    }
-}
-
-/// Starts showing the UI for the frame.
-fn show_ui_system(world: &mut World) {
-    let Ok(egui_context) = world
-        .query_filtered::<&mut EguiContext, With<PrimaryWindow>>()
-        .get_single(world)
-    else {
-        return;
-    };
-    let mut egui_context = egui_context.clone();
-
-    world.resource_scope::<UiState, _>(|world, mut ui_state| {
-        ui_state.ui(world, egui_context.get_mut())
-    });
-}
-
-/// Has the camera only render the visible area.
-fn update_camera_viewport(
-    ui_state: Res<UiState>,
-    primary_window: Query<&mut Window, With<PrimaryWindow>>,
-    egui_settings: Res<bevy_egui::EguiSettings>,
-    mut cameras: Query<&mut Camera, With<EditorCamera>>,
-) {
-    let mut cam = cameras.single_mut();
-
-    let Ok(window) = primary_window.get_single() else {
-        return;
-    };
-
-    let scale_factor = window.scale_factor() * egui_settings.scale_factor;
-
-    let viewport_pos = ui_state.viewport_rect.left_top().to_vec2() * scale_factor;
-    let viewport_size = ui_state.viewport_rect.size() * scale_factor;
-
-    cam.viewport = Some(Viewport {
-        physical_position: UVec2::new(viewport_pos.x as u32, viewport_pos.y as u32),
-        physical_size: UVec2::new(viewport_size.x as u32, viewport_size.y as u32),
-        depth: 0.0..1.0,
-    });
-}
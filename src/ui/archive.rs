@@ -0,0 +1,235 @@
+//! Archive browser tab.
+
+use std::fs::OpenOptions;
+use std::io::{self, Write};
+use std::ops::Range;
+
+use bevy::prelude::*;
+
+use crate::editor::archive::{Archive, OpenArchiveMap};
+use crate::format::wad::Section;
+
+use super::Tab;
+
+/// Lists the lumps of the [`Archive`], grouped by their markers.
+#[derive(Default)]
+pub struct ArchiveTab {
+    /// The lump being renamed, and its new name.
+    renaming: Option<(usize, String)>,
+}
+
+/// Something to do to the archive, once it's done being shown.
+enum Action {
+    OpenMap(String),
+    Select(usize),
+    Extract(usize),
+    Rename(usize, String),
+    Delete(usize),
+}
+
+impl Tab for ArchiveTab {
+    fn title(&self) -> egui::WidgetText {
+        "Archive".into()
+    }
+
+    fn ui(&mut self, ui: &mut egui::Ui, world: &mut World) {
+        let Some(archive) = world.get_resource::<Archive>() else {
+            ui.label("No archive loaded.");
+            return;
+        };
+
+        let mut action = None;
+
+        ui.horizontal(|ui| {
+            ui.label(archive.path().display().to_string());
+            if archive.is_read_only() {
+                ui.weak("(read-only)");
+            }
+            if archive.wad.is_modified() {
+                ui.weak("(not saved)");
+            }
+        });
+        if let Some(status) = &archive.status {
+            ui.weak(status);
+        }
+
+        ui.separator();
+
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            for section in archive.wad.sections() {
+                match section {
+                    Section::Map { name, lumps } => {
+                        let open = archive.map.as_ref() == Some(&name);
+                        let title = if open {
+                            format!("{} (open)", name)
+                        } else {
+                            name.clone()
+                        };
+
+                        egui::CollapsingHeader::new(title)
+                            .id_source(("map", lumps.start))
+                            .show(ui, |ui| self.lumps(ui, archive, lumps, &mut action))
+                            .header_response
+                            .context_menu(|ui| {
+                                if ui
+                                    .add_enabled(!open, egui::Button::new("Open map"))
+                                    .clicked()
+                                {
+                                    action = Some(Action::OpenMap(name.clone()));
+                                    ui.close_menu();
+                                }
+                            });
+                    }
+                    Section::Namespace { name, lumps } => {
+                        egui::CollapsingHeader::new(name)
+                            .id_source(("namespace", lumps.start))
+                            .show(ui, |ui| self.lumps(ui, archive, lumps, &mut action));
+                    }
+                    Section::Lump(idx) => self.lumps(ui, archive, idx..idx + 1, &mut action),
+                }
+            }
+        });
+
+        match action {
+            Some(Action::OpenMap(name)) => {
+                world.send_event(OpenArchiveMap(name));
+            }
+            Some(action) => {
+                let mut archive = world.resource_mut::<Archive>();
+                apply(&mut archive, action);
+            }
+            None => (),
+        }
+    }
+}
+
+impl ArchiveTab {
+    /// Shows a row for each lump.
+    fn lumps(
+        &mut self,
+        ui: &mut egui::Ui,
+        archive: &Archive,
+        lumps: Range<usize>,
+        action: &mut Option<Action>,
+    ) {
+        for idx in lumps {
+            let Some(lump) = archive.wad.lump_at(idx) else {
+                continue;
+            };
+
+            if let Some((renaming, name)) = &mut self.renaming {
+                if *renaming == idx {
+                    ui.horizontal(|ui| {
+                        let edit = ui.text_edit_singleline(name);
+                        let enter =
+                            edit.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+
+                        if ui.button("Rename").clicked() || enter {
+                            *action = Some(Action::Rename(idx, name.trim().to_uppercase()));
+                        }
+                    });
+
+                    if matches!(action, Some(Action::Rename(..))) {
+                        self.renaming = None;
+                    }
+                    continue;
+                }
+            }
+
+            let response = ui
+                .selectable_label(archive.selected == Some(idx), lump.name())
+                .on_hover_text(format!("{} bytes", lump.data().len()));
+
+            if response.clicked() {
+                *action = Some(Action::Select(idx));
+            }
+
+            response.context_menu(|ui| {
+                for (text, lump_action) in [
+                    ("View hex", Action::Select(idx)),
+                    ("Extract", Action::Extract(idx)),
+                    ("Delete", Action::Delete(idx)),
+                ] {
+                    if ui.button(text).clicked() {
+                        *action = Some(lump_action);
+                        ui.close_menu();
+                    }
+                }
+
+                if ui.button("Rename").clicked() {
+                    self.renaming = Some((idx, lump.name().to_string()));
+                    ui.close_menu();
+                }
+            });
+        }
+    }
+}
+
+fn apply(archive: &mut Archive, action: Action) {
+    if archive.is_saving() && matches!(action, Action::Rename(..) | Action::Delete(_)) {
+        archive.status = Some("Wait for the save to finish to change the archive".into());
+        return;
+    }
+
+    match action {
+        Action::OpenMap(_) => (),
+        Action::Select(idx) => {
+            archive.selected = Some(idx);
+        }
+        Action::Extract(idx) => {
+            let Some(lump) = archive.wad.lump_at(idx) else {
+                return;
+            };
+
+            // next to the archive, like other tools do
+            let path = archive
+                .path()
+                .with_file_name(format!("{}.lmp", lump.name()));
+
+            // never replace a file that is already there
+            let written = OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .open(&path)
+                .and_then(|mut file| file.write_all(lump.data()));
+
+            archive.status = Some(match written {
+                Ok(()) => format!("Extracted to {}", path.display()),
+                Err(err) if err.kind() == io::ErrorKind::AlreadyExists => {
+                    format!("Not extracted, {} already exists", path.display())
+                }
+                Err(err) => format!("Failed to extract: {}", err),
+            });
+        }
+        Action::Rename(idx, name) => {
+            let Some(mut lump) = archive.wad.lump_at_mut(idx) else {
+                return;
+            };
+            let old_name = lump.name().to_string();
+
+            if let Err(err) = lump.rename(name.clone()) {
+                archive.status = Some(format!("Failed to rename: {}", err));
+                return;
+            }
+
+            // follow the open map if its marker was renamed
+            if archive.map.as_deref() == Some(old_name.as_str())
+                && archive.wad.map_lump_index(&name, "TEXTMAP").is_some()
+            {
+                archive.map = Some(name);
+            }
+        }
+        Action::Delete(idx) => {
+            if idx >= archive.wad.lumps().count() {
+                return;
+            }
+
+            archive.wad.remove(idx);
+            archive.selected = match archive.selected {
+                Some(selected) if selected == idx => None,
+                Some(selected) if selected > idx => Some(selected - 1),
+                selected => selected,
+            };
+        }
+    }
+}
@@ -0,0 +1,96 @@
+//! Script console tab.
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use bevy::prelude::*;
+
+use egui::Color32;
+
+use crate::editor::{Editor, Selection};
+use crate::script;
+
+use super::Tab;
+
+/// Runs scripts on the open map.
+#[derive(Default)]
+pub struct Console {
+    source: String,
+    output: Vec<Line>,
+}
+
+enum Line {
+    Print(String),
+    Error(String),
+}
+
+impl Tab for Console {
+    fn title(&self) -> egui::WidgetText {
+        "Console".into()
+    }
+
+    fn ui(&mut self, ui: &mut egui::Ui, world: &mut World) {
+        ui.horizontal(|ui| {
+            if ui.button("Run").clicked() {
+                self.run(world);
+            }
+
+            if ui.button("Clear").clicked() {
+                self.output.clear();
+            }
+        });
+
+        ui.add(
+            egui::TextEdit::multiline(&mut self.source)
+                .code_editor()
+                .desired_rows(8)
+                .desired_width(f32::INFINITY),
+        );
+
+        ui.separator();
+
+        egui::ScrollArea::vertical()
+            .stick_to_bottom(true)
+            .show(ui, |ui| {
+                for line in self.output.iter() {
+                    match line {
+                        Line::Print(s) => ui.monospace(s),
+                        Line::Error(s) => ui.colored_label(Color32::RED, s),
+                    };
+                }
+            });
+    }
+}
+
+impl Console {
+    fn run(&mut self, world: &mut World) {
+        let mut editors = world.query::<(&mut Editor, &mut Selection)>();
+        let Ok((mut editor, mut selection)) = editors.get_single_mut(world) else {
+            return;
+        };
+
+        // only touch the editor if the script succeeds
+        let printed = Rc::new(RefCell::new(Vec::new()));
+        let result = {
+            let printed = printed.clone();
+            let mut map = editor.map().clone();
+
+            script::run(&self.source, &mut map, move |s| {
+                printed.borrow_mut().push(s.to_string())
+            })
+            .map(|()| map)
+        };
+
+        self.output
+            .extend(printed.take().into_iter().map(Line::Print));
+
+        match result {
+            Ok(map) => {
+                // indices may not point to the same objects anymore
+                selection.clear();
+                *editor.map_mut() = map;
+            }
+            Err(err) => self.output.push(Line::Error(err.to_string())),
+        }
+    }
+}
@@ -0,0 +1,196 @@
+//! Editing the extra fields of objects.
+
+use std::hash::Hash;
+
+use crate::format::udmf::{is_identifier, Type, Value};
+use crate::map::group::EDITOR_PREFIX;
+use crate::map::namespace::{Namespace, Object};
+use crate::map::Extras;
+
+/// The types a field can be given.
+///
+/// Nil fields aren't written when saving, so they can't be picked.
+const TYPES: [Type; 4] = [Type::Boolean, Type::Integer, Type::Float, Type::String];
+
+/// Edits the [`Extras`] of an object.
+///
+/// Each field gets an editor for its type, and can be converted to other
+/// types. Fields the editor keeps for itself, like groups, are left out.
+pub struct ExtrasEditor {
+    /// The key of the field being added.
+    new_key: String,
+    /// The type of the field being added.
+    new_type: Type,
+}
+
+impl Default for ExtrasEditor {
+    fn default() -> ExtrasEditor {
+        ExtrasEditor {
+            new_key: String::new(),
+            new_type: Type::Integer,
+        }
+    }
+}
+
+impl ExtrasEditor {
+    /// Shows the fields of `extras`, returning the edited fields if anything
+    /// changed.
+    ///
+    /// Fields that aren't in `namespace`, or have the wrong type for it, are
+    /// marked. Unknown namespaces allow anything.
+    pub fn show(
+        &mut self,
+        ui: &mut egui::Ui,
+        id: impl Hash,
+        extras: &Extras,
+        namespace: Option<Namespace>,
+        object: Object,
+    ) -> Option<Extras> {
+        let allowed = |field: &str| namespace.is_none_or(|ns| ns.allows(object, field));
+        let expected = |field: &str| namespace.and_then(|ns| ns.field(object, field));
+
+        let id = egui::Id::new(id);
+        let mut edited = extras.clone();
+        let mut changed = false;
+        let mut removed = None;
+
+        egui::Grid::new(id).striped(true).show(ui, |ui| {
+            for (key, value) in edited
+                .iter_mut()
+                .filter(|(key, _)| !key.starts_with(EDITOR_PREFIX))
+            {
+                let warning = if !allowed(key) {
+                    Some("Not a field in the namespace of the map".to_owned())
+                } else {
+                    expected(key)
+                        .filter(|field| !field.fits(value))
+                        .map(|field| {
+                            format!(
+                                "Should be a {} in the namespace of the map",
+                                field.ty.name()
+                            )
+                        })
+                };
+
+                match warning {
+                    Some(warning) => {
+                        ui.colored_label(ui.visuals().warn_fg_color, format!("⚠ {}", key))
+                            .on_hover_text(warning);
+                    }
+                    None => {
+                        ui.label(key);
+                    }
+                }
+
+                changed |= value_editor(ui, value);
+                changed |= type_picker(ui, id.with(key), value);
+
+                if ui.small_button("x").on_hover_text("Remove field").clicked() {
+                    removed = Some(key.to_owned());
+                }
+                ui.end_row();
+            }
+        });
+
+        if let Some(key) = removed {
+            edited.remove(&key);
+            changed = true;
+        }
+
+        ui.horizontal(|ui| {
+            ui.add(
+                egui::TextEdit::singleline(&mut self.new_key)
+                    .hint_text("New field")
+                    .desired_width(120.0),
+            );
+
+            egui::ComboBox::from_id_source(id.with("new type"))
+                .selected_text(self.new_type.name())
+                .show_ui(ui, |ui| {
+                    for ty in TYPES {
+                        ui.selectable_value(&mut self.new_type, ty, ty.name());
+                    }
+                });
+
+            let key = self.new_key.trim();
+            let valid = is_identifier(key) && !edited.contains_key(key);
+            if ui.add_enabled(valid, egui::Button::new("Add")).clicked() {
+                edited.insert(key, self.new_type.default_value());
+                self.new_key.clear();
+                changed = true;
+            }
+        });
+
+        let key = self.new_key.trim();
+        if !key.is_empty() && !is_identifier(key) {
+            ui.colored_label(
+                ui.visuals().error_fg_color,
+                "Fields start with a letter or underscore, followed by letters, digits and underscores",
+            );
+        } else if !key.is_empty() && !allowed(key) {
+            ui.colored_label(
+                ui.visuals().warn_fg_color,
+                "Not a field in the namespace of the map",
+            );
+        } else if let Some(field) = expected(key).filter(|field| field.ty != self.new_type) {
+            ui.colored_label(
+                ui.visuals().warn_fg_color,
+                format!(
+                    "Should be a {} in the namespace of the map",
+                    field.ty.name()
+                ),
+            );
+        }
+
+        changed.then_some(edited)
+    }
+}
+
+/// Shows an editor for `value`, returning whether it was edited.
+fn value_editor(ui: &mut egui::Ui, value: &mut Value) -> bool {
+    match value {
+        Value::Boolean(v) => ui.checkbox(v, "").changed(),
+        Value::Integer(v) => ui.add(egui::DragValue::new(v)).changed(),
+        Value::Float(v) => ui.add(egui::DragValue::new(v).speed(0.1)).changed(),
+        Value::String(v) => ui
+            .add(egui::TextEdit::singleline(v).desired_width(120.0))
+            .changed(),
+        Value::Nil => {
+            ui.weak("nil");
+            false
+        }
+    }
+}
+
+/// Shows the type of `value`, converting it when another type is picked.
+///
+/// Types the value can't be converted to can't be picked. Returns whether
+/// the value was converted.
+fn type_picker(ui: &mut egui::Ui, id: egui::Id, value: &mut Value) -> bool {
+    let current = value.value_type();
+    let mut converted = None;
+
+    egui::ComboBox::from_id_source(id)
+        .selected_text(current.name())
+        .show_ui(ui, |ui| {
+            for ty in TYPES {
+                let coerced = value.coerce(ty);
+                let response = ui.add_enabled(
+                    coerced.is_some(),
+                    egui::SelectableLabel::new(ty == current, ty.name()),
+                );
+
+                if response.clicked() && ty != current {
+                    converted = coerced;
+                }
+            }
+        });
+
+    match converted {
+        Some(new) => {
+            *value = new;
+            true
+        }
+        None => false,
+    }
+}
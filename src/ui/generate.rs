@@ -0,0 +1,115 @@
+//! Generators tab.
+
+use bevy::prelude::*;
+
+use crate::editor::{Editor, Selection};
+use crate::map::generate::Side;
+
+use super::Tab;
+
+/// Builds stairs and slopes out of the selection.
+pub struct GeneratorsTab {
+    steps: usize,
+    side: Side,
+    floor: bool,
+    ceiling: bool,
+    status: Option<String>,
+}
+
+impl Default for GeneratorsTab {
+    fn default() -> GeneratorsTab {
+        GeneratorsTab {
+            steps: 4,
+            side: Side::Front,
+            floor: true,
+            ceiling: false,
+            status: None,
+        }
+    }
+}
+
+impl Tab for GeneratorsTab {
+    fn title(&self) -> egui::WidgetText {
+        "Generators".into()
+    }
+
+    fn ui(&mut self, ui: &mut egui::Ui, world: &mut World) {
+        let mut editors = world.query::<(&mut Editor, &mut Selection)>();
+        let Ok((mut editor, mut selection)) = editors.get_single_mut(world) else {
+            ui.label("No map loaded.");
+            return;
+        };
+
+        ui.strong("Stairs");
+        ui.label("Select two sectors to build stairs between their closest walls.");
+
+        ui.horizontal(|ui| {
+            ui.add(
+                egui::DragValue::new(&mut self.steps)
+                    .prefix("Steps: ")
+                    .clamp_range(1..=64),
+            );
+
+            let sectors = match selection.sectors.iter().collect::<Vec<_>>()[..] {
+                [&a, &b] => Some((a, b)),
+                _ => None,
+            };
+
+            if ui
+                .add_enabled(sectors.is_some(), egui::Button::new("Build stairs"))
+                .clicked()
+            {
+                let (a, b) = sectors.unwrap();
+
+                self.status = match editor.map_mut().build_stairs(a, b, self.steps) {
+                    Ok(created) => {
+                        selection.0 = created;
+                        None
+                    }
+                    Err(err) => Some(format!("Failed to build stairs: {}", err)),
+                };
+            }
+        });
+
+        ui.separator();
+
+        ui.strong("Slope");
+        ui.label("Select a two-sided linedef to slope one side up to the other.");
+
+        ui.horizontal(|ui| {
+            ui.selectable_value(&mut self.side, Side::Front, "Front");
+            ui.selectable_value(&mut self.side, Side::Back, "Back");
+            ui.checkbox(&mut self.floor, "Floor");
+            ui.checkbox(&mut self.ceiling, "Ceiling");
+        });
+
+        let linedef = match selection.linedefs.len() {
+            1 => selection.linedefs.first().copied(),
+            _ => None,
+        };
+
+        if ui
+            .add_enabled(
+                linedef.is_some() && (self.floor || self.ceiling),
+                egui::Button::new("Make slope"),
+            )
+            .clicked()
+        {
+            let result = editor.map_mut().slope_from_line(
+                linedef.unwrap(),
+                self.side,
+                self.floor,
+                self.ceiling,
+            );
+
+            self.status = result
+                .err()
+                .map(|err| format!("Failed to make slope: {}", err));
+        }
+
+        if let Some(status) = &self.status {
+            ui.separator();
+            ui.weak(status);
+        }
+    }
+}
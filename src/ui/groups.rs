@@ -0,0 +1,109 @@
+//! Groups tab.
+
+use std::collections::BTreeSet;
+
+use bevy::ecs::component::Tick;
+use bevy::prelude::*;
+
+use crate::editor::group::Groups;
+use crate::editor::{Editor, Selection};
+
+use super::Tab;
+
+/// Lists the groups in the map, to hide and lock them.
+#[derive(Default)]
+pub struct GroupsTab {
+    last_changed: Option<Tick>,
+    groups: BTreeSet<String>,
+    name: String,
+}
+
+impl Tab for GroupsTab {
+    fn title(&self) -> egui::WidgetText {
+        "Groups".into()
+    }
+
+    fn ui(&mut self, ui: &mut egui::Ui, world: &mut World) {
+        let mut editors = world.query::<(Ref<Editor>, &Selection)>();
+        let Ok((editor, selection)) = editors.get_single(world) else {
+            ui.label("No map loaded.");
+            return;
+        };
+
+        if self.last_changed != Some(editor.last_changed()) {
+            self.groups = Editor::map(&editor).groups();
+            self.last_changed = Some(editor.last_changed());
+        }
+
+        let has_selection = !selection.is_empty();
+        let mut assign = None;
+
+        ui.horizontal(|ui| {
+            ui.text_edit_singleline(&mut self.name);
+
+            if ui
+                .add_enabled(
+                    has_selection && !self.name.trim().is_empty(),
+                    egui::Button::new("Add selection"),
+                )
+                .clicked()
+            {
+                assign = Some(Some(self.name.trim().to_string()));
+            }
+
+            if ui
+                .add_enabled(has_selection, egui::Button::new("Ungroup selection"))
+                .clicked()
+            {
+                assign = Some(None);
+            }
+        });
+
+        ui.separator();
+
+        let mut select = None;
+
+        world.resource_scope::<Groups, _>(|_, mut groups| {
+            let mut state = groups.clone();
+
+            egui::Grid::new("groups").striped(true).show(ui, |ui| {
+                for group in self.groups.iter() {
+                    toggle(ui, &mut state.hidden, group, "Hidden");
+                    toggle(ui, &mut state.locked, group, "Locked");
+
+                    if ui.link(group).on_hover_text("Select group").clicked() {
+                        select = Some(group.clone());
+                    }
+                    ui.end_row();
+                }
+            });
+
+            groups.set_if_neq(state);
+        });
+
+        let mut editors = world.query::<(&mut Editor, &mut Selection)>();
+        let Ok((mut editor, mut selection)) = editors.get_single_mut(world) else {
+            return;
+        };
+
+        if let Some(group) = assign {
+            editor.map_mut().set_group(&selection, group.as_deref());
+        }
+
+        if let Some(group) = select {
+            selection.0 = editor.map().in_groups(&BTreeSet::from([group]));
+        }
+    }
+}
+
+fn toggle(ui: &mut egui::Ui, set: &mut BTreeSet<String>, group: &str, text: &str) {
+    let mut checked = set.contains(group);
+
+    if ui.checkbox(&mut checked, text).changed() {
+        if checked {
+            set.insert(group.to_string());
+        } else {
+            set.remove(group);
+        }
+    }
+}
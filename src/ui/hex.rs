@@ -0,0 +1,162 @@
+//! Lump hex viewer tab.
+
+use std::fmt::Write;
+
+use bevy::prelude::*;
+
+use crate::editor::archive::Archive;
+
+use super::Tab;
+
+/// How many bytes are shown on a row.
+const ROW_SIZE: usize = 16;
+
+/// Shows the raw data of a lump in the [`Archive`].
+#[derive(Default)]
+pub struct HexViewer {
+    export_path: String,
+    status: Option<String>,
+}
+
+impl Tab for HexViewer {
+    fn title(&self) -> egui::WidgetText {
+        "Hex".into()
+    }
+
+    fn ui(&mut self, ui: &mut egui::Ui, world: &mut World) {
+        let Some(mut archive) = world.get_resource_mut::<Archive>() else {
+            ui.label("No archive loaded.");
+            return;
+        };
+
+        let mut selected = archive.selected;
+
+        egui::ComboBox::from_label("Lump")
+            .selected_text(
+                match selected.and_then(|idx| archive.wad.lumps().nth(idx)) {
+                    Some(lump) => lump.name().to_string(),
+                    None => "None".to_string(),
+                },
+            )
+            .show_ui(ui, |ui| {
+                for (idx, lump) in archive.wad.lumps().enumerate() {
+                    ui.selectable_value(
+                        &mut selected,
+                        Some(idx),
+                        format!("{}: {}", idx, lump.name()),
+                    );
+                }
+            });
+
+        if selected != archive.selected {
+            archive.selected = selected;
+            self.status = None;
+        }
+
+        let Some(lump) = selected.and_then(|idx| archive.wad.lumps().nth(idx)) else {
+            ui.label("Pick a lump to view.");
+            return;
+        };
+
+        egui::Grid::new("lump").show(ui, |ui| {
+            ui.label("Name");
+            ui.label(lump.name());
+            ui.end_row();
+
+            ui.label("Offset");
+            if lump.is_modified() {
+                ui.label("Not saved yet");
+            } else {
+                ui.label(format!("{0} (0x{0:x})", lump.file_pos()));
+            }
+            ui.end_row();
+
+            ui.label("Size");
+            ui.label(format!("{} bytes", lump.data().len()));
+            ui.end_row();
+        });
+
+        ui.horizontal(|ui| {
+            ui.text_edit_singleline(&mut self.export_path)
+                .on_hover_text("File to export the lump to");
+
+            if ui
+                .add_enabled(
+                    !self.export_path.trim().is_empty(),
+                    egui::Button::new("Export to file"),
+                )
+                .clicked()
+            {
+                let path = self.export_path.trim();
+
+                self.status = Some(match std::fs::write(path, lump.data()) {
+                    Ok(()) => format!("Exported to {}", path),
+                    Err(err) => format!("Failed to export: {}", err),
+                });
+            }
+        });
+
+        if let Some(status) = &self.status {
+            ui.weak(status);
+        }
+
+        ui.separator();
+
+        let data = lump.data();
+        let rows = data.len().div_ceil(ROW_SIZE);
+        let row_height = ui.text_style_height(&egui::TextStyle::Monospace);
+
+        egui::ScrollArea::vertical()
+            .auto_shrink([false, false])
+            .show_rows(ui, row_height, rows, |ui, rows| {
+                for row in rows {
+                    let start = row * ROW_SIZE;
+                    let end = (start + ROW_SIZE).min(data.len());
+
+                    ui.monospace(hex_row(start, &data[start..end]));
+                }
+            });
+    }
+}
+
+/// Formats a row of a hex dump, with the offset, the bytes and their ASCII.
+fn hex_row(offset: usize, bytes: &[u8]) -> String {
+    let mut out = format!("{:08x} ", offset);
+
+    for idx in 0..ROW_SIZE {
+        // split the row in half, like most hex editors
+        if idx % (ROW_SIZE / 2) == 0 {
+            out.push(' ');
+        }
+
+        match bytes.get(idx) {
+            Some(byte) => write!(out, "{:02x} ", byte).unwrap(),
+            None => out.push_str("   "),
+        }
+    }
+
+    out.push_str(" |");
+    out.extend(bytes.iter().map(|&byte| {
+        if byte.is_ascii_graphic() || byte == b' ' {
+            byte as char
+        } else {
+            '.'
+        }
+    }));
+    out.push('|');
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats_rows() {
+        assert_eq!(
+            hex_row(0x10, b"TEXTMAP\0"),
+            "00000010  54 45 58 54 4d 41 50 00                           |TEXTMAP.|"
+        );
+    }
+}
@@ -0,0 +1,142 @@
+//! Inspector tab.
+
+use bevy::prelude::*;
+
+use crate::editor::{Editor, Selection};
+use crate::map;
+use crate::map::namespace::{Namespace, Object};
+
+use super::extras::ExtrasEditor;
+use super::Tab;
+
+/// Shows details about the selection.
+///
+/// The position of a single selected vertex or thing can be typed in here,
+/// and the other fields of any single selected object edited.
+#[derive(Default)]
+pub struct Inspector {
+    extras: ExtrasEditor,
+}
+
+impl Tab for Inspector {
+    fn title(&self) -> egui::WidgetText {
+        "Inspector".into()
+    }
+
+    fn ui(&mut self, ui: &mut egui::Ui, world: &mut World) {
+        let mut editors = world.query::<(&mut Editor, &Selection)>();
+        let Ok((mut editor, selection)) = editors.get_single_mut(world) else {
+            ui.label("No map open.");
+            return;
+        };
+
+        let vertex = single(&selection.vertices);
+        let thing = single(&selection.things);
+        let object = single_object(selection);
+
+        if vertex.is_none() && thing.is_none() && object.is_none() {
+            ui.label(format!(
+                "{} vertices, {} linedefs, {} things selected.",
+                selection.vertices.len(),
+                selection.linedefs.len(),
+                selection.things.len(),
+            ));
+            return;
+        }
+
+        if let Some(idx) = vertex {
+            if let Some(v) = editor.map().vertices.get(idx) {
+                ui.strong(format!("Vertex {}", idx));
+
+                if let Some((x, y)) = position(ui, ("vertex", idx), v.x, v.y) {
+                    let v = &mut editor.map_mut().vertices[idx];
+                    v.x = x;
+                    v.y = y;
+                }
+            }
+        }
+
+        if let Some(idx) = thing {
+            if let Some(thing) = editor.map().things.get(idx) {
+                ui.strong(format!("Thing {}", idx));
+
+                if let Some((x, y)) = position(ui, ("thing", idx), thing.x, thing.y) {
+                    let thing = &mut editor.map_mut().things[idx];
+                    thing.x = x;
+                    thing.y = y;
+                }
+            }
+        }
+
+        let Some((object, idx)) = object else {
+            return;
+        };
+        let Some(extras) = editor.map().extras(object, idx) else {
+            return;
+        };
+
+        if !matches!(object, Object::Vertex | Object::Thing) {
+            ui.strong(format!("{} {}", object.name(), idx));
+        }
+        ui.separator();
+        ui.label("Fields");
+
+        let namespace = Namespace::from_name(&editor.map().namespace);
+        if let Some(extras) = self
+            .extras
+            .show(ui, (object, idx), extras, namespace, object)
+        {
+            if let Some(old) = editor.map_mut().extras_mut(object, idx) {
+                *old = extras;
+            }
+        }
+    }
+}
+
+/// The only selected object, if only one is selected.
+fn single_object(selection: &map::Selection) -> Option<(Object, usize)> {
+    let mut selected = [
+        (Object::Thing, &selection.things),
+        (Object::Vertex, &selection.vertices),
+        (Object::LineDef, &selection.linedefs),
+        (Object::SideDef, &selection.sidedefs),
+        (Object::Sector, &selection.sectors),
+    ]
+    .into_iter()
+    .flat_map(|(object, set)| set.iter().map(move |&idx| (object, idx)));
+
+    match (selected.next(), selected.next()) {
+        (Some(object), None) => Some(object),
+        _ => None,
+    }
+}
+
+/// The only index in `set`, if there is only one.
+fn single(set: &std::collections::BTreeSet<usize>) -> Option<usize> {
+    match set.len() {
+        1 => set.first().copied(),
+        _ => None,
+    }
+}
+
+/// Shows fields for a position, returning the new position if it was edited.
+fn position(
+    ui: &mut egui::Ui,
+    id: impl std::hash::Hash,
+    mut x: f32,
+    mut y: f32,
+) -> Option<(f32, f32)> {
+    let mut changed = false;
+
+    egui::Grid::new(id).show(ui, |ui| {
+        ui.label("X");
+        changed |= ui.add(egui::DragValue::new(&mut x)).changed();
+        ui.end_row();
+
+        ui.label("Y");
+        changed |= ui.add(egui::DragValue::new(&mut y)).changed();
+        ui.end_row();
+    });
+
+    changed.then_some((x, y))
+}
@@ -0,0 +1,86 @@
+//! Map info dialog.
+
+use bevy::ecs::component::Tick;
+use bevy::prelude::*;
+
+use crate::editor::Editor;
+use crate::map::stats::Stats;
+
+/// Shows [`Stats`] about the map.
+#[derive(Default)]
+pub struct MapInfo {
+    pub open: bool,
+    last_changed: Option<Tick>,
+    stats: Stats,
+}
+
+impl MapInfo {
+    /// Shows the dialog, if it is open.
+    pub fn show(&mut self, ctx: &egui::Context, world: &mut World) {
+        if !self.open {
+            return;
+        }
+
+        let mut editors = world.query::<Ref<Editor>>();
+        let Ok(editor) = editors.get_single(world) else {
+            return;
+        };
+
+        if self.last_changed != Some(editor.last_changed()) {
+            self.stats = Editor::map(&editor).stats();
+            self.last_changed = Some(editor.last_changed());
+        }
+
+        let stats = &self.stats;
+
+        egui::Window::new("Map Info")
+            .open(&mut self.open)
+            .resizable(true)
+            .show(ctx, |ui| {
+                egui::Grid::new("counts").striped(true).show(ui, |ui| {
+                    for (name, count) in [
+                        ("Things", stats.things),
+                        ("Vertices", stats.vertices),
+                        ("Linedefs", stats.linedefs),
+                        ("Sidedefs", stats.sidedefs),
+                        ("Sectors", stats.sectors),
+                    ] {
+                        ui.label(name);
+                        ui.label(count.to_string());
+                        ui.end_row();
+                    }
+
+                    if let Some([min_x, min_y, max_x, max_y]) = stats.bounds {
+                        ui.label("Size");
+                        ui.label(format!("{} x {}", max_x - min_x, max_y - min_y));
+                        ui.end_row();
+                    }
+
+                    ui.label("Linedef length");
+                    ui.label(format!("{:.0}", stats.linedef_length));
+                    ui.end_row();
+                });
+
+                ui.columns(2, |columns| {
+                    columns[0].collapsing("Textures", |ui| {
+                        egui::ScrollArea::vertical()
+                            .id_source("textures")
+                            .show(ui, |ui| {
+                                for (texture, count) in stats.textures.iter() {
+                                    ui.label(format!("{}: {}", texture, count));
+                                }
+                            });
+                    });
+                    columns[1].collapsing("Thing types", |ui| {
+                        egui::ScrollArea::vertical()
+                            .id_source("thing types")
+                            .show(ui, |ui| {
+                                for (kind, count) in stats.thing_types.iter() {
+                                    ui.label(format!("{}: {}", kind, count));
+                                }
+                            });
+                    });
+                });
+            });
+    }
+}
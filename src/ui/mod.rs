@@ -0,0 +1,473 @@
+//! UI details with egui.
+
+mod archive;
+#[cfg(feature = "scripting")]
+mod console;
+mod extras;
+mod generate;
+mod groups;
+mod hex;
+mod inspector;
+mod map_info;
+mod nodes;
+mod overview;
+mod painter;
+mod prefabs;
+mod problems;
+mod replace;
+mod save_as;
+mod startup;
+mod tasks;
+mod things;
+mod underlay;
+mod view3d;
+
+use bevy::prelude::*;
+use bevy::render::camera::{CameraProjection, Viewport};
+use bevy::window::PrimaryWindow;
+
+use bevy_egui::{EguiContext, EguiSet};
+
+use egui_dock::{DockArea, DockState, NodeIndex, Style};
+
+use crate::editor::angle::ThingAngles;
+use crate::editor::archive::{save_archive, Archive};
+use crate::editor::command::run_map_command;
+use crate::editor::history::History;
+use crate::editor::nudge::Grid;
+use crate::editor::preview::{run_destructive, OperationPreview};
+use crate::editor::session::{OpenMap, Session};
+use crate::editor::{Cursor, EditModes, Editor, EditorCamera, MapCommands, Selection};
+use crate::map::remove::Destructive;
+use crate::EditorAppExt;
+
+use archive::ArchiveTab;
+#[cfg(feature = "scripting")]
+use console::Console;
+use generate::GeneratorsTab;
+use groups::GroupsTab;
+use hex::HexViewer;
+use inspector::Inspector;
+use map_info::MapInfo;
+use nodes::NodesTab;
+use overview::Overview;
+use painter::PainterTab;
+use prefabs::Prefabs;
+use problems::Problems;
+use replace::FindReplace;
+use save_as::SaveAs;
+use startup::StartupScreen;
+use things::ThingsTab;
+use underlay::UnderlayTab;
+use view3d::View3dTab;
+
+/// `egui` UI plugin.
+pub struct UiPlugin;
+
+impl Plugin for UiPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(UiState::new())
+            .init_resource::<NewTabs>()
+            .add_editor_tab(Prefabs::default())
+            .add_editor_tab(Problems)
+            .add_editor_tab(PainterTab)
+            .add_editor_tab(GroupsTab::default())
+            .add_editor_tab(ThingsTab::default())
+            .add_editor_tab(GeneratorsTab::default())
+            .add_editor_tab(ArchiveTab::default())
+            .add_editor_tab(HexViewer::default())
+            .add_editor_tab(NodesTab)
+            .add_editor_tab(UnderlayTab::default())
+            .add_editor_tab(View3dTab)
+            .add_systems(
+                PostUpdate,
+                (show_ui_system, update_camera_viewport)
+                    .chain()
+                    .before(EguiSet::ProcessOutput)
+                    .before(bevy::transform::TransformSystem::TransformPropagate),
+            );
+
+        #[cfg(feature = "scripting")]
+        app.add_editor_tab(Console::default());
+    }
+}
+
+/// A tab in the dock.
+///
+/// Tabs are added with [`EditorAppExt::add_editor_tab`].
+pub trait Tab: Send + Sync + 'static {
+    /// The title of the tab.
+    fn title(&self) -> egui::WidgetText;
+
+    /// Shows the tab.
+    fn ui(&mut self, ui: &mut egui::Ui, world: &mut World);
+}
+
+/// Tabs waiting to be put in the dock.
+#[derive(Resource, Default, Deref, DerefMut)]
+pub(crate) struct NewTabs(Vec<Box<dyn Tab>>);
+
+#[derive(Resource)]
+struct UiState {
+    state: DockState<EguiWindow>,
+    /// Where new tabs are put.
+    side: NodeIndex,
+    viewport_rect: egui::Rect,
+    map_info: MapInfo,
+    find_replace: FindReplace,
+    save_as: SaveAs,
+    startup: StartupScreen,
+}
+
+impl UiState {
+    pub fn new() -> Self {
+        let mut state = DockState::new(vec![EguiWindow::View]);
+        let tree = state.main_surface_mut();
+        let [_game, inspector] = tree.split_right(
+            NodeIndex::root(),
+            0.75,
+            vec![EguiWindow::Tab(Box::new(Inspector::default()))],
+        );
+        let [_inspector, side] = tree.split_below(
+            inspector,
+            0.6,
+            vec![EguiWindow::Tab(Box::new(Overview::default()))],
+        );
+
+        Self {
+            state,
+            side,
+            viewport_rect: egui::Rect::NOTHING,
+            map_info: MapInfo::default(),
+            find_replace: FindReplace::default(),
+            save_as: SaveAs::default(),
+            startup: StartupScreen::default(),
+        }
+    }
+
+    fn ui(&mut self, world: &mut World, ctx: &mut egui::Context) {
+        for tab in world.resource_mut::<NewTabs>().drain(..) {
+            let tree = self.state.main_surface_mut();
+
+            // the side may have been moved around
+            if tree[self.side].is_leaf() {
+                tree[self.side].append_tab(EguiWindow::Tab(tab));
+            } else {
+                self.state.push_to_first_leaf(EguiWindow::Tab(tab));
+            }
+        }
+
+        egui::TopBottomPanel::top("menu").show(ctx, |ui| {
+            egui::menu::bar(ui, |ui| {
+                menu_bar(
+                    ui,
+                    world,
+                    &mut self.map_info,
+                    &mut self.find_replace,
+                    &mut self.save_as,
+                )
+            });
+        });
+
+        self.map_info.show(ctx, world);
+        if self.find_replace.open {
+            self.find_replace.show(ctx, world);
+        }
+        self.save_as.show(ctx, world);
+
+        let mut editors = world.query_filtered::<(), With<Editor>>();
+        if editors.iter(world).next().is_none() {
+            self.startup.show(ctx, world);
+        }
+
+        tasks::show_tasks(ctx, world);
+
+        // the view tab sets this if it is hovered
+        world.resource_mut::<Cursor>().hovered = false;
+
+        let mut tab_viewer = TabViewer {
+            world,
+            viewport_rect: &mut self.viewport_rect,
+        };
+        DockArea::new(&mut self.state)
+            .style(Style::from_egui(ctx.style().as_ref()))
+            .show(ctx, &mut tab_viewer);
+    }
+}
+
+/// Shows the menu bar.
+fn menu_bar(
+    ui: &mut egui::Ui,
+    world: &mut World,
+    map_info: &mut MapInfo,
+    find_replace: &mut FindReplace,
+    save_as: &mut SaveAs,
+) {
+    let mut command = None;
+    let mut destructive = None;
+    let mut hovered = None;
+    let mut open = None;
+    let mut save = false;
+
+    ui.menu_button("File", |ui| {
+        let archive = world.get_resource::<Archive>();
+        let has_archive = archive.is_some();
+        let read_only = archive.is_some_and(Archive::is_read_only);
+        let saving = archive.is_some_and(Archive::is_saving);
+
+        let mut response = ui.add_enabled(
+            has_archive && !read_only && !saving,
+            egui::Button::new("Save"),
+        );
+        if read_only {
+            response = response.on_disabled_hover_text("The wad file is read-only, use Save As");
+        } else if saving {
+            response = response.on_disabled_hover_text("The wad file is being saved");
+        }
+        if response.clicked() {
+            save = true;
+            ui.close_menu();
+        }
+        if ui
+            .add_enabled(has_archive && !saving, egui::Button::new("Save As..."))
+            .clicked()
+        {
+            save_as.open = true;
+            ui.close_menu();
+        }
+
+        ui.menu_button("Open recent", |ui| {
+            let session = world.resource::<Session>();
+
+            if session.recent.is_empty() {
+                ui.weak("No recent files");
+            }
+            startup::recent_files(ui, session, &mut open);
+
+            if open.is_some() {
+                ui.close_menu();
+            }
+        });
+    });
+
+    if let Some(path) = open {
+        world.send_event(OpenMap(path));
+    }
+
+    if save {
+        save_archive(world);
+    }
+
+    ui.menu_button("Map", |ui| {
+        if ui.button("Info").clicked() {
+            map_info.open = true;
+            ui.close_menu();
+        }
+    });
+
+    ui.menu_button("Edit", |ui| {
+        world.resource_scope::<History, _>(|world, mut history| {
+            let mut editors = world.query::<&mut Editor>();
+            let Ok(mut editor) = editors.get_single_mut(world) else {
+                return;
+            };
+
+            if ui
+                .add_enabled(history.can_undo(), egui::Button::new("Undo"))
+                .clicked()
+            {
+                history.undo(&mut editor);
+                ui.close_menu();
+            }
+            if ui
+                .add_enabled(history.can_redo(), egui::Button::new("Redo"))
+                .clicked()
+            {
+                history.redo(&mut editor);
+                ui.close_menu();
+            }
+
+            ui.separator();
+        });
+
+        if ui.button("Find and replace").clicked() {
+            find_replace.open = true;
+            ui.close_menu();
+        }
+
+        let mut editors = world.query::<&Selection>();
+        let has_things = editors
+            .get_single(world)
+            .is_ok_and(|selection| !selection.things.is_empty());
+        if ui
+            .add_enabled(has_things, egui::Button::new("Face point"))
+            .on_hover_text("Turn the selected things to face the next click")
+            .clicked()
+        {
+            world.resource_mut::<ThingAngles>().facing = true;
+            ui.close_menu();
+        }
+
+        ui.separator();
+
+        for map_command in world.resource::<MapCommands>().iter() {
+            if ui.button(map_command.name.as_ref()).clicked() {
+                command = Some(map_command.name.to_string());
+                ui.close_menu();
+            }
+        }
+
+        ui.separator();
+
+        // the map is previewed while these are hovered, and changed on click
+        let preview = world.resource::<OperationPreview>();
+        for operation in Destructive::ALL {
+            let response = ui.button(operation.name());
+            if response.hovered() {
+                hovered = Some(operation);
+            }
+
+            let impact = preview
+                .impact
+                .as_ref()
+                .filter(|_| preview.operation == Some(operation));
+            let response = response.on_hover_ui(|ui| match impact {
+                Some(impact) => {
+                    ui.label(impact.to_string());
+                }
+                None => {
+                    ui.spinner();
+                }
+            });
+            if response.clicked() {
+                destructive = Some(operation);
+                ui.close_menu();
+            }
+        }
+    });
+
+    if let Some(command) = command {
+        run_map_command(world, &command);
+    }
+
+    if world.resource::<OperationPreview>().operation != hovered {
+        world.resource_mut::<OperationPreview>().operation = hovered;
+    }
+    if let Some(operation) = destructive {
+        run_destructive(world, operation);
+    }
+
+    ui.separator();
+
+    let mut grid = world.resource_mut::<Grid>();
+    let mut size = grid.size;
+    ui.add(
+        egui::DragValue::new(&mut size)
+            .prefix("Grid: ")
+            .clamp_range(1.0..=1024.0),
+    )
+    .on_hover_text("How far the arrow keys move the selection");
+    if size != grid.size {
+        grid.size = size;
+    }
+
+    ui.separator();
+
+    let mut modes = world.resource_mut::<EditModes>();
+    let mut active = modes.active();
+
+    for mode in modes.modes() {
+        ui.selectable_value(&mut active, Some(mode.id), mode.name.as_ref());
+    }
+
+    if let Some(active) = active {
+        if modes.active() != Some(active) {
+            modes.set_active(active);
+        }
+    }
+
+    if world.resource::<ThingAngles>().facing {
+        ui.separator();
+        ui.label("Click where the selected things should face, or press escape");
+    }
+}
+
+enum EguiWindow {
+    /// The main viewport.
+    View,
+    /// Any other tab.
+    Tab(Box<dyn Tab>),
+}
+
+struct TabViewer<'a> {
+    world: &'a mut World,
+    viewport_rect: &'a mut egui::Rect,
+}
+
+impl egui_dock::TabViewer for TabViewer<'_> {
+    type Tab = EguiWindow;
+
+    fn ui(&mut self, ui: &mut egui_dock::egui::Ui, window: &mut Self::Tab) {
+        match window {
+            EguiWindow::View => {
+                *self.viewport_rect = ui.clip_rect();
+                self.world.resource_mut::<Cursor>().hovered =
+                    ui.rect_contains_pointer(ui.clip_rect());
+            }
+            EguiWindow::Tab(tab) => {
+                tab.ui(ui, self.world);
+            }
+        }
+    }
+
+    fn title(&mut self, window: &mut Self::Tab) -> egui::WidgetText {
+        match window {
+            EguiWindow::View => "View".into(),
+            EguiWindow::Tab(tab) => tab.title(),
+        }
+    }
+
+    fn clear_background(&self, window: &Self::Tab) -> bool {
+        !matches!(window, EguiWindow::View)
+    }
+}
+
+/// Starts showing the UI for the frame.
+fn show_ui_system(world: &mut World) {
+    let Ok(egui_context) = world
+        .query_filtered::<&mut EguiContext, With<PrimaryWindow>>()
+        .get_single(world)
+    else {
+        return;
+    };
+    let mut egui_context = egui_context.clone();
+
+    world.resource_scope::<UiState, _>(|world, mut ui_state| {
+        ui_state.ui(world, egui_context.get_mut())
+    });
+}
+
+/// Has the camera only render the visible area.
+fn update_camera_viewport(
+    ui_state: Res<UiState>,
+    primary_window: Query<&mut Window, With<PrimaryWindow>>,
+    egui_settings: Res<bevy_egui::EguiSettings>,
+    mut cameras: Query<&mut Camera, With<EditorCamera>>,
+) {
+    let mut cam = cameras.single_mut();
+
+    let Ok(window) = primary_window.get_single() else {
+        return;
+    };
+
+    let scale_factor = window.scale_factor() * egui_settings.scale_factor;
+
+    let viewport_pos = ui_state.viewport_rect.left_top().to_vec2() * scale_factor;
+    let viewport_size = ui_state.viewport_rect.size() * scale_factor;
+
+    cam.viewport = Some(Viewport {
+        physical_position: UVec2::new(viewport_pos.x as u32, viewport_pos.y as u32),
+        physical_size: UVec2::new(viewport_size.x as u32, viewport_size.y as u32),
+        depth: 0.0..1.0,
+    });
+}
@@ -0,0 +1,96 @@
+//! Nodes tab.
+
+use bevy::prelude::*;
+
+use crate::editor::nodes::NodesOverlay;
+use crate::editor::Editor;
+
+use super::Tab;
+
+/// Toggles the [`NodesOverlay`], to see what the nodebuilder did.
+pub struct NodesTab;
+
+impl Tab for NodesTab {
+    fn title(&self) -> egui::WidgetText {
+        "Nodes".into()
+    }
+
+    fn ui(&mut self, ui: &mut egui::Ui, world: &mut World) {
+        let mut editors = world.query::<Ref<Editor>>();
+        let Ok(editor) = editors.get_single(world) else {
+            ui.label("No map loaded.");
+            return;
+        };
+
+        let overlay = world.resource::<NodesOverlay>();
+        let outdated = overlay.is_outdated(&editor);
+
+        let mut show_subsectors = overlay.show_subsectors;
+        let mut show_splits = overlay.show_splits;
+        let mut show_blockmap = overlay.show_blockmap;
+        let mut depth = overlay.depth;
+
+        match &overlay.nodes {
+            None => {
+                ui.label("The map has no ZNODES lump. Build nodes to see them here.");
+            }
+            Some(Err(err)) => {
+                ui.label(format!("Failed to read nodes: {}", err));
+            }
+            Some(Ok(nodes)) => {
+                ui.label(format!(
+                    "{} nodes, {} subsectors, {} segs",
+                    nodes.nodes.len(),
+                    nodes.subsectors.len(),
+                    nodes.segs.len(),
+                ));
+
+                ui.checkbox(&mut show_subsectors, "Subsectors");
+                ui.horizontal(|ui| {
+                    ui.checkbox(&mut show_splits, "Splits");
+                    ui.add_enabled(
+                        show_splits,
+                        egui::Slider::new(&mut depth, 1..=nodes.depth().max(1)).text("Depth"),
+                    );
+                });
+            }
+        }
+
+        ui.separator();
+
+        match &overlay.blockmap {
+            None => {
+                ui.label("The map has no BLOCKMAP lump.");
+            }
+            Some(Err(err)) => {
+                ui.label(format!("Failed to read blockmap: {}", err));
+            }
+            Some(Ok(blockmap)) => {
+                ui.checkbox(
+                    &mut show_blockmap,
+                    format!("Blockmap ({} by {})", blockmap.columns, blockmap.rows),
+                );
+            }
+        }
+
+        if outdated && overlay.is_shown() {
+            ui.separator();
+            ui.weak("The map changed since the nodes were built.");
+        }
+
+        if (show_subsectors, show_splits, show_blockmap, depth)
+            != (
+                overlay.show_subsectors,
+                overlay.show_splits,
+                overlay.show_blockmap,
+                overlay.depth,
+            )
+        {
+            let mut overlay = world.resource_mut::<NodesOverlay>();
+            overlay.show_subsectors = show_subsectors;
+            overlay.show_splits = show_splits;
+            overlay.show_blockmap = show_blockmap;
+            overlay.depth = depth;
+        }
+    }
+}
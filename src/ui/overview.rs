@@ -0,0 +1,136 @@
+//! Map overview tab.
+
+use std::collections::BTreeSet;
+
+use bevy::ecs::component::Tick;
+use bevy::prelude::*;
+
+use egui::{Color32, Pos2, Rect, Sense, Shape, Stroke};
+
+use crate::editor::{Editor, EditorCamera};
+use crate::map::Map;
+
+use super::Tab;
+
+/// How many cells the longest side of the map is snapped to, see
+/// [`Map::simplified_linedefs`].
+const DETAIL: f32 = 256.0;
+
+/// The overview of the whole map.
+///
+/// Shows the extent of the main viewport, which can be dragged around to move
+/// the camera.
+#[derive(Default)]
+pub struct Overview {
+    last_changed: Option<Tick>,
+    /// The bounds of the map, in map units.
+    bounds: Option<Rect>,
+    /// The simplified lines of the map, in map units.
+    lines: Vec<[Pos2; 2]>,
+}
+
+impl Tab for Overview {
+    fn title(&self) -> egui::WidgetText {
+        "Overview".into()
+    }
+
+    fn ui(&mut self, ui: &mut egui::Ui, world: &mut World) {
+        {
+            let mut editors = world.query::<Ref<Editor>>();
+            let Ok(editor) = editors.get_single(world) else {
+                ui.label("No map loaded.");
+                return;
+            };
+
+            if self.last_changed != Some(editor.last_changed()) {
+                self.rebuild(Editor::map(&editor));
+                self.last_changed = Some(editor.last_changed());
+            }
+        }
+
+        let Some(bounds) = self.bounds else {
+            return;
+        };
+
+        // fit map to the tab, keeping the aspect ratio
+        let available = ui.available_rect_before_wrap();
+        let scale = (available.width() / bounds.width()).min(available.height() / bounds.height());
+        let rect = Rect::from_center_size(available.center(), bounds.size() * scale);
+
+        let response = ui.allocate_rect(available, Sense::click_and_drag());
+        let painter = ui.painter_at(available);
+
+        let to_screen = |p: Pos2| {
+            Pos2::new(
+                rect.left() + (p.x - bounds.min.x) * scale,
+                rect.bottom() - (p.y - bounds.min.y) * scale,
+            )
+        };
+        let from_screen = |p: Pos2| {
+            Pos2::new(
+                bounds.min.x + (p.x - rect.left()) / scale,
+                bounds.min.y + (rect.bottom() - p.y) / scale,
+            )
+        };
+
+        let stroke = Stroke::new(1.0, Color32::GRAY);
+        painter.extend(
+            self.lines
+                .iter()
+                .map(|&[a, b]| Shape::line_segment([to_screen(a), to_screen(b)], stroke)),
+        );
+
+        // show main viewport
+        let mut cameras = world.query_filtered::<
+            (&mut Transform, &Camera, &OrthographicProjection),
+            With<EditorCamera>,
+        >();
+        let Ok((mut transform, camera, projection)) = cameras.get_single_mut(world) else {
+            return;
+        };
+
+        if response.clicked() || response.dragged() {
+            if let Some(pos) = response.interact_pointer_pos() {
+                let pos = from_screen(pos);
+                transform.translation.x = pos.x;
+                transform.translation.y = pos.y;
+            }
+        }
+
+        let center = to_screen(Pos2::new(transform.translation.x, transform.translation.y));
+        let viewport_size = camera.logical_viewport_size().unwrap_or_default();
+        let extent = egui::vec2(viewport_size.x, viewport_size.y) * projection.scale * scale;
+        painter.rect_stroke(
+            Rect::from_center_size(center, extent),
+            0.0,
+            Stroke::new(1.0, Color32::YELLOW),
+        );
+    }
+}
+
+impl Overview {
+    fn rebuild(&mut self, map: &Map) {
+        let mut vertices = map.vertices.iter().map(|v| Pos2::new(v.x, v.y));
+        let Some(first) = vertices.next() else {
+            self.bounds = None;
+            self.lines.clear();
+            return;
+        };
+        let bounds = vertices.fold(Rect::from_min_max(first, first), |rect, v| {
+            rect.union(Rect::from_min_max(v, v))
+        });
+
+        // avoid dividing by zero on degenerate maps
+        self.bounds = Some(bounds.expand(1.0));
+        self.lines = map
+            .simplified_linedefs(DETAIL, &BTreeSet::new())
+            .into_iter()
+            .map(|segment| {
+                [
+                    Pos2::new(segment.a.x, segment.a.y),
+                    Pos2::new(segment.b.x, segment.b.y),
+                ]
+            })
+            .collect();
+    }
+}
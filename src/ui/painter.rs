@@ -0,0 +1,67 @@
+//! Property painter tab.
+
+use std::collections::BTreeSet;
+
+use bevy::prelude::*;
+
+use crate::editor::paint::{self, Field, Painter, Source};
+use crate::editor::EditModes;
+
+use super::Tab;
+
+/// Chooses what the paint mode copies.
+#[derive(Default)]
+pub struct PainterTab;
+
+impl Tab for PainterTab {
+    fn title(&self) -> egui::WidgetText {
+        "Painter".into()
+    }
+
+    fn ui(&mut self, ui: &mut egui::Ui, world: &mut World) {
+        if world.resource::<EditModes>().active() != Some(paint::MODE) {
+            ui.label("Switch to paint mode to use the painter.");
+        }
+
+        let mut painter = world.resource_mut::<Painter>();
+
+        match painter.source {
+            Some(Source::Sector(idx)) => ui.label(format!("Painting from sector {}", idx)),
+            Some(Source::SideDef(idx)) => ui.label(format!("Painting from sidedef {}", idx)),
+            None => ui.label("Click a sector or side to pick it up."),
+        };
+        ui.weak("Shift-click to pick up another.");
+
+        ui.separator();
+
+        let mut fields = painter.fields.clone();
+
+        ui.columns(2, |columns| {
+            columns[0].strong("Sectors");
+            for field in Field::SECTOR {
+                checkbox(&mut columns[0], &mut fields, field);
+            }
+
+            columns[1].strong("Sides");
+            for field in Field::SIDEDEF {
+                checkbox(&mut columns[1], &mut fields, field);
+            }
+        });
+
+        if fields != painter.fields {
+            painter.fields = fields;
+        }
+    }
+}
+
+fn checkbox(ui: &mut egui::Ui, fields: &mut BTreeSet<Field>, field: Field) {
+    let mut checked = fields.contains(&field);
+
+    if ui.checkbox(&mut checked, field.name()).changed() {
+        if checked {
+            fields.insert(field);
+        } else {
+            fields.remove(&field);
+        }
+    }
+}
@@ -0,0 +1,167 @@
+//! Prefab library tab.
+
+use bevy::prelude::*;
+
+use egui::{Color32, Pos2, Rect, Sense, Stroke, Vec2 as EguiVec2};
+
+use crate::editor::prefab::PrefabLibrary;
+use crate::editor::{Editor, EditorCamera, Selection};
+use crate::map::Map;
+
+use super::Tab;
+
+/// How big thumbnails are, in points.
+const THUMBNAIL_SIZE: f32 = 96.0;
+
+/// Browses the [`PrefabLibrary`].
+#[derive(Default)]
+pub struct Prefabs {
+    name: String,
+    status: Option<String>,
+}
+
+impl Tab for Prefabs {
+    fn title(&self) -> egui::WidgetText {
+        "Prefabs".into()
+    }
+
+    fn ui(&mut self, ui: &mut egui::Ui, world: &mut World) {
+        ui.horizontal(|ui| {
+            ui.text_edit_singleline(&mut self.name);
+
+            if ui.button("Save selection").clicked() {
+                self.save(world);
+            }
+
+            if ui.button("Reload").clicked() {
+                self.status = world
+                    .resource_mut::<PrefabLibrary>()
+                    .reload()
+                    .err()
+                    .map(|err| err.to_string());
+            }
+        });
+
+        if let Some(status) = &self.status {
+            ui.colored_label(Color32::RED, status);
+        }
+
+        ui.separator();
+
+        let mut stamp = None;
+
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            ui.horizontal_wrapped(|ui| {
+                let library = world.resource::<PrefabLibrary>();
+
+                for prefab in library.prefabs() {
+                    ui.vertical(|ui| {
+                        ui.set_width(THUMBNAIL_SIZE);
+
+                        let response =
+                            thumbnail(ui, &prefab.map).on_hover_text("Click to stamp into the map");
+                        ui.label(&prefab.name);
+
+                        if response.clicked() {
+                            stamp = Some(prefab.name.clone());
+                        }
+                    });
+                }
+            });
+        });
+
+        if let Some(name) = stamp {
+            self.stamp(world, &name);
+        }
+    }
+}
+
+impl Prefabs {
+    fn save(&mut self, world: &mut World) {
+        let mut editors = world.query::<(&Editor, &Selection)>();
+        let Ok((editor, selection)) = editors.get_single(world) else {
+            return;
+        };
+
+        if selection.is_empty() {
+            self.status = Some("nothing selected".into());
+            return;
+        }
+
+        let map = editor.map().clone();
+        let selection = selection.0.clone();
+
+        self.status = world
+            .resource_mut::<PrefabLibrary>()
+            .save(&self.name, &map, &selection)
+            .err()
+            .map(|err| err.to_string());
+    }
+
+    fn stamp(&mut self, world: &mut World, name: &str) {
+        let Some(prefab) = world.resource::<PrefabLibrary>().prefab(name).cloned() else {
+            return;
+        };
+
+        // stamp in the middle of the view
+        let mut cameras = world.query_filtered::<&Transform, With<EditorCamera>>();
+        let Ok(camera) = cameras.get_single(world) else {
+            return;
+        };
+        let center = camera.translation.truncate().round();
+
+        let mut editors = world.query::<(&mut Editor, &mut Selection)>();
+        let Ok((mut editor, mut selection)) = editors.get_single_mut(world) else {
+            return;
+        };
+
+        selection.0 = editor.map_mut().stamp(&prefab.map, center.x, center.y);
+    }
+}
+
+/// Draws a small picture of a map.
+fn thumbnail(ui: &mut egui::Ui, map: &Map) -> egui::Response {
+    let (rect, response) = ui.allocate_exact_size(EguiVec2::splat(THUMBNAIL_SIZE), Sense::click());
+    let painter = ui.painter_at(rect);
+
+    painter.rect_filled(rect, 2.0, ui.visuals().extreme_bg_color);
+
+    let points = map
+        .vertices
+        .iter()
+        .map(|v| Pos2::new(v.x, v.y))
+        .chain(map.things.iter().map(|t| Pos2::new(t.x, t.y)));
+    let Some(bounds) = points.fold(None, |bounds: Option<Rect>, p| {
+        Some(match bounds {
+            Some(bounds) => bounds.union(Rect::from_min_max(p, p)),
+            None => Rect::from_min_max(p, p),
+        })
+    }) else {
+        return response;
+    };
+
+    let inner = rect.shrink(4.0);
+    let bounds = bounds.expand(1.0);
+    let scale = (inner.width() / bounds.width()).min(inner.height() / bounds.height());
+    let center = bounds.center();
+    let to_screen =
+        |x: f32, y: f32| inner.center() + EguiVec2::new(x - center.x, center.y - y) * scale;
+
+    let stroke = Stroke::new(1.0, Color32::GRAY);
+    for linedef in map.linedefs.iter() {
+        let (Some(v1), Some(v2)) = (
+            map.vertices.get(linedef.v1 as usize),
+            map.vertices.get(linedef.v2 as usize),
+        ) else {
+            continue;
+        };
+
+        painter.line_segment([to_screen(v1.x, v1.y), to_screen(v2.x, v2.y)], stroke);
+    }
+
+    for thing in map.things.iter() {
+        painter.circle_filled(to_screen(thing.x, thing.y), 1.5, Color32::LIGHT_BLUE);
+    }
+
+    response
+}
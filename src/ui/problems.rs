@@ -0,0 +1,160 @@
+//! Problems tab.
+
+use bevy::prelude::*;
+
+use egui::Color32;
+
+use crate::editor::archive::Archive;
+use crate::editor::validate::Validation;
+use crate::editor::{Editor, EditorCamera, Selection};
+use crate::map::validate::{Fix, Severity};
+use crate::map::{self, Map};
+
+use super::Tab;
+
+/// Lists the problems in the map.
+///
+/// The map is checked in the background by [`Validation`].
+#[derive(Default)]
+pub struct Problems;
+
+impl Tab for Problems {
+    fn title(&self) -> egui::WidgetText {
+        "Problems".into()
+    }
+
+    fn ui(&mut self, ui: &mut egui::Ui, world: &mut World) {
+        let mut editors = world.query_filtered::<(), With<Editor>>();
+        if editors.get_single(world).is_err() {
+            ui.label("No map loaded.");
+            return;
+        }
+
+        if let Some(archive) = world.get_resource::<Archive>() {
+            if !archive.diagnostics.is_empty() {
+                ui.collapsing(
+                    format!("Skipped {} broken values", archive.diagnostics.len()),
+                    |ui| {
+                        ui.weak("These were left out when the map was read, and are lost on save.");
+                        for diagnostic in archive.diagnostics.iter() {
+                            ui.horizontal(|ui| {
+                                ui.colored_label(Color32::RED, "⛔");
+                                ui.label(diagnostic.to_string());
+                            });
+                        }
+                    },
+                );
+                ui.separator();
+            }
+        }
+
+        let validation = world.resource::<Validation>();
+        let problems = validation.problems();
+
+        if validation.is_checking() {
+            ui.horizontal(|ui| {
+                ui.spinner();
+                ui.label("Checking...");
+            });
+        } else if problems.is_empty() {
+            ui.label("No problems found.");
+            return;
+        }
+
+        let mut show = None;
+        let mut fix = None;
+
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            for problem in problems.iter() {
+                ui.horizontal(|ui| {
+                    let (icon, color) = match problem.severity {
+                        Severity::Error => ("⛔", Color32::RED),
+                        Severity::Warning => ("⚠", Color32::YELLOW),
+                    };
+                    ui.colored_label(color, icon);
+
+                    if ui
+                        .link(&problem.message)
+                        .on_hover_text("Select and show in the view")
+                        .clicked()
+                    {
+                        show = Some(problem.objects.clone());
+                    }
+
+                    if let Some(problem_fix) = &problem.fix {
+                        if ui.small_button(problem_fix.description()).clicked() {
+                            fix = Some(problem_fix.clone());
+                        }
+                    }
+                });
+            }
+        });
+
+        if let Some(objects) = show {
+            show_objects(world, &objects);
+        }
+
+        if let Some(fix) = fix {
+            apply_fix(world, &fix);
+        }
+    }
+}
+
+/// Selects `objects` and moves the camera to them.
+fn show_objects(world: &mut World, objects: &map::Selection) {
+    let mut editors = world.query::<(&Editor, &mut Selection)>();
+    let Ok((editor, mut selection)) = editors.get_single_mut(world) else {
+        return;
+    };
+
+    selection.0 = objects.clone();
+    let center = center_of(editor.map(), objects);
+
+    let Some(center) = center else {
+        return;
+    };
+
+    let mut cameras = world.query_filtered::<&mut Transform, With<EditorCamera>>();
+    if let Ok(mut transform) = cameras.get_single_mut(world) {
+        transform.translation.x = center.x;
+        transform.translation.y = center.y;
+    }
+}
+
+fn apply_fix(world: &mut World, fix: &Fix) {
+    let mut editors = world.query::<&mut Editor>();
+    if let Ok(mut editor) = editors.get_single_mut(world) {
+        fix.apply(editor.map_mut());
+    }
+}
+
+/// The middle of a set of objects.
+fn center_of(map: &Map, objects: &map::Selection) -> Option<Vec2> {
+    let vertex = |idx: i32| map.vertices.get(idx as usize).map(|v| Vec2::new(v.x, v.y));
+
+    let points = objects
+        .things
+        .iter()
+        .filter_map(|&idx| map.things.get(idx))
+        .map(|t| Vec2::new(t.x, t.y))
+        .chain(
+            objects
+                .vertices
+                .iter()
+                .filter_map(|&idx| vertex(idx as i32)),
+        )
+        .chain(
+            objects
+                .linedefs
+                .iter()
+                .filter_map(|&idx| map.linedefs.get(idx))
+                .filter_map(|l| Some((vertex(l.v1)? + vertex(l.v2)?) / 2.0)),
+        )
+        .collect::<Vec<_>>();
+
+    if points.is_empty() {
+        None
+    } else {
+        Some(points.iter().sum::<Vec2>() / points.len() as f32)
+    }
+}
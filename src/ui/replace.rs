@@ -0,0 +1,176 @@
+//! Find and replace dialog.
+
+use bevy::ecs::component::Tick;
+use bevy::prelude::*;
+
+use crate::editor::{Editor, Selection};
+use crate::map::{self, replace::Replace};
+
+/// What the dialog is replacing.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+enum Kind {
+    #[default]
+    Texture,
+    ThingType,
+}
+
+/// Replaces textures or thing types across the map.
+#[derive(Default)]
+pub struct FindReplace {
+    pub open: bool,
+    kind: Kind,
+    from: String,
+    to: String,
+    within_selection: bool,
+    status: Option<String>,
+    /// The last matches, kept until the search or the map changes.
+    found: Option<(Search, map::Selection)>,
+}
+
+/// What the matches of [`FindReplace`] were found with.
+#[derive(Clone, Debug, PartialEq)]
+struct Search {
+    replace: Replace,
+    /// When the map was last changed.
+    map: Tick,
+    /// When the selection was last changed, if searching within it.
+    selection: Option<Tick>,
+}
+
+impl FindReplace {
+    /// Shows the dialog, if it is open.
+    pub fn show(&mut self, ctx: &egui::Context, world: &mut World) {
+        let mut open = self.open;
+
+        egui::Window::new("Find and Replace")
+            .open(&mut open)
+            .resizable(false)
+            .show(ctx, |ui| self.ui(ui, world));
+
+        self.open = open;
+    }
+
+    fn ui(&mut self, ui: &mut egui::Ui, world: &mut World) {
+        ui.horizontal(|ui| {
+            ui.selectable_value(&mut self.kind, Kind::Texture, "Texture");
+            ui.selectable_value(&mut self.kind, Kind::ThingType, "Thing type");
+        });
+
+        egui::Grid::new("find replace").show(ui, |ui| {
+            ui.label("Find");
+            ui.text_edit_singleline(&mut self.from);
+            ui.end_row();
+
+            ui.label("Replace with");
+            ui.text_edit_singleline(&mut self.to);
+            ui.end_row();
+        });
+
+        ui.checkbox(&mut self.within_selection, "Only in selection");
+
+        let replace = match self.replace() {
+            Ok(replace) => replace,
+            Err(err) => {
+                ui.label(err);
+                return;
+            }
+        };
+
+        let mut editors = world.query::<(Ref<Editor>, Ref<Selection>)>();
+        let Ok((editor, selection)) = editors.get_single(world) else {
+            return;
+        };
+
+        // preview what would change
+        let search = Search {
+            replace: replace.clone(),
+            map: editor.last_changed(),
+            selection: self.within_selection.then(|| selection.last_changed()),
+        };
+        let found = match &self.found {
+            Some((last, found)) if *last == search => found,
+            _ => {
+                let within = self.within_selection.then_some(&selection.0);
+                let found = Editor::map(&editor).find(&replace, within);
+                &self.found.insert((search, found)).1
+            }
+        };
+        ui.label(describe(found));
+
+        let (mut select, mut replace_all) = (false, false);
+        ui.horizontal(|ui| {
+            select = ui
+                .add_enabled(!found.is_empty(), egui::Button::new("Select matches"))
+                .clicked();
+            replace_all = ui
+                .add_enabled(!found.is_empty(), egui::Button::new("Replace all"))
+                .clicked();
+        });
+
+        let mut editors = world.query::<(&mut Editor, &mut Selection)>();
+        let Ok((mut editor, mut selection)) = editors.get_single_mut(world) else {
+            return;
+        };
+
+        if select {
+            if let Some((_, found)) = &self.found {
+                selection.0 = found.clone();
+            }
+        }
+        if replace_all {
+            let within = self.within_selection.then_some(&selection.0);
+            let changed = editor.map_mut().replace(&replace, within);
+            self.status = Some(format!("Changed {}", describe(&changed)));
+        }
+
+        if let Some(status) = &self.status {
+            ui.label(status);
+        }
+    }
+
+    fn replace(&self) -> Result<Replace, &'static str> {
+        if self.from.trim().is_empty() {
+            return Err("Enter something to find.");
+        }
+
+        match self.kind {
+            Kind::Texture => {
+                if self.to.trim().is_empty() {
+                    return Err("Enter a texture to replace with.");
+                }
+
+                // texture names are upper case in the game
+                Ok(Replace::Texture {
+                    from: self.from.trim().to_uppercase(),
+                    to: self.to.trim().to_uppercase(),
+                })
+            }
+            Kind::ThingType => {
+                let (Ok(from), Ok(to)) = (self.from.trim().parse(), self.to.trim().parse()) else {
+                    return Err("Thing types are numbers.");
+                };
+
+                Ok(Replace::ThingType { from, to })
+            }
+        }
+    }
+}
+
+/// Describes how many objects are in a selection.
+fn describe(selection: &map::Selection) -> String {
+    let counts = [
+        (selection.things.len(), "things"),
+        (selection.sidedefs.len(), "sidedefs"),
+        (selection.sectors.len(), "sectors"),
+    ]
+    .into_iter()
+    .filter(|(count, _)| *count > 0)
+    .map(|(count, name)| format!("{} {}", count, name))
+    .collect::<Vec<_>>();
+
+    if counts.is_empty() {
+        "No matches".into()
+    } else {
+        counts.join(", ")
+    }
+}
@@ -0,0 +1,118 @@
+//! Save as dialog.
+
+use std::path::{Path, PathBuf};
+
+use bevy::prelude::*;
+
+use crate::editor::archive::{save_archive_as, Archive};
+use crate::editor::Editor;
+use crate::map::namespace::Namespace;
+
+/// Saves the [`Archive`] to a new file.
+#[derive(Default)]
+pub struct SaveAs {
+    pub open: bool,
+    path: String,
+    /// The namespace to save the map in, if it was picked.
+    namespace: Option<Namespace>,
+    /// The existing file that is asked about overwriting.
+    overwrite: Option<PathBuf>,
+}
+
+impl SaveAs {
+    /// Shows the dialog, if it is open.
+    pub fn show(&mut self, ctx: &egui::Context, world: &mut World) {
+        if !self.open {
+            return;
+        }
+
+        let mut editors = world.query::<&Editor>();
+        let current = editors
+            .get_single(world)
+            .ok()
+            .and_then(|editor| Namespace::from_name(&editor.map().namespace));
+
+        let Some(archive) = world.get_resource::<Archive>() else {
+            self.open = false;
+            return;
+        };
+
+        let mut namespace = self.namespace.or(current).unwrap_or(Namespace::RingRacers);
+        let mut save = None;
+
+        egui::Window::new("Save As")
+            .open(&mut self.open)
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                if archive.is_read_only() {
+                    ui.weak(format!(
+                        "{} is read-only, so it is saved to a new PWAD.",
+                        archive.path().display()
+                    ));
+                }
+
+                ui.horizontal(|ui| {
+                    ui.label("Namespace");
+                    egui::ComboBox::from_id_source("save as namespace")
+                        .selected_text(namespace.name())
+                        .show_ui(ui, |ui| {
+                            for ns in Namespace::ALL {
+                                ui.selectable_value(&mut namespace, ns, ns.name());
+                            }
+                        });
+                })
+                .response
+                .on_hover_text("Fields the namespace doesn't have are warned about when saving");
+
+                ui.horizontal(|ui| {
+                    ui.text_edit_singleline(&mut self.path)
+                        .on_hover_text("Path to the new wad file");
+
+                    if ui
+                        .add_enabled(!self.path.trim().is_empty(), egui::Button::new("Save"))
+                        .clicked()
+                    {
+                        let path = PathBuf::from(self.path.trim());
+                        if path.exists() {
+                            self.overwrite = Some(path);
+                        } else {
+                            save = Some(path);
+                        }
+                    }
+                });
+
+                let Some(path) = &self.overwrite else {
+                    return;
+                };
+                if path.as_path() != Path::new(self.path.trim()) {
+                    self.overwrite = None;
+                    return;
+                }
+
+                ui.colored_label(
+                    ui.visuals().warn_fg_color,
+                    format!("{} already exists.", path.display()),
+                );
+                ui.horizontal(|ui| {
+                    if ui.button("Overwrite").clicked() {
+                        save = self.overwrite.take();
+                    }
+                    if ui.button("Cancel").clicked() {
+                        self.overwrite = None;
+                    }
+                });
+            });
+
+        if Some(namespace) != current {
+            self.namespace = Some(namespace);
+        }
+
+        if let Some(path) = save {
+            save_archive_as(world, path, namespace);
+            self.namespace = None;
+            self.overwrite = None;
+            self.open = false;
+        }
+    }
+}
@@ -0,0 +1,71 @@
+//! Startup screen.
+
+use std::path::PathBuf;
+
+use bevy::prelude::*;
+
+use crate::editor::session::{OpenMap, Session};
+
+/// Opens a map when none is open, from the last session or the recent files.
+#[derive(Default)]
+pub struct StartupScreen {
+    path: String,
+}
+
+impl StartupScreen {
+    /// Shows the screen.
+    pub fn show(&mut self, ctx: &egui::Context, world: &mut World) {
+        let mut open = None;
+        let session = world.resource::<Session>();
+
+        egui::Window::new("rrmap")
+            .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                if let Some(error) = &session.error {
+                    ui.colored_label(ui.visuals().error_fg_color, error);
+                    ui.separator();
+                }
+
+                if let Some(last) = &session.last {
+                    if ui.button("Reopen last session").clicked() {
+                        open = Some(last.path.clone());
+                    }
+                    ui.weak(last.path.display().to_string());
+                    ui.separator();
+                }
+
+                if !session.recent.is_empty() {
+                    ui.strong("Recent files");
+                    recent_files(ui, session, &mut open);
+                    ui.separator();
+                }
+
+                ui.horizontal(|ui| {
+                    ui.text_edit_singleline(&mut self.path)
+                        .on_hover_text("Path to a wad file");
+
+                    if ui
+                        .add_enabled(!self.path.trim().is_empty(), egui::Button::new("Open"))
+                        .clicked()
+                    {
+                        open = Some(PathBuf::from(self.path.trim()));
+                    }
+                });
+            });
+
+        if let Some(path) = open {
+            world.send_event(OpenMap(path));
+        }
+    }
+}
+
+/// Lists the recent files as links, setting `open` to the clicked one.
+pub fn recent_files(ui: &mut egui::Ui, session: &Session, open: &mut Option<PathBuf>) {
+    for path in session.recent.iter() {
+        if ui.link(path.display().to_string()).clicked() {
+            *open = Some(path.clone());
+        }
+    }
+}
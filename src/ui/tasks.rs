@@ -0,0 +1,45 @@
+//! Progress of background tasks.
+
+use bevy::prelude::*;
+
+use crate::editor::tasks::BackgroundTasks;
+
+/// Shows a popup with the progress of the [`BackgroundTasks`], if any are
+/// running.
+pub fn show_tasks(ctx: &egui::Context, world: &mut World) {
+    let tasks = world.resource::<BackgroundTasks>();
+    if tasks.is_empty() {
+        return;
+    }
+
+    let mut cancel = None;
+
+    egui::Window::new("Working")
+        .anchor(egui::Align2::RIGHT_BOTTOM, [-8.0, -8.0])
+        .collapsible(false)
+        .resizable(false)
+        .show(ctx, |ui| {
+            for (idx, task) in tasks.iter().enumerate() {
+                ui.strong(&task.name);
+
+                ui.horizontal(|ui| {
+                    ui.add(
+                        egui::ProgressBar::new(task.progress.fraction())
+                            .desired_width(200.0)
+                            .text(task.progress.message()),
+                    );
+
+                    if ui.button("Cancel").clicked() {
+                        cancel = Some(idx);
+                    }
+                });
+            }
+        });
+
+    // keep the bars moving
+    ctx.request_repaint();
+
+    if let Some(idx) = cancel {
+        world.resource_mut::<BackgroundTasks>().cancel(idx);
+    }
+}
@@ -0,0 +1,70 @@
+//! Things tab.
+
+use std::collections::BTreeMap;
+
+use bevy::ecs::component::Tick;
+use bevy::prelude::*;
+
+use crate::editor::filter::ThingFilter;
+use crate::editor::Editor;
+use crate::map::things::Category;
+
+use super::Tab;
+
+/// Shows how many things there are of each category, to hide them.
+#[derive(Default)]
+pub struct ThingsTab {
+    last_changed: Option<Tick>,
+    counts: BTreeMap<Category, usize>,
+}
+
+impl Tab for ThingsTab {
+    fn title(&self) -> egui::WidgetText {
+        "Things".into()
+    }
+
+    fn ui(&mut self, ui: &mut egui::Ui, world: &mut World) {
+        let mut editors = world.query::<Ref<Editor>>();
+        let Ok(editor) = editors.get_single(world) else {
+            ui.label("No map loaded.");
+            return;
+        };
+
+        if self.last_changed != Some(editor.last_changed()) {
+            self.counts = Editor::map(&editor).category_counts();
+            self.last_changed = Some(editor.last_changed());
+        }
+
+        let mut filter = world.resource_mut::<ThingFilter>();
+
+        let mut state = filter.clone();
+
+        ui.checkbox(&mut state.colored, "Color by category");
+        ui.separator();
+
+        egui::Grid::new("thing categories")
+            .striped(true)
+            .show(ui, |ui| {
+                for category in Category::ALL {
+                    let mut shown = !state.hidden.contains(&category);
+
+                    if ui.checkbox(&mut shown, category.to_string()).changed() {
+                        if shown {
+                            state.hidden.remove(&category);
+                        } else {
+                            state.hidden.insert(category);
+                        }
+                    }
+
+                    let color = state.color(category).as_rgba_u8();
+                    ui.colored_label(
+                        egui::Color32::from_rgb(color[0], color[1], color[2]),
+                        self.counts.get(&category).unwrap_or(&0).to_string(),
+                    );
+                    ui.end_row();
+                }
+            });
+
+        filter.set_if_neq(state);
+    }
+}
@@ -0,0 +1,104 @@
+//! Underlay tab.
+
+use std::path::PathBuf;
+
+use bevy::prelude::*;
+
+use crate::editor::session::MapFile;
+use crate::editor::underlay::{Underlay, UnderlaySettings};
+
+use super::Tab;
+
+/// Picks the [`Underlay`] drawn under the map, and where it is drawn.
+#[derive(Default)]
+pub struct UnderlayTab {
+    path: String,
+}
+
+impl Tab for UnderlayTab {
+    fn title(&self) -> egui::WidgetText {
+        "Underlay".into()
+    }
+
+    fn ui(&mut self, ui: &mut egui::Ui, world: &mut World) {
+        let mut files = world.query::<&MapFile>();
+        let Ok(MapFile(map)) = files.get_single(world) else {
+            ui.label("No map loaded.");
+            return;
+        };
+        let map = map.clone();
+
+        let underlay = world.resource::<Underlay>();
+        let mut settings = underlay.settings.clone();
+
+        ui.horizontal(|ui| {
+            ui.text_edit_singleline(&mut self.path)
+                .on_hover_text("A wad file to draw the first map of, or an image");
+
+            if ui
+                .add_enabled(!self.path.trim().is_empty(), egui::Button::new("Load"))
+                .clicked()
+            {
+                let path = PathBuf::from(self.path.trim());
+
+                // keep where the old underlay was put
+                settings = Some(match settings.take() {
+                    Some(old) => UnderlaySettings { path, ..old },
+                    None => UnderlaySettings::new(&map, path),
+                });
+            }
+        });
+
+        if let Some(current) = &mut settings {
+            ui.horizontal(|ui| {
+                ui.label(current.path.display().to_string());
+                if underlay.is_loading() {
+                    ui.spinner();
+                }
+            });
+            if let Some(error) = &underlay.error {
+                ui.colored_label(ui.visuals().error_fg_color, error);
+            }
+
+            ui.separator();
+
+            egui::Grid::new("underlay").show(ui, |ui| {
+                ui.label("Position");
+                ui.horizontal(|ui| {
+                    ui.add(egui::DragValue::new(&mut current.x).prefix("x: "));
+                    ui.add(egui::DragValue::new(&mut current.y).prefix("y: "));
+                });
+                ui.end_row();
+
+                ui.label("Scale");
+                ui.add(
+                    egui::DragValue::new(&mut current.scale)
+                        .speed(0.01)
+                        .clamp_range(0.01..=100.0),
+                )
+                .on_hover_text(if current.is_map() {
+                    "How much the map is scaled"
+                } else {
+                    "Map units per pixel"
+                });
+                ui.end_row();
+
+                ui.label("Opacity");
+                ui.add(egui::Slider::new(&mut current.opacity, 0.0..=1.0));
+                ui.end_row();
+            });
+
+            ui.separator();
+
+            if ui.button("Remove underlay").clicked() {
+                settings = None;
+            }
+        } else {
+            ui.weak("Nothing is drawn under the map.");
+        }
+
+        if settings != world.resource::<Underlay>().settings {
+            world.resource_mut::<Underlay>().settings = settings;
+        }
+    }
+}
@@ -0,0 +1,42 @@
+//! 3D view tab.
+
+use bevy::prelude::*;
+use bevy_egui::EguiUserTextures;
+
+use crate::editor::view3d::View3d;
+use crate::editor::Editor;
+
+use super::Tab;
+
+/// Shows the [`View3d`] of the map.
+///
+/// The camera is pointed at the selected wall, and the walls update as their
+/// offsets are nudged.
+pub struct View3dTab;
+
+impl Tab for View3dTab {
+    fn title(&self) -> egui::WidgetText {
+        "3D View".into()
+    }
+
+    fn ui(&mut self, ui: &mut egui::Ui, world: &mut World) {
+        let mut editors = world.query::<&Editor>();
+        if editors.get_single(world).is_err() {
+            ui.label("No map loaded.");
+            return;
+        }
+
+        let image = world.resource::<View3d>().image.clone();
+        let texture = world.resource_mut::<EguiUserTextures>().add_image(image);
+
+        let size = ui.available_size().floor().max(egui::Vec2::splat(1.0));
+        let pixels = size * ui.ctx().pixels_per_point();
+
+        let mut view = world.resource_mut::<View3d>();
+        view.shown = true;
+        view.size = UVec2::new(pixels.x as u32, pixels.y as u32);
+
+        ui.image(egui::load::SizedTexture::new(texture, size))
+            .on_hover_text("Select a wall to look at it, and nudge its offsets with alt");
+    }
+}